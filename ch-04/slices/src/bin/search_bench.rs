@@ -0,0 +1,56 @@
+// Rough benchmark: every Searcher against plain str::find().
+// ? cargo run --release --bin search_bench
+//
+// it's not a rigorous benchmark (no warmup or statistics), just a sanity check
+// that the fancy algorithms aren't embarrassingly slower than the standard library.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use slices::search::{AhoCorasick, BoyerMoore, Kmp, Searcher, StdFind};
+
+
+const ROUNDS: u32 = 20;
+
+
+fn main() {
+    // a big haystack that only has the needle at the very end:
+    let line = "INFO 2024-01-01T00:00:00 request handled in 12ms by worker-7\n";
+    let mut haystack = line.repeat(50_000);
+    haystack.push_str("ERROR connection reset by peer\n");
+
+    for needle in ["ERROR", "connection reset by peer"] {
+        println!("Needle: {needle:?} ({} MB haystack)", haystack.len() / 1_000_000);
+
+        report("str::find",   time(|| haystack.find(needle).is_some()));
+        report("StdFind",     time(|| StdFind::new(needle).find(&haystack).is_some()));
+        report("Kmp",         time(|| Kmp::new(needle).find(&haystack).is_some()));
+        report("BoyerMoore",  time(|| BoyerMoore::new(needle).find(&haystack).is_some()));
+        report("AhoCorasick", time(|| AhoCorasick::new([needle]).find(&haystack).is_some()));
+        println!();
+    }
+
+    // many patterns: one Aho–Corasick pass vs. one str::find() per pattern
+    let needles = ["ERROR", "WARN", "panic", "timeout", "reset by peer"];
+    println!("Needles: {needles:?}");
+
+    report("str::find x5", time(|| needles.iter().filter_map(|n| haystack.find(n)).min().is_some()));
+    report("AhoCorasick",  time(|| AhoCorasick::new(needles).find(&haystack).is_some()));
+}
+
+
+// average time per round of a search:
+fn time(mut search: impl FnMut() -> bool) -> Duration {
+    let start = Instant::now();
+
+    for _ in 0..ROUNDS {
+        assert!(black_box(search()), "needle not found!");
+    }
+
+    start.elapsed() / ROUNDS
+}
+
+
+fn report(name: &str, elapsed: Duration) {
+    println!("- {name:<14} {elapsed:>12.3?}");
+}
@@ -0,0 +1,10 @@
+// Slices!
+// Utilities built on top of borrowed slices: &str in, &str out.
+
+pub mod search;
+pub mod slicing;
+pub mod split;
+pub mod words;
+
+#[cfg(test)]
+mod test_support;
@@ -1,15 +1,35 @@
 // Slices!
 
+use slices::search::{AhoCorasick, BoyerMoore, Kmp, Searcher};
+use slices::slicing::{self, StrSliceExt};
+use slices::split::{Remainder, SliceSplitExt, StrSplitExt};
+use slices::words::{self, WordOptions, Words};
+
 
 fn main() {
     let s = String::from("Hello, world!");
 
     // slices are references to a range of indices:
-    let hello = &s[0..5];
-    let world = &s[7..12];
+    // ? let hello = &s[0..5];
+    // ? let world = &s[7..12];
+    // - those ranges are in *bytes*, and they panic if they land in the middle of a char.
+    // - try_slice() returns None instead of panicking:
+    let hello = s.try_slice(0..5).unwrap_or_default();
+    let world = s.try_slice(7..12).unwrap_or_default();
 
     println!("{hello}, {world}!");
 
+    let greeting = "¡Hola, señor! 👩\u{200D}💻🇳🇮";
+
+    // ! &greeting[0..1]  // '¡' is two bytes long, this would panic
+    println!("try_slice(0..1):            {:?}", greeting.try_slice(0..1));
+    println!("slice_clamped(0..1):        {:?}", greeting.slice_clamped(0..1));
+    println!("slice_chars(1..5):          {:?}", greeting.slice_chars(1..5));
+    println!("slice_chars(7..99):         {:?}", greeting.slice_chars(7..99));
+    println!("slice_chars_clamped(7..99): {:?}", greeting.slice_chars_clamped(7..99));
+    println!("slice_graphemes(14..):      {:?}", greeting.slice_graphemes(14..));
+    println!("grapheme count:             {}", slicing::graphemes(greeting).count());
+
     // * range syntax:
     // - [0..2]   = [..2]
     // - [3..len] = [3..]
@@ -20,25 +40,65 @@ fn main() {
     // they're a slice pointing to their position in it:
     // ? let s: &str = "Literal!"
 
-    println!("First word: {}", first_word(&s));
+    println!("First word: {}", words::first_word(&s).unwrap_or(""));
+
+    // the old first_word() walked the bytes and gave up at the first non-ascii letter,
+    // so "café" became "caf" and "don't" became "don". the tokenizer knows better:
+    let sentence = "Don't panic: the café's well-known Ñandú ate 42 crêpes";
+
+    println!("Last word: {}", words::last_word(sentence).unwrap_or(""));
+    println!("Third word: {}", words::nth_word(sentence, 2).unwrap_or(""));
+    println!("Word count: {}", words::word_count(sentence));
+
+    let options = WordOptions { hyphens: true, digits: false, ..WordOptions::default() };
+    for (offset, word) in Words::with_options(sentence, options) {
+        println!("- {offset:>2}: {word}");
+    }
 
     // slices of an array:
     let a = [1, 2, 3, 4, 5];
     let slice = &a[1..3];
 
     assert_eq!(slice, &[2, 3]);
-}
 
-// the function can take a &String *OR* a &str, to maximize usability
-fn first_word(s: &str) -> &str {
-    let bytes = s.as_bytes();
+    // windows, chunks and splits are all slices of the original array too:
+    let windows: Vec<&[i32]> = a.windows_by(3, 2).collect();
+    let chunks: Vec<&[i32]> = a.chunks_by(2, Remainder::First).collect();
+    let pieces: Vec<&[i32]> = a.split_when(|&n| n == 3).collect();
 
-    for (i, &item) in bytes.iter().enumerate() {
-        if item.is_ascii_whitespace() || !item.is_ascii_alphabetic() {
-            return &s[0..i];
-        }
+    assert_eq!(windows, [&[1, 2, 3][..], &[3, 4, 5]]);
+    assert_eq!(chunks, [&[1][..], &[2, 3], &[4, 5]]);
+    assert_eq!(pieces, [&[1, 2][..], &[4, 5]]);
+
+    // * walking a log buffer line by line without copying a single byte:
+    let log = b"INFO start\nWARN disk at 91%\nINFO done";
+    for line in log.split_when(|&b| b == b'\n') {
+        println!("{}", String::from_utf8_lossy(line));
+    }
+
+    let text = "añejo jalapeño piñata";
+    let trigrams: Vec<&str> = text.char_windows(3, 3).take(3).collect();
+    let chunks: Vec<&str> = text.char_chunks(8, Remainder::Drop).collect();
+    let (head, tail) = text.split_at_word_boundary(9);
+
+    println!("Trigrams: {trigrams:?}");
+    println!("Chunks:   {chunks:?}");
+    println!("Split:    {head:?} | {tail:?}");
+    println!("Words:    {:?}", text.split_when(char::is_whitespace).collect::<Vec<_>>());
+
+    // * searching returns slices too, along with where they were found:
+    let haystack = "banana bandana: ananas";
+
+    if let Some(found) = Kmp::new("ana").find(haystack) {
+        println!("First \"ana\" at {}: {}", found.start, found.text);
     }
 
-    // return the whole string if no spaces were found:
-    s
+    let overlapping = BoyerMoore::new("ana").find_all(haystack).count();
+    let disjoint = BoyerMoore::new("ana").find_disjoint(haystack).count();
+    println!("\"ana\": {overlapping} overlapping, {disjoint} disjoint");
+
+    let patterns = ["band", "nana", "ananas"];
+    for found in AhoCorasick::new(patterns).find_all(haystack) {
+        println!("- {:>2}: {} ({})", found.start, found.text, patterns[found.pattern]);
+    }
 }
@@ -0,0 +1,473 @@
+// Substring search that returns slices.
+// Same shape as first_word(): a &str goes in, and what comes out borrows from it.
+//
+// * Searchers:
+// - Kmp:          Knuth–Morris–Pratt, never looks at a haystack byte twice.
+// - BoyerMoore:   compares from the end of the pattern and skips ahead on mismatches,
+//                 usually the fastest for long patterns.
+// - AhoCorasick:  many patterns at once, in a single pass over the haystack.
+//
+// * Every searcher implements the Searcher trait, which gives them all:
+// - find():          the first (leftmost) occurrence.
+// - find_all():      every occurrence, even if they overlap ("aa" in "aaa" => 2 matches).
+// - find_disjoint(): non-overlapping occurrences, scanning left to right ("aa" in "aaa" => 1).
+//
+// if several patterns match at the same position, the longest one wins.
+// empty patterns never match anything.
+//
+// searching works on bytes, but since a valid UTF-8 pattern can only ever match
+// at char boundaries of a valid UTF-8 haystack, slicing the haystack is always safe.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'h> {
+    pub pattern: usize,    // index of the pattern that matched (always 0 for single patterns)
+    pub start:   usize,    // byte offset in the haystack
+    pub text:    &'h str,  // the matching slice of the haystack
+}
+
+impl Match<'_> {
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+}
+
+
+pub trait Searcher {
+    // the leftmost match that starts at or after the byte offset `from`:
+    fn find_at<'h>(&self, haystack: &'h str, from: usize) -> Option<Match<'h>>;
+
+    fn find<'h>(&self, haystack: &'h str) -> Option<Match<'h>> {
+        self.find_at(haystack, 0)
+    }
+
+    fn find_all<'s, 'h>(&'s self, haystack: &'h str) -> Matches<'s, 'h, Self> {
+        Matches { searcher: self, haystack, pos: 0, overlapping: true }
+    }
+
+    fn find_disjoint<'s, 'h>(&'s self, haystack: &'h str) -> Matches<'s, 'h, Self> {
+        Matches { searcher: self, haystack, pos: 0, overlapping: false }
+    }
+}
+
+
+pub struct Matches<'s, 'h, S: ?Sized> {
+    searcher:    &'s S,
+    haystack:    &'h str,
+    pos:         usize,
+    overlapping: bool,
+}
+
+impl<'h, S: Searcher + ?Sized> Iterator for Matches<'_, 'h, S> {
+    type Item = Match<'h>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let found = self.searcher.find_at(self.haystack, self.pos)?;
+
+        // overlapping matches resume right after the start of the last one,
+        // disjoint matches resume after its end:
+        self.pos = match self.overlapping {
+            true  => found.start + 1,
+            false => found.end(),
+        };
+
+        Some(found)
+    }
+}
+
+
+// builds a Match from byte offsets:
+fn slice_match(haystack: &str, pattern: usize, start: usize, len: usize) -> Match<'_> {
+    Match { pattern, start, text: &haystack[start..start + len] }
+}
+
+
+// * Knuth–Morris–Pratt:
+// when a comparison fails partway through the pattern, the part that *did* match
+// is already known. the failure table stores, for every prefix of the pattern,
+// the length of its longest proper prefix that's also a suffix of it,
+// so the search can keep going from there instead of starting over.
+
+#[derive(Debug, Clone)]
+pub struct Kmp {
+    pattern: Vec<u8>,
+    failure: Vec<usize>,
+}
+
+impl Kmp {
+    pub fn new(pattern: &str) -> Self {
+        let pattern = pattern.as_bytes().to_vec();
+        let mut failure = vec![0; pattern.len()];
+        let mut len = 0;
+
+        for i in 1..pattern.len() {
+            while len > 0 && pattern[i] != pattern[len] {
+                len = failure[len - 1];
+            }
+
+            if pattern[i] == pattern[len] {
+                len += 1;
+            }
+
+            failure[i] = len;
+        }
+
+        Self { pattern, failure }
+    }
+}
+
+impl Searcher for Kmp {
+    fn find_at<'h>(&self, haystack: &'h str, from: usize) -> Option<Match<'h>> {
+        let bytes = haystack.as_bytes();
+        let size = self.pattern.len();
+
+        if size == 0 {
+            return None;
+        }
+
+        let mut matched = 0;
+
+        for (i, &byte) in bytes.iter().enumerate().skip(from) {
+            while matched > 0 && byte != self.pattern[matched] {
+                matched = self.failure[matched - 1];
+            }
+
+            if byte == self.pattern[matched] {
+                matched += 1;
+            }
+
+            if matched == size {
+                return Some(slice_match(haystack, 0, i + 1 - size, size));
+            }
+        }
+
+        None
+    }
+}
+
+
+// * Boyer–Moore:
+// the pattern is lined up against the haystack and compared from right to left.
+// on a mismatch, two rules say how far the pattern can safely slide forward,
+// and the bigger shift wins:
+// - bad character:  line up the mismatched haystack byte with its last occurrence in the pattern.
+// - good suffix:    line up the part that already matched with another occurrence of it.
+
+#[derive(Debug, Clone)]
+pub struct BoyerMoore {
+    pattern:     Vec<u8>,
+    last:        [Option<usize>; 256],  // last position of every byte in the pattern
+    good_suffix: Vec<usize>,            // shift for a mismatch at each position
+}
+
+impl BoyerMoore {
+    pub fn new(pattern: &str) -> Self {
+        let pattern = pattern.as_bytes().to_vec();
+        let size = pattern.len();
+
+        let mut last = [None; 256];
+        for (i, &byte) in pattern.iter().enumerate() {
+            last[byte as usize] = Some(i);
+        }
+
+        // border[i] = start of the widest border of the suffix pattern[i..]
+        let mut good_suffix = vec![0; size + 1];
+        let mut border = vec![0; size + 1];
+        let (mut i, mut j) = (size, size + 1);
+        border[i] = j;
+
+        while i > 0 {
+            while j <= size && pattern[i - 1] != pattern[j - 1] {
+                if good_suffix[j] == 0 {
+                    good_suffix[j] = j - i;
+                }
+                j = border[j];
+            }
+
+            i -= 1;
+            j -= 1;
+            border[i] = j;
+        }
+
+        // suffixes that only partially occur at the start of the pattern:
+        j = border[0];
+        for (i, shift) in good_suffix.iter_mut().enumerate() {
+            if *shift == 0 {
+                *shift = j;
+            }
+            if i == j {
+                j = border[j];
+            }
+        }
+
+        Self { pattern, last, good_suffix }
+    }
+}
+
+impl Searcher for BoyerMoore {
+    fn find_at<'h>(&self, haystack: &'h str, from: usize) -> Option<Match<'h>> {
+        let bytes = haystack.as_bytes();
+        let size = self.pattern.len();
+
+        if size == 0 {
+            return None;
+        }
+
+        let mut start = from;
+
+        while start + size <= bytes.len() {
+            let mismatch = (0..size)
+                .rev()
+                .find(|&i| self.pattern[i] != bytes[start + i]);
+
+            let Some(i) = mismatch else {
+                return Some(slice_match(haystack, 0, start, size));
+            };
+
+            let bad_char = match self.last[bytes[start + i] as usize] {
+                Some(last) if last < i => i - last,
+                Some(_)                => 1,
+                None                   => i + 1,
+            };
+
+            start += bad_char.max(self.good_suffix[i + 1]);
+        }
+
+        None
+    }
+}
+
+
+// * Aho–Corasick:
+// all the patterns are stored in a trie (a tree where each edge is a byte).
+// every node also gets a failure link: where to continue from if the next byte
+// doesn't have an edge, pointing to the longest suffix of the current node that's
+// also in the trie. that way, the haystack is read only once no matter how many patterns.
+//
+// the edges are a sorted Vec rather than a HashMap: a node only has a few, and hashing
+// every byte of the haystack made it ~40x slower than str::find(). the root is where the search
+// spends most of its time (and where every failure ends up), so it gets a table with all 256 bytes.
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    edges:   Vec<(u8, usize)>,  // sorted by byte
+    fail:    usize,
+    outputs: Vec<usize>,        // indices of the patterns that end at this node
+}
+
+impl Node {
+    fn edge(&self, byte: u8) -> Option<usize> {
+        self.edges.binary_search_by_key(&byte, |&(b, _)| b).ok().map(|i| self.edges[i].1)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    nodes:   Vec<Node>,
+    root:    [usize; 256],  // the root's edges, 0 (the root itself) for bytes that don't have one
+    lengths: Vec<usize>,
+    longest: usize,
+}
+
+impl AhoCorasick {
+    pub fn new<I, P>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<str>,
+    {
+        let mut nodes = vec![Node::default()];
+        let mut lengths = Vec::new();
+
+        // 1) build the trie:
+        for (index, pattern) in patterns.into_iter().enumerate() {
+            let pattern = pattern.as_ref().as_bytes();
+            let mut node = 0;
+
+            for &byte in pattern {
+                node = match nodes[node].edges.binary_search_by_key(&byte, |&(b, _)| b) {
+                    Ok(i)  => nodes[node].edges[i].1,
+                    Err(i) => {
+                        nodes.push(Node::default());
+                        let next = nodes.len() - 1;
+                        nodes[node].edges.insert(i, (byte, next));
+                        next
+                    },
+                };
+            }
+
+            if !pattern.is_empty() {
+                nodes[node].outputs.push(index);
+            }
+            lengths.push(pattern.len());
+        }
+
+        // 2) breadth-first, so a node's failure link is always ready before its children need it:
+        let mut queue: Vec<usize> = nodes[0].edges.iter().map(|&(_, next)| next).collect();
+        let mut head = 0;
+
+        while let Some(&node) = queue.get(head) {
+            head += 1;
+
+            for (byte, child) in nodes[node].edges.clone() {
+                let mut fail = nodes[node].fail;
+                while fail != 0 && nodes[fail].edge(byte).is_none() {
+                    fail = nodes[fail].fail;
+                }
+
+                let fail = nodes[fail].edge(byte).unwrap_or(0);
+                let inherited = nodes[fail].outputs.clone();
+
+                nodes[child].fail = fail;
+                nodes[child].outputs.extend(inherited);
+                queue.push(child);
+            }
+        }
+
+        let mut root = [0; 256];
+        for &(byte, next) in &nodes[0].edges {
+            root[byte as usize] = next;
+        }
+
+        let longest = lengths.iter().copied().max().unwrap_or(0);
+        Self { nodes, root, lengths, longest }
+    }
+
+    fn step(&self, mut node: usize, byte: u8) -> usize {
+        while node != 0 {
+            if let Some(next) = self.nodes[node].edge(byte) {
+                return next;
+            }
+            node = self.nodes[node].fail;
+        }
+
+        self.root[byte as usize]
+    }
+}
+
+impl Searcher for AhoCorasick {
+    fn find_at<'h>(&self, haystack: &'h str, from: usize) -> Option<Match<'h>> {
+        let bytes = haystack.as_bytes();
+        let mut node = 0;
+        let mut best: Option<(usize, usize)> = None;  // (start, pattern)
+
+        let mut i = from;
+
+        while i < bytes.len() {
+            // back at the root with nothing found yet: skip ahead to a byte that starts a pattern.
+            // it's the same as stepping through them, but a tight loop over the table is much faster:
+            if node == 0 && best.is_none() {
+                match bytes[i..].iter().position(|&byte| self.root[byte as usize] != 0) {
+                    Some(skip) => i += skip,
+                    None       => break,
+                }
+            }
+
+            // a match that starts further left can't show up anymore, so stop early:
+            if best.is_some_and(|(start, _)| i >= start + self.longest) {
+                break;
+            }
+
+            node = self.step(node, bytes[i]);
+
+            for &pattern in &self.nodes[node].outputs {
+                let len = self.lengths[pattern];
+                let start = i + 1 - len;
+                let better = match best {
+                    None => true,
+                    Some((s, p)) => start < s || (start == s && len > self.lengths[p]),
+                };
+
+                if better {
+                    best = Some((start, pattern));
+                }
+            }
+
+            i += 1;
+        }
+
+        best.map(|(start, pattern)| slice_match(haystack, pattern, start, self.lengths[pattern]))
+    }
+}
+
+
+// * plain str::find() behind the same trait, as the baseline to compare against:
+#[derive(Debug, Clone)]
+pub struct StdFind {
+    pattern: String,
+}
+
+impl StdFind {
+    pub fn new(pattern: &str) -> Self {
+        Self { pattern: pattern.to_string() }
+    }
+}
+
+impl Searcher for StdFind {
+    fn find_at<'h>(&self, haystack: &'h str, from: usize) -> Option<Match<'h>> {
+        if self.pattern.is_empty() {
+            return None;
+        }
+
+        // `from` might be in the middle of a char when resuming an overlapping search:
+        let from = haystack.ceil_char_boundary(from);
+        let start = from + haystack.get(from..)?.find(&self.pattern)?;
+        Some(slice_match(haystack, 0, start, self.pattern.len()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Random;
+
+    // few different chars, so patterns match (and almost match) all the time:
+    const CHARS: &[char] = &['a', 'b', ' ', 'é', '中'];
+
+    // tries every pattern at every position, the slow way:
+    struct Naive(Vec<String>);
+
+    impl Searcher for Naive {
+        fn find_at<'h>(&self, haystack: &'h str, from: usize) -> Option<Match<'h>> {
+            (from..haystack.len()).find_map(|start| {
+                let rest = &haystack.as_bytes()[start..];
+                let matching = self.0.iter().enumerate()
+                    .filter(|(_, p)| !p.is_empty() && rest.starts_with(p.as_bytes()));
+                // the longest one, and the first of those if some are the same length:
+                let (pattern, p) = matching.rev().max_by_key(|(_, p)| p.len())?;
+                Some(slice_match(haystack, pattern, start, p.len()))
+            })
+        }
+    }
+
+    fn all<'h>(searcher: &impl Searcher, haystack: &'h str) -> Vec<Match<'h>> {
+        searcher.find_all(haystack).collect()
+    }
+
+    fn disjoint<'h>(searcher: &impl Searcher, haystack: &'h str) -> Vec<Match<'h>> {
+        searcher.find_disjoint(haystack).collect()
+    }
+
+    #[test]
+    fn every_searcher_agrees_with_the_naive_one() {
+        let mut random = Random(0x9E37_79B9_7F4A_7C15);
+
+        for _ in 0..3_000 {
+            let haystack = random.string(CHARS, 60);
+            let patterns: Vec<String> = (0..1 + random.below(5)).map(|_| random.string(CHARS, 4)).collect();
+            let context = format!("{patterns:?} in {haystack:?}");
+
+            let naive = Naive(patterns.clone());
+            let aho = AhoCorasick::new(&patterns);
+            assert_eq!(all(&aho, &haystack), all(&naive, &haystack), "{context}");
+            assert_eq!(disjoint(&aho, &haystack), disjoint(&naive, &haystack), "{context}");
+
+            // and the single pattern ones, with just the first:
+            let first = &patterns[0];
+            let expected = all(&Naive(vec![first.clone()]), &haystack);
+            assert_eq!(all(&Kmp::new(first), &haystack), expected, "{context}");
+            assert_eq!(all(&BoyerMoore::new(first), &haystack), expected, "{context}");
+            assert_eq!(all(&StdFind::new(first), &haystack), expected, "{context}");
+            assert_eq!(all(&AhoCorasick::new([first]), &haystack), expected, "{context}");
+        }
+    }
+}
@@ -0,0 +1,263 @@
+// Safe slicing for &str.
+// &s[0..5] slices by *bytes*, and panics if either end lands in the middle of a char:
+// ! let s = "héllo";
+// ! let h = &s[0..2];  // 'é' takes up bytes 1..3, so byte 2 isn't a char boundary!
+//
+// * StrSliceExt adds slicing methods to every &str that never panic:
+// - try_slice:        byte range, None if it's out of bounds or not on a char boundary.
+// - slice_chars:      range of chars (unicode scalar values) instead of bytes.
+// - slice_graphemes:  range of graphemes (what a person would call a "character").
+// - *_clamped:        never fail, the range gets snapped to the nearest valid boundaries.
+
+use std::iter;
+use std::ops::{Bound, RangeBounds};
+
+use crate::words::is_mark;
+
+
+pub trait StrSliceExt {
+    fn try_slice<R: RangeBounds<usize>>(&self, range: R) -> Option<&str>;
+    fn slice_chars<R: RangeBounds<usize>>(&self, range: R) -> Option<&str>;
+    fn slice_graphemes<R: RangeBounds<usize>>(&self, range: R) -> Option<&str>;
+
+    fn slice_clamped<R: RangeBounds<usize>>(&self, range: R) -> &str;
+    fn slice_chars_clamped<R: RangeBounds<usize>>(&self, range: R) -> &str;
+    fn slice_graphemes_clamped<R: RangeBounds<usize>>(&self, range: R) -> &str;
+}
+
+impl StrSliceExt for str {
+    fn try_slice<R: RangeBounds<usize>>(&self, range: R) -> Option<&str> {
+        let (start, end) = resolve(&range, self.len(), Some)?;
+        // str::get() already checks bounds *and* char boundaries:
+        self.get(start..end)
+    }
+
+    fn slice_chars<R: RangeBounds<usize>>(&self, range: R) -> Option<&str> {
+        let (start, end) = resolve(&range, self.len(), |n| char_offset(self, n))?;
+        Some(&self[start..end])
+    }
+
+    fn slice_graphemes<R: RangeBounds<usize>>(&self, range: R) -> Option<&str> {
+        let (start, end) = resolve(&range, self.len(), |n| grapheme_offset(self, n))?;
+        Some(&self[start..end])
+    }
+
+    fn slice_clamped<R: RangeBounds<usize>>(&self, range: R) -> &str {
+        // the start snaps backwards and the end snaps forwards,
+        // so any char that's only partially covered by the range is kept whole:
+        let (start, end) = resolve_clamped(&range, self.len(), |n| n);
+        let start = self.floor_char_boundary(start);
+        let end = self.ceil_char_boundary(end);
+
+        &self[start..end]
+    }
+
+    fn slice_chars_clamped<R: RangeBounds<usize>>(&self, range: R) -> &str {
+        let (start, end) = resolve_clamped(&range, self.len(), |n| {
+            char_offset(self, n).unwrap_or(self.len())
+        });
+        &self[start..end]
+    }
+
+    fn slice_graphemes_clamped<R: RangeBounds<usize>>(&self, range: R) -> &str {
+        let (start, end) = resolve_clamped(&range, self.len(), |n| {
+            grapheme_offset(self, n).unwrap_or(self.len())
+        });
+        &self[start..end]
+    }
+}
+
+
+// turns a range of indices (bytes, chars or graphemes) into a pair of byte offsets.
+// `offset` maps an index to its byte offset, returning None if it's out of bounds:
+fn resolve<R, F>(range: &R, len: usize, offset: F) -> Option<(usize, usize)>
+where
+    R: RangeBounds<usize>,
+    F: Fn(usize) -> Option<usize>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(&n) => offset(n)?,
+        Bound::Excluded(&n) => offset(n.checked_add(1)?)?,
+        Bound::Unbounded    => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(&n) => offset(n.checked_add(1)?)?,
+        Bound::Excluded(&n) => offset(n)?,
+        Bound::Unbounded    => len,
+    };
+
+    (start <= end).then_some((start, end))
+}
+
+
+// same as resolve(), but out of bounds indices are clamped instead of rejected,
+// and a backwards range becomes an empty slice at its start:
+fn resolve_clamped<R, F>(range: &R, len: usize, offset: F) -> (usize, usize)
+where
+    R: RangeBounds<usize>,
+    F: Fn(usize) -> usize,
+{
+    let start = match range.start_bound() {
+        Bound::Included(&n) => offset(n),
+        Bound::Excluded(&n) => offset(n.saturating_add(1)),
+        Bound::Unbounded    => 0,
+    }
+    .min(len);
+
+    let end = match range.end_bound() {
+        Bound::Included(&n) => offset(n.saturating_add(1)),
+        Bound::Excluded(&n) => offset(n),
+        Bound::Unbounded    => len,
+    }
+    .clamp(start, len);
+
+    (start, end)
+}
+
+
+// byte offset of the nth char. n == char count is valid: it's the end of the string.
+fn char_offset(s: &str, n: usize) -> Option<usize> {
+    s.char_indices()
+        .map(|(i, _)| i)
+        .chain(iter::once(s.len()))
+        .nth(n)
+}
+
+
+fn grapheme_offset(s: &str, n: usize) -> Option<usize> {
+    graphemes(s)
+        .map(|(i, _)| i)
+        .chain(iter::once(s.len()))
+        .nth(n)
+}
+
+
+// * Graphemes:
+// a single "character" on screen can be made up of several chars:
+// - "e\u{301}"                     => é  (letter + combining accent)
+// - "👍\u{1F3FD}"                  => 👍🏽 (emoji + skin tone modifier)
+// - "👩\u{200D}💻"                 => 👩‍💻 (emojis glued together with a zero width joiner)
+// - "\u{1F1F3}\u{1F1EE}"           => 🇳🇮 (a pair of regional indicators is a flag)
+// this covers the common cases of Unicode's grapheme cluster rules, not every single one.
+
+#[derive(Debug, Clone)]
+pub struct Graphemes<'a> {
+    s:   &'a str,
+    pos: usize,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    // (byte offset, grapheme)
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.pos;
+        let mut chars = self.s[start..].chars().peekable();
+        let first = chars.next()?;
+        let mut end = start + first.len_utf8();
+
+        if first == '\r' && chars.peek() == Some(&'\n') {
+            end += 1;
+        } else if is_regional_indicator(first) && chars.peek().is_some_and(|&c| is_regional_indicator(c)) {
+            end += chars.next().map_or(0, char::len_utf8);
+        } else {
+            while let Some(&c) = chars.peek() {
+                if is_extender(c) {
+                    end += c.len_utf8();
+                    chars.next();
+                } else if c == ZWJ {
+                    // the zero width joiner glues the next char onto this grapheme:
+                    end += c.len_utf8();
+                    chars.next();
+                    end += chars.next().map_or(0, char::len_utf8);
+                } else {
+                    break;
+                }
+            }
+        }
+
+        self.pos = end;
+        Some((start, &self.s[start..end]))
+    }
+}
+
+
+pub fn graphemes(s: &str) -> Graphemes<'_> {
+    Graphemes { s, pos: 0 }
+}
+
+
+const ZWJ: char = '\u{200D}';
+
+
+fn is_extender(c: char) -> bool {
+    is_mark(c) || matches!(c,
+        '\u{FE00}'..='\u{FE0F}'          // variation selectors
+        | '\u{1F3FB}'..='\u{1F3FF}'      // emoji skin tone modifiers
+        | '\u{E0020}'..='\u{E007F}'      // tags (used by some flags)
+        | '\u{E0100}'..='\u{E01EF}'      // variation selectors supplement
+    )
+}
+
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c, '\u{1F1E6}'..='\u{1F1FF}')
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Random;
+
+    // 1, 2, 3 and 4 byte chars, with combining marks, joiners and flags mixed in:
+    const CHARS: &[char] = &[
+        'a', 'Z', ' ', '\r', '\n', 'é', 'ß', '\u{301}', '\u{308}', '中', '\u{200D}', '\u{FE0F}',
+        '👍', '\u{1F3FD}', '💻', '\u{1F1F3}', '\u{1F1EE}', '\u{10FFFF}',
+    ];
+
+    fn chars(s: &str, start: usize, end: usize) -> String {
+        s.chars().skip(start).take(end.saturating_sub(start)).collect()
+    }
+
+    #[test]
+    fn random_ranges_never_panic_and_agree_with_chars() {
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..20_000 {
+            let s = random.string(CHARS, 23);
+            let count = s.chars().count();
+            // sometimes past the end, sometimes backwards:
+            let (a, b) = (random.below(count + 3), random.below(count + 3));
+
+            let expected = (a <= b && b <= count).then(|| chars(&s, a, b));
+            assert_eq!(s.slice_chars(a..b).map(str::to_string), expected, "{s:?} {a}..{b}");
+            assert_eq!(s.slice_chars_clamped(a..b), chars(&s, a, b), "{s:?} {a}..{b}");
+
+            // like str::get(), a..=b is a..b + 1 (so 3..=2 is empty, not backwards):
+            let expected = (a <= b + 1 && b < count).then(|| chars(&s, a, b + 1));
+            assert_eq!(s.slice_chars(a..=b).map(str::to_string), expected, "{s:?} {a}..={b}");
+            assert_eq!(s.slice_chars(a..).map(str::to_string), (a <= count).then(|| chars(&s, a, count)));
+            assert_eq!(s.slice_chars_clamped(..b), chars(&s, 0, b));
+
+            // bytes: the same as str::get(), or snapped outwards to whole chars:
+            let (x, y) = (random.below(s.len() + 3), random.below(s.len() + 3));
+            assert_eq!(s.try_slice(x..y), s.get(x..y), "{s:?} {x}..{y}");
+
+            let clamped = s.slice_clamped(x..y);
+            if let Some(exact) = s.get(x..y) {
+                assert_eq!(clamped, exact);
+            }
+            assert!(s.contains(clamped));
+
+            // graphemes put back together are the whole string, and slicing them doesn't panic:
+            let all: Vec<&str> = graphemes(&s).map(|(_, g)| g).collect();
+            assert_eq!(all.concat(), s);
+
+            let expected = (a <= b && b <= all.len()).then(|| all[a..b].concat());
+            assert_eq!(s.slice_graphemes(a..b).map(str::to_string), expected, "{s:?} {a}..{b}");
+            let _ = s.slice_graphemes_clamped(b..=a);
+        }
+    }
+}
@@ -0,0 +1,384 @@
+// Zero-copy splitting, windows and chunks.
+// Everything here hands out slices *into* the original array or string:
+// nothing is copied and nothing is allocated, so it works just as well on huge buffers.
+//
+// * [T] (arrays, vectors, byte buffers):
+// - windows_by(size, step):   overlapping windows  [1, 2, 3, 4] => [1, 2], [2, 3], [3, 4]
+// - chunks_by(size, policy):  non-overlapping      [1, 2, 3, 4, 5] => [1, 2], [3, 4], [5]
+// - split_when(predicate):    split on elements    [1, 0, 2, 3] => [1], [2, 3]
+//
+// * str (sizes and steps are counted in chars, not bytes, so they never cut a char in half):
+// - char_windows(size, step)
+// - char_chunks(size, policy)
+// - split_when(predicate)
+// - split_at_word_boundary(index)
+
+use crate::words;
+
+
+// what to do with the leftover elements when the length isn't a multiple of the chunk size:
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Remainder {
+    Keep,   // the last chunk is shorter:        [1, 2], [3, 4], [5]
+    Drop,   // the leftovers are skipped:        [1, 2], [3, 4]
+    First,  // the *first* chunk is shorter:     [1], [2, 3], [4, 5]
+}
+
+
+pub trait SliceSplitExt<T> {
+    fn windows_by(&self, size: usize, step: usize) -> Windows<'_, T>;
+    fn chunks_by(&self, size: usize, remainder: Remainder) -> Chunks<'_, T>;
+    fn split_when<F: FnMut(&T) -> bool>(&self, predicate: F) -> SplitWhen<'_, T, F>;
+}
+
+impl<T> SliceSplitExt<T> for [T] {
+    fn windows_by(&self, size: usize, step: usize) -> Windows<'_, T> {
+        // a size or step of 0 would loop forever, that's a bug in the caller:
+        assert!(size > 0 && step > 0, "window size and step must be greater than 0");
+        Windows { slice: self, size, step }
+    }
+
+    fn chunks_by(&self, size: usize, remainder: Remainder) -> Chunks<'_, T> {
+        assert!(size > 0, "chunk size must be greater than 0");
+
+        let leftover = self.len() % size;
+        let (slice, first) = match remainder {
+            Remainder::Keep  => (self, 0),
+            Remainder::Drop  => (&self[..self.len() - leftover], 0),
+            Remainder::First => (self, leftover),
+        };
+
+        Chunks { slice, size, first }
+    }
+
+    fn split_when<F: FnMut(&T) -> bool>(&self, predicate: F) -> SplitWhen<'_, T, F> {
+        SplitWhen { slice: Some(self), predicate }
+    }
+}
+
+
+pub struct Windows<'a, T> {
+    slice: &'a [T],
+    size:  usize,
+    step:  usize,
+}
+
+impl<'a, T> Iterator for Windows<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let window = self.slice.get(..self.size)?;
+        self.slice = self.slice.get(self.step..).unwrap_or_default();
+        Some(window)
+    }
+}
+
+
+pub struct Chunks<'a, T> {
+    slice: &'a [T],
+    size:  usize,
+    first: usize,  // length of a shorter first chunk, if there is one
+}
+
+impl<'a, T> Iterator for Chunks<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.slice.is_empty() {
+            return None;
+        }
+
+        let size = match self.first {
+            0 => self.size,
+            _ => std::mem::take(&mut self.first),
+        };
+
+        let (chunk, rest) = self.slice.split_at(size.min(self.slice.len()));
+        self.slice = rest;
+        Some(chunk)
+    }
+}
+
+
+pub struct SplitWhen<'a, T, F> {
+    slice:     Option<&'a [T]>,  // None once the last piece has been handed out
+    predicate: F,
+}
+
+impl<'a, T, F: FnMut(&T) -> bool> Iterator for SplitWhen<'a, T, F> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slice = self.slice?;
+
+        match slice.iter().position(&mut self.predicate) {
+            Some(i) => {
+                self.slice = Some(&slice[i + 1..]);
+                Some(&slice[..i])
+            },
+            None => self.slice.take(),
+        }
+    }
+}
+
+
+pub trait StrSplitExt {
+    fn char_windows(&self, size: usize, step: usize) -> CharWindows<'_>;
+    fn char_chunks(&self, size: usize, remainder: Remainder) -> CharChunks<'_>;
+    fn split_when<F: FnMut(char) -> bool>(&self, predicate: F) -> StrSplitWhen<'_, F>;
+    fn split_at_word_boundary(&self, index: usize) -> (&str, &str);
+}
+
+impl StrSplitExt for str {
+    fn char_windows(&self, size: usize, step: usize) -> CharWindows<'_> {
+        assert!(size > 0 && step > 0, "window size and step must be greater than 0");
+        CharWindows { s: self, size, step }
+    }
+
+    fn char_chunks(&self, size: usize, remainder: Remainder) -> CharChunks<'_> {
+        assert!(size > 0, "chunk size must be greater than 0");
+
+        // counting chars walks the string once, but doesn't allocate:
+        let count = self.chars().count();
+        let leftover = count % size;
+
+        let (s, first) = match remainder {
+            Remainder::Keep  => (self, 0),
+            Remainder::Drop  => (&self[..char_end(self, count - leftover)], 0),
+            Remainder::First => (self, leftover),
+        };
+
+        CharChunks { s, size, first }
+    }
+
+    fn split_when<F: FnMut(char) -> bool>(&self, predicate: F) -> StrSplitWhen<'_, F> {
+        StrSplitWhen { s: Some(self), predicate }
+    }
+
+    // splits the string in two around a byte index, without cutting a word in half:
+    // - if the index falls inside a word, the split moves back to where that word starts.
+    // - if that word is the very first one, the split moves forward to where it ends instead.
+    // - an index past the end of the string splits at the end.
+    fn split_at_word_boundary(&self, index: usize) -> (&str, &str) {
+        let index = self.floor_char_boundary(index.min(self.len()));
+
+        let split = words::words(self)
+            .find(|&(start, word)| start < index && index < start + word.len())
+            .map_or(index, |(start, word)| match start {
+                0 => word.len(),
+                _ => start,
+            });
+
+        self.split_at(split)
+    }
+}
+
+
+// byte offset where the first n chars end:
+fn char_end(s: &str, n: usize) -> usize {
+    s.char_indices().nth(n).map_or(s.len(), |(i, _)| i)
+}
+
+
+pub struct CharWindows<'a> {
+    s:    &'a str,
+    size: usize,
+    step: usize,
+}
+
+impl<'a> Iterator for CharWindows<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // if the string doesn't have enough chars left, there's no more windows:
+        let mut ends = self.s.char_indices().map(|(i, c)| i + c.len_utf8());
+        let end = ends.nth(self.size - 1)?;
+
+        let window = &self.s[..end];
+        self.s = &self.s[char_end(self.s, self.step)..];
+        Some(window)
+    }
+}
+
+
+pub struct CharChunks<'a> {
+    s:     &'a str,
+    size:  usize,
+    first: usize,
+}
+
+impl<'a> Iterator for CharChunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.s.is_empty() {
+            return None;
+        }
+
+        let size = match self.first {
+            0 => self.size,
+            _ => std::mem::take(&mut self.first),
+        };
+
+        let (chunk, rest) = self.s.split_at(char_end(self.s, size));
+        self.s = rest;
+        Some(chunk)
+    }
+}
+
+
+pub struct StrSplitWhen<'a, F> {
+    s:         Option<&'a str>,
+    predicate: F,
+}
+
+impl<'a, F: FnMut(char) -> bool> Iterator for StrSplitWhen<'a, F> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let s = self.s?;
+
+        match s.char_indices().find(|&(_, c)| (self.predicate)(c)) {
+            Some((i, c)) => {
+                self.s = Some(&s[i + c.len_utf8()..]);
+                Some(&s[..i])
+            },
+            None => self.s.take(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY: [i32; 0] = [];
+
+    fn windows(slice: &[i32], size: usize, step: usize) -> Vec<&[i32]> {
+        slice.windows_by(size, step).collect()
+    }
+
+    fn chunks(slice: &[i32], size: usize, remainder: Remainder) -> Vec<&[i32]> {
+        slice.chunks_by(size, remainder).collect()
+    }
+
+    #[test]
+    fn slice_windows() {
+        let v = [1, 2, 3, 4, 5];
+
+        assert_eq!(windows(&v, 2, 1), [&[1, 2][..], &[2, 3], &[3, 4], &[4, 5]]);
+        assert_eq!(windows(&v, 2, 3), [&[1, 2][..], &[4, 5]]);
+        assert_eq!(windows(&v, 2, 2), [&[1, 2][..], &[3, 4]]);  // [5] isn't a whole window
+        assert_eq!(windows(&v, 5, 1), [&v[..]]);
+        assert!(windows(&v, 6, 1).is_empty());
+        assert!(windows(&EMPTY, 1, 1).is_empty());
+    }
+
+    #[test]
+    fn slice_chunks() {
+        let v = [1, 2, 3, 4, 5];
+
+        assert_eq!(chunks(&v, 2, Remainder::Keep),  [&[1, 2][..], &[3, 4], &[5]]);
+        assert_eq!(chunks(&v, 2, Remainder::Drop),  [&[1, 2][..], &[3, 4]]);
+        assert_eq!(chunks(&v, 2, Remainder::First), [&[1][..], &[2, 3], &[4, 5]]);
+
+        // bigger than the whole slice:
+        assert_eq!(chunks(&v, 9, Remainder::Keep),  [&v[..]]);
+        assert!(chunks(&v, 9, Remainder::Drop).is_empty());
+        assert_eq!(chunks(&v, 9, Remainder::First), [&v[..]]);
+
+        // nothing left over:
+        assert_eq!(chunks(&v[..4], 2, Remainder::First), [&[1, 2][..], &[3, 4]]);
+
+        for remainder in [Remainder::Keep, Remainder::Drop, Remainder::First] {
+            assert!(chunks(&EMPTY, 3, remainder).is_empty());
+        }
+    }
+
+    #[test]
+    fn slice_split_when() {
+        let split = |slice: &[i32]| slice.split_when(|&n| n == 0).map(<[i32]>::to_vec).collect::<Vec<_>>();
+
+        assert_eq!(split(&[1, 0, 2, 3]), [vec![1], vec![2, 3]]);
+        // separators at the ends and next to each other leave empty pieces, like str::split:
+        assert_eq!(split(&[1, 0]), [vec![1], vec![]]);
+        assert_eq!(split(&[0, 1]), [vec![], vec![1]]);
+        assert_eq!(split(&[1, 0, 0, 2]), [vec![1], vec![], vec![2]]);
+        assert_eq!(split(&[0]), [vec![], vec![]]);
+        assert_eq!(split(&EMPTY), [vec![]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "window size and step must be greater than 0")]
+    fn windows_of_size_0() {
+        [1, 2, 3].windows_by(0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "window size and step must be greater than 0")]
+    fn windows_with_a_step_of_0() {
+        "abc".char_windows(1, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be greater than 0")]
+    fn chunks_of_size_0() {
+        [1, 2, 3].chunks_by(0, Remainder::Keep);
+    }
+
+    #[test]
+    fn str_windows_and_chunks_count_chars() {
+        let s = "héllo";
+
+        assert_eq!(s.char_windows(2, 1).collect::<Vec<_>>(), ["hé", "él", "ll", "lo"]);
+        assert_eq!(s.char_windows(2, 2).collect::<Vec<_>>(), ["hé", "ll"]);
+        assert_eq!(s.char_windows(5, 1).collect::<Vec<_>>(), [s]);
+        assert_eq!(s.char_windows(6, 1).count(), 0);
+        assert_eq!("".char_windows(1, 1).count(), 0);
+
+        assert_eq!(s.char_chunks(2, Remainder::Keep).collect::<Vec<_>>(),  ["hé", "ll", "o"]);
+        assert_eq!(s.char_chunks(2, Remainder::Drop).collect::<Vec<_>>(),  ["hé", "ll"]);
+        assert_eq!(s.char_chunks(2, Remainder::First).collect::<Vec<_>>(), ["h", "él", "lo"]);
+        assert_eq!(s.char_chunks(9, Remainder::Keep).collect::<Vec<_>>(),  [s]);
+        assert_eq!(s.char_chunks(9, Remainder::Drop).count(), 0);
+        assert_eq!("".char_chunks(2, Remainder::First).count(), 0);
+    }
+
+    #[test]
+    fn str_split_when() {
+        let split = |s: &'static str| s.split_when(|c| c == ',').collect::<Vec<_>>();
+
+        assert_eq!(split("a,b"), ["a", "b"]);
+        assert_eq!(split("a,b,"), ["a", "b", ""]);
+        assert_eq!(split(",a"), ["", "a"]);
+        assert_eq!(split("a,,b"), ["a", "", "b"]);
+        assert_eq!(split(","), ["", ""]);
+        assert_eq!(split(""), [""]);
+
+        // the separator can be more than a byte long:
+        assert_eq!("a→b→".split_when(|c| c == '→').collect::<Vec<_>>(), ["a", "b", ""]);
+    }
+
+    #[test]
+    fn split_at_word_boundary() {
+        let s = "hello big world";
+
+        let cases = [
+            (0,   ("", "hello big world")),
+            (3,   ("hello", " big world")),      // inside the first word: moves forward
+            (5,   ("hello", " big world")),
+            (7,   ("hello ", "big world")),      // inside a later word: moves back
+            (10,  ("hello big ", "world")),
+            (15,  ("hello big world", "")),
+            (100, ("hello big world", "")),
+        ];
+
+        for (index, expected) in cases {
+            assert_eq!(s.split_at_word_boundary(index), expected, "{index}");
+        }
+
+        // an index in the middle of a char doesn't panic:
+        assert_eq!("né là".split_at_word_boundary(6), ("né ", "là"));
+    }
+}
@@ -0,0 +1,240 @@
+// Unicode-aware word tokenizer.
+// Iterates over the words of a &str, yielding each one as a borrowed slice
+// together with the byte offset where it starts. No allocations, just slices!
+//
+// * Word boundary rules (a simplified take on Unicode's UAX #29):
+// - a word is a run of alphabetic chars, optionally digits, and combining marks.
+// - apostrophes ("don't") and hyphens ("well-known") only join two word chars,
+//   so a leading/trailing one is *not* part of the word ("'quoted'" => "quoted").
+// - everything else (whitespace, punctuation, symbols) is a boundary.
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordOptions {
+    pub hyphens:     bool,  // "well-known" => one word instead of two
+    pub apostrophes: bool,  // "don't"      => one word instead of two
+    pub digits:      bool,  // "abc123"     => digits are part of words
+}
+
+impl Default for WordOptions {
+    fn default() -> Self {
+        Self {
+            hyphens:     false,
+            apostrophes: true,
+            digits:      true,
+        }
+    }
+}
+
+
+// the tokenizer borrows the string, so every slice it hands out lives as long as 'a:
+#[derive(Debug, Clone)]
+pub struct Words<'a> {
+    s:       &'a str,
+    pos:     usize,
+    options: WordOptions,
+}
+
+impl<'a> Words<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self::with_options(s, WordOptions::default())
+    }
+
+    pub fn with_options(s: &'a str, options: WordOptions) -> Self {
+        Self { s, pos: 0, options }
+    }
+
+    fn is_word_char(&self, c: char) -> bool {
+        c.is_alphabetic() || is_mark(c) || (self.options.digits && c.is_numeric())
+    }
+
+    fn is_joiner(&self, c: char) -> bool {
+        (self.options.apostrophes && is_apostrophe(c)) || (self.options.hyphens && is_hyphen(c))
+    }
+}
+
+impl<'a> Iterator for Words<'a> {
+    // (byte offset, word)
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.s[self.pos..];
+
+        // skip everything up to the first word char.
+        // combining marks can't start a word, they belong to whatever came before:
+        let (skipped, _) = rest
+            .char_indices()
+            .find(|&(_, c)| self.is_word_char(c) && !is_mark(c))?;
+
+        let start = self.pos + skipped;
+        let mut end = start;
+        let mut chars = self.s[start..].char_indices().peekable();
+
+        while let Some((i, c)) = chars.next() {
+            if self.is_word_char(c) {
+                end = start + i + c.len_utf8();
+            } else if self.is_joiner(c) {
+                // a joiner only counts if another word char follows it:
+                match chars.peek() {
+                    Some(&(_, next)) if self.is_word_char(next) && !is_mark(next) => continue,
+                    _ => break,
+                }
+            } else {
+                break;
+            }
+        }
+
+        self.pos = end;
+        Some((start, &self.s[start..end]))
+    }
+}
+
+
+pub fn words(s: &str) -> Words<'_> {
+    Words::new(s)
+}
+
+
+// * helpers:
+// - they all take a &str (so a &String works too) and return slices of it.
+// - if there's no such word, they return None instead of an empty slice.
+
+pub fn first_word(s: &str) -> Option<&str> {
+    words(s).next().map(|(_, word)| word)
+}
+
+
+pub fn last_word(s: &str) -> Option<&str> {
+    words(s).last().map(|(_, word)| word)
+}
+
+
+pub fn nth_word(s: &str, n: usize) -> Option<&str> {
+    words(s).nth(n).map(|(_, word)| word)
+}
+
+
+pub fn word_count(s: &str) -> usize {
+    words(s).count()
+}
+
+
+fn is_apostrophe(c: char) -> bool {
+    matches!(c, '\'' | '\u{2019}' | '\u{02BC}')
+}
+
+
+fn is_hyphen(c: char) -> bool {
+    matches!(c, '-' | '\u{2010}' | '\u{2011}')
+}
+
+
+// combining marks (accents written as separate chars, like "e\u{301}" == "é")
+// should stick to the char before them instead of splitting the word:
+pub(crate) fn is_mark(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'    // combining diacritical marks
+        | '\u{0483}'..='\u{0489}'  // cyrillic
+        | '\u{0591}'..='\u{05BD}'  // hebrew
+        | '\u{0610}'..='\u{061A}'  // arabic
+        | '\u{064B}'..='\u{065F}'
+        | '\u{0900}'..='\u{0903}'  // devanagari
+        | '\u{093A}'..='\u{093C}'
+        | '\u{093E}'..='\u{094F}'
+        | '\u{1AB0}'..='\u{1AFF}'  // combining diacritical marks extended
+        | '\u{1DC0}'..='\u{1DFF}'  // combining diacritical marks supplement
+        | '\u{20D0}'..='\u{20FF}'  // combining marks for symbols
+        | '\u{FE20}'..='\u{FE2F}'  // combining half marks
+    )
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all(s: &str) -> Vec<&str> {
+        words(s).map(|(_, word)| word).collect()
+    }
+
+    fn all_with(s: &str, options: WordOptions) -> Vec<&str> {
+        Words::with_options(s, options).map(|(_, word)| word).collect()
+    }
+
+    #[test]
+    fn apostrophes_join_but_dont_stick() {
+        let cases: [(&str, &[&str]); 7] = [
+            ("don't stop",      &["don't", "stop"]),
+            ("don\u{2019}t",    &["don\u{2019}t"]),  // the curly one
+            ("'quoted'",        &["quoted"]),
+            ("dogs' bones",     &["dogs", "bones"]),
+            ("rock 'n' roll",   &["rock", "n", "roll"]),
+            ("don''t",          &["don", "t"]),     // two in a row don't join
+            ("o'clock's",       &["o'clock's"]),
+        ];
+
+        for (s, expected) in cases {
+            assert_eq!(all(s), expected, "{s:?}");
+        }
+
+        let split = WordOptions { apostrophes: false, ..WordOptions::default() };
+        assert_eq!(all_with("don't", split), ["don", "t"]);
+    }
+
+    #[test]
+    fn hyphens_and_digits_are_options() {
+        let hyphens = WordOptions { hyphens: true, ..WordOptions::default() };
+        let no_digits = WordOptions { digits: false, ..WordOptions::default() };
+
+        assert_eq!(all("well-known"), ["well", "known"]);
+        assert_eq!(all_with("well-known -ish- co\u{2011}op", hyphens), ["well-known", "ish", "co\u{2011}op"]);
+        assert_eq!(all("abc123 456"), ["abc123", "456"]);
+        assert_eq!(all_with("abc123 456", no_digits), ["abc"]);
+    }
+
+    #[test]
+    fn combining_marks_stay_in_the_word() {
+        let cafe = "cafe\u{301} au lait";
+        assert_eq!(all(cafe), ["cafe\u{301}", "au", "lait"]);
+
+        // a mark at the end of a word, before an apostrophe, and one with nothing to combine with:
+        assert_eq!(all("nai\u{308}ve"), ["nai\u{308}ve"]);
+        assert_eq!(all("e\u{301}'e\u{301}"), ["e\u{301}'e\u{301}"]);
+        assert_eq!(all(" \u{301}abc"), ["abc"]);
+        assert_eq!(all("\u{301}\u{308}"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn cjk_runs_are_one_word() {
+        // there are no spaces to split on, and real CJK segmentation needs a dictionary:
+        assert_eq!(all("日本語のテキスト"), ["日本語のテキスト"]);
+        assert_eq!(all("hello 世界, 안녕"), ["hello", "世界", "안녕"]);
+        assert_eq!(all("東京。大阪"), ["東京", "大阪"]);  // the ideographic full stop is punctuation
+    }
+
+    #[test]
+    fn no_words_at_all() {
+        for s in ["", "   ", "...!?", "- ' -", "\u{2019}\u{2019}", "+ = * / 🎉", "\n\t"] {
+            assert_eq!(all(s), Vec::<&str>::new(), "{s:?}");
+            assert_eq!(first_word(s), None);
+            assert_eq!(last_word(s), None);
+            assert_eq!(word_count(s), 0);
+        }
+    }
+
+    #[test]
+    fn offsets_point_at_the_words() {
+        let s = "  «Ça va?» — très bien, merci'";
+
+        let found: Vec<(usize, &str)> = words(s).collect();
+        assert_eq!(found.iter().map(|&(_, w)| w).collect::<Vec<_>>(), ["Ça", "va", "très", "bien", "merci"]);
+        for (start, word) in found {
+            assert_eq!(&s[start..start + word.len()], word);
+        }
+
+        assert_eq!(first_word(s), Some("Ça"));
+        assert_eq!(nth_word(s, 2), Some("très"));
+        assert_eq!(nth_word(s, 5), None);
+        assert_eq!(last_word(s), Some("merci"));
+    }
+}
@@ -0,0 +1,388 @@
+// Builder derive macro!
+// `#[derive(Builder)]` on a struct with named fields generates a `<Name>Builder` for it:
+//
+// ? #[derive(Builder)]
+// ? struct User {
+// ?     username: Username,               // required
+// ?     email: Email,                     // required
+// ?     #[builder(default = true)]
+// ?     active: bool,                     // optional, true unless set
+// ?     #[builder(default)]
+// ?     sign_in_count: u64,               // optional, Default::default() unless set
+// ?     nickname: Option<String>,         // optional, None unless set (the setter takes a String)
+// ? }
+// ?
+// ? let user = User::builder().username(name).email(email).build();
+//
+// * a missing required field is a compile error, not a panic or a Result:
+// every required field is a generic parameter of the builder, which starts out as `()`
+// and becomes the field's type once it's set. build() only exists once none of them are `()`:
+// ! error[E0277]: can't build a `User` without `email`
+//
+// * #[builder(with)] on the struct itself also generates, for deriving from a template:
+// - to_builder(&self):        a builder with every field already set (cloned), to change some of them.
+// - with_<field>(&self, v):   a copy with one field changed, instead of `User { email, ..user1 }`,
+//                             which would *move* the other fields out of user1.
+//
+// there's no syn or quote here, so the struct is parsed straight from the tokens (with the helpers in
+// derive-tokens), and the generated code is written as a string and parsed back into tokens.
+// generic structs aren't supported.
+
+use derive_tokens::{attribute, split_top_level, to_string, visibility};
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+
+/// ```
+/// use builder_derive::Builder;
+///
+/// #[derive(Builder)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+///     #[builder(default = 1.0)]
+///     scale: f64,
+///     name: Option<String>,
+/// }
+///
+/// let point = Point::builder().y(2).x(1).name("origin".to_string()).build();
+/// assert_eq!((point.x, point.y, point.scale, point.name.as_deref()), (1, 2, 1.0, Some("origin")));
+/// ```
+///
+/// a required field that's never set is a compile error:
+/// ```compile_fail,E0277
+/// use builder_derive::Builder;
+///
+/// #[derive(Builder)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// let point = Point::builder().x(1).build();
+/// ```
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive_builder(input: TokenStream) -> TokenStream {
+    // code that doesn't parse is a bug in here, but it's still better as an error at the derive than a panic:
+    let generated = parse(input).and_then(|input| {
+        generate(&input).parse().map_err(|e| format!("#[derive(Builder)] generated invalid code: {e}"))
+    });
+
+    match generated {
+        Ok(tokens)   => tokens,
+        Err(message) => format!("compile_error!({message:?});").parse().expect("compile_error! is valid Rust"),
+    }
+}
+
+
+struct Input {
+    vis:    String,
+    name:   String,
+    with:   bool,  // #[builder(with)]
+    fields: Vec<Field>,
+}
+
+struct Field {
+    name: String,
+    ty:   String,
+    kind: Kind,
+}
+
+enum Kind {
+    Required,
+    Default(Option<String>),  // #[builder(default)] or #[builder(default = <expr>)]
+    Optional(String),         // an Option<T> field, holding T
+}
+
+
+// * parsing:
+// ? #[attributes] pub struct Name { #[attributes] pub field: Type, ... }
+
+fn parse(input: TokenStream) -> Result<Input, String> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut i = 0;
+    let mut with = false;
+
+    while let Some((args, next)) = attribute(&tokens, i, "builder") {
+        for arg in args.into_iter().flatten() {
+            match arg.as_str() {
+                "with" => with = true,
+                other  => return Err(format!("unknown #[builder] option on a struct: {other:?} (expected `with`)")),
+            }
+        }
+        i = next;
+    }
+
+    let (vis, next) = visibility(&tokens, i);
+    i = next;
+
+    match tokens.get(i) {
+        Some(TokenTree::Ident(ident)) if ident.to_string() == "struct" => i += 1,
+        _ => return Err("#[derive(Builder)] only works on structs".to_string()),
+    }
+
+    let name = match tokens.get(i) {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err("expected the struct's name".to_string()),
+    };
+
+    match tokens.get(i + 1) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => Ok(Input {
+            vis,
+            name,
+            with,
+            fields: fields(group.stream())?,
+        }),
+        Some(TokenTree::Punct(p)) if p.as_char() == '<' => {
+            Err(format!("#[derive(Builder)] doesn't support generic structs like `{name}` yet"))
+        },
+        _ => Err(format!("#[derive(Builder)] needs named fields, `{name}` is a tuple or unit struct")),
+    }
+}
+
+
+fn fields(body: TokenStream) -> Result<Vec<Field>, String> {
+    let tokens: Vec<TokenTree> = body.into_iter().collect();
+    let mut fields = Vec::new();
+
+    // a field per piece between the commas (not the ones inside a type like `HashMap<K, V>`):
+    for tokens in split_top_level(&tokens) {
+        let mut i = 0;
+        let mut default = None;
+
+        while let Some((args, next)) = attribute(tokens, i, "builder") {
+            for arg in args.into_iter().flatten() {
+                match arg.split_once('=') {
+                    _ if arg == "default" => default = Some(None),
+                    Some((key, expr)) if key.trim() == "default" => default = Some(Some(expr.trim().to_string())),
+                    _ => return Err(format!("unknown #[builder] option on a field: {arg:?} (expected `default` or `default = <value>`)")),
+                }
+            }
+            i = next;
+        }
+
+        i = visibility(tokens, i).1;
+
+        let name = match tokens.get(i) {
+            Some(TokenTree::Ident(ident)) => ident.to_string(),
+            _ => return Err("expected a field name".to_string()),
+        };
+
+        // the type is everything after the colon:
+        let ty = match tokens.get(i + 1) {
+            Some(TokenTree::Punct(p)) if p.as_char() == ':' => &tokens[i + 2..],
+            _ => return Err(format!("expected `:` after the field `{name}`")),
+        };
+
+        let kind = match (default, option_inner(ty)) {
+            (Some(expr), _)     => Kind::Default(expr),
+            (None, Some(inner)) => Kind::Optional(inner),
+            (None, None)        => Kind::Required,
+        };
+
+        fields.push(Field { name, ty: to_string(ty), kind });
+    }
+
+    Ok(fields)
+}
+
+
+// `Option<T>` (or `std::option::Option<T>`) => Some("T"):
+fn option_inner(ty: &[TokenTree]) -> Option<String> {
+    let open = ty.iter().position(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == '<'))?;
+
+    match (ty.get(open.checked_sub(1)?)?, ty.last()?) {
+        (TokenTree::Ident(name), TokenTree::Punct(close)) if name.to_string() == "Option" && close.as_char() == '>' => {
+            Some(to_string(&ty[open + 1..ty.len() - 1]))
+        },
+        _ => None,
+    }
+}
+
+
+// * generating:
+// for `struct User { username: Username, email: Email, #[builder(default)] active: bool }`:
+// ? pub struct UserBuilder<__Username = (), __Email = ()> { username: __Username, email: __Email, active: Option<bool> }
+// ? impl<__Username, __Email> UserBuilder<__Username, __Email> {
+// ?     pub fn email(self, email: Email) -> UserBuilder<__Username, Email> { ... }
+// ?     pub fn active(mut self, active: bool) -> Self { ... }
+// ? }
+// ? impl<__Username, __Email> UserBuilder<__Username, __Email> {
+// ?     pub fn build(self) -> User where __Username: UserBuilderUsername, __Email: UserBuilderEmail { ... }
+// ? }
+
+fn generate(input: &Input) -> String {
+    let Input { vis, name, fields, .. } = input;
+    let builder = format!("{name}Builder");
+
+    let required: Vec<&Field> = fields.iter().filter(|f| matches!(f.kind, Kind::Required)).collect();
+    let param = |f: &Field| format!("__{}", camel_case(&f.name));
+    let state_trait = |f: &Field| format!("{builder}{}", camel_case(&f.name));
+
+    // the builder's generic parameters, with one of them replaced by something else:
+    let params_with = |replace: Option<(&str, &str)>| {
+        required
+            .iter()
+            .map(|f| match replace {
+                Some((field, with)) if field == f.name => with.to_string(),
+                _ => param(f),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let params = params_with(None);
+
+    let mut out = String::new();
+
+    // the builder:
+    let declared: Vec<String> = required.iter().map(|f| format!("{} = ()", param(f))).collect();
+    let storage: Vec<String> = fields
+        .iter()
+        .map(|f| match &f.kind {
+            Kind::Required    => format!("{}: {}", f.name, param(f)),
+            Kind::Default(_)  => format!("{}: ::core::option::Option<{}>", f.name, f.ty),
+            Kind::Optional(_) => format!("{}: {}", f.name, f.ty),
+        })
+        .collect();
+
+    out += &format!(
+        "#[must_use = \"a builder does nothing until .build() is called\"]\n\
+         {vis} struct {builder}<{}> {{ {} }}\n",
+        declared.join(", "), storage.join(", ")
+    );
+
+    let empty: Vec<String> = fields
+        .iter()
+        .map(|f| match f.kind {
+            Kind::Required => format!("{}: ()", f.name),
+            _              => format!("{}: ::core::option::Option::None", f.name),
+        })
+        .collect();
+
+    out += &format!(
+        "impl {name} {{ {vis} fn builder() -> {builder} {{ {builder} {{ {} }} }} }}\n",
+        empty.join(", ")
+    );
+
+    // the setters:
+    let mut setters = String::new();
+    for field in fields {
+        let Field { name: field_name, ty, .. } = field;
+
+        setters += &match &field.kind {
+            Kind::Required => {
+                let moved: Vec<String> = fields
+                    .iter()
+                    .filter(|f| f.name != *field_name)
+                    .map(|f| format!("{0}: self.{0}", f.name))
+                    .collect();
+
+                format!(
+                    "{vis} fn {field_name}(self, {field_name}: {ty}) -> {builder}<{}> {{ {builder} {{ {field_name}, {} }} }}\n",
+                    params_with(Some((field_name, ty))), moved.join(", ")
+                )
+            },
+            Kind::Default(_) => format!(
+                "{vis} fn {field_name}(mut self, {field_name}: {ty}) -> Self {{ \
+                 self.{field_name} = ::core::option::Option::Some({field_name}); self }}\n"
+            ),
+            Kind::Optional(inner) => format!(
+                "{vis} fn {field_name}(mut self, {field_name}: {inner}) -> Self {{ \
+                 self.{field_name} = ::core::option::Option::Some({field_name}); self }}\n"
+            ),
+        };
+    }
+    out += &format!("impl<{params}> {builder}<{params}> {{ {setters} }}\n");
+
+    // a trait per required field, only implemented once it's set, so a missing one gets a clear error:
+    for field in &required {
+        let trait_name = state_trait(field);
+        let Field { name: field_name, ty, .. } = field;
+
+        out += &format!(
+            "#[doc(hidden)]\n\
+             #[diagnostic::on_unimplemented(\
+                 message = \"can't build a `{name}` without `{field_name}`\", \
+                 label = \"`{field_name}` hasn't been set\", \
+                 note = \"call `.{field_name}(...)` on the builder before `.build()`\")]\n\
+             {vis} trait {trait_name} {{ fn value(self) -> {ty}; }}\n\
+             impl {trait_name} for {ty} {{ fn value(self) -> {ty} {{ self }} }}\n"
+        );
+    }
+
+    let bounds: Vec<String> = required.iter().map(|f| format!("{}: {}", param(f), state_trait(f))).collect();
+    let built: Vec<String> = fields
+        .iter()
+        .map(|f| match &f.kind {
+            Kind::Required          => format!("{0}: {1}::value(self.{0})", f.name, state_trait(f)),
+            Kind::Default(None)     => format!("{0}: self.{0}.unwrap_or_default()", f.name),
+            Kind::Default(Some(e))  => format!("{0}: self.{0}.unwrap_or_else(|| {e})", f.name),
+            Kind::Optional(_)       => format!("{0}: self.{0}", f.name),
+        })
+        .collect();
+
+    // the bounds go on build() rather than the impl, so a missing field is reported as an unmet
+    // bound (with the message above) instead of a confusing "no method named build":
+    let bounds = match bounds.is_empty() {
+        true  => String::new(),
+        false => format!("where {}", bounds.join(", ")),
+    };
+
+    out += &format!(
+        "impl<{params}> {builder}<{params}> {{ {vis} fn build(self) -> {name} {bounds} {{ {name} {{ {} }} }} }}\n",
+        built.join(", ")
+    );
+
+    if input.with {
+        out += &generate_with(input, &builder, &required);
+    }
+
+    out
+}
+
+
+fn generate_with(input: &Input, builder: &str, required: &[&Field]) -> String {
+    let Input { vis, name, fields, .. } = input;
+
+    let set_types: Vec<&str> = required.iter().map(|f| f.ty.as_str()).collect();
+    let cloned: Vec<String> = fields
+        .iter()
+        .map(|f| match f.kind {
+            Kind::Default(_) => format!("{0}: ::core::option::Option::Some(::core::clone::Clone::clone(&self.{0}))", f.name),
+            _                => format!("{0}: ::core::clone::Clone::clone(&self.{0})", f.name),
+        })
+        .collect();
+
+    let mut methods = format!(
+        "{vis} fn to_builder(&self) -> {builder}<{}> {{ {builder} {{ {} }} }}\n",
+        set_types.join(", "), cloned.join(", ")
+    );
+
+    for field in fields {
+        let Field { name: field_name, ty, .. } = field;
+        let others: Vec<String> = fields
+            .iter()
+            .filter(|f| f.name != *field_name)
+            .map(|f| format!("{0}: ::core::clone::Clone::clone(&self.{0})", f.name))
+            .collect();
+
+        // `with_r#type` isn't an identifier, the method is with_type:
+        methods += &format!(
+            "{vis} fn with_{}(&self, {field_name}: {ty}) -> Self {{ Self {{ {field_name}, {} }} }}\n",
+            field_name.trim_start_matches("r#"), others.join(", ")
+        );
+    }
+
+    format!("impl {name} {{ {methods} }}\n")
+}
+
+
+// sign_in_count => SignInCount (r#type => Type)
+fn camel_case(name: &str) -> String {
+    name.trim_start_matches("r#")
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect()
+}
@@ -0,0 +1,105 @@
+// the generated builders, used the way a struct elsewhere would use them.
+
+use builder_derive::Builder;
+
+
+#[derive(Debug, Clone, PartialEq, Builder)]
+#[builder(with)]
+pub struct User {
+    pub username: String,
+    pub email: String,
+    #[builder(default = true)]
+    pub active: bool,
+    #[builder(default)]
+    pub sign_in_count: u64,
+    pub nickname: Option<String>,
+}
+
+fn ferris() -> User {
+    User::builder().username("ferris".to_string()).email("ferris@example.com".to_string()).build()
+}
+
+
+#[test]
+fn optional_fields_get_their_defaults() {
+    let user = ferris();
+
+    assert_eq!(user.username, "ferris");
+    assert_eq!(user.email, "ferris@example.com");
+    assert!(user.active);
+    assert_eq!(user.sign_in_count, 0);
+    assert_eq!(user.nickname, None);
+}
+
+#[test]
+fn every_field_can_be_set_in_any_order() {
+    let user = User::builder()
+        .nickname("crab".to_string())
+        .sign_in_count(3)
+        .email("e".to_string())
+        .active(false)
+        .username("u".to_string())
+        .build();
+
+    assert_eq!(user, User {
+        username: "u".to_string(),
+        email: "e".to_string(),
+        active: false,
+        sign_in_count: 3,
+        nickname: Some("crab".to_string()),
+    });
+}
+
+#[test]
+fn a_later_setter_wins() {
+    let user = User::builder().username("a".to_string()).username("b".to_string()).email(String::new()).build();
+    assert_eq!(user.username, "b");
+}
+
+#[test]
+fn deriving_from_a_template() {
+    let user = ferris();
+
+    let changed = user.to_builder().email("new@example.com".to_string()).build();
+    assert_eq!(changed.email, "new@example.com");
+    assert_eq!(changed.username, user.username);
+
+    // with_ copies instead of moving out of the original:
+    let inactive = user.with_active(false);
+    assert!(!inactive.active);
+    assert_eq!(user, ferris());
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pair<A, B>(A, B);
+
+impl<A, B> Pair<A, B> {
+    fn new(a: A, b: B) -> Self {
+        Pair(a, b)
+    }
+}
+
+// a raw identifier for a field, and defaults with commas in them that aren't between options:
+#[derive(Debug, Clone, PartialEq, Builder)]
+#[builder(with)]
+pub struct Token {
+    pub r#type: String,
+    #[builder(default = Pair::<u8, char>::new(1, 'a'))]
+    pub pair: Pair<u8, char>,
+    #[builder(default = vec![1, 2, 3])]
+    pub list: Vec<i32>,
+}
+
+#[test]
+fn raw_identifiers_and_generic_defaults() {
+    let token = Token::builder().r#type("ident".to_string()).build();
+
+    assert_eq!(token.r#type, "ident");
+    assert_eq!(token.pair, Pair(1, 'a'));
+    assert_eq!(token.list, [1, 2, 3]);
+
+    let keyword = token.with_type("keyword".to_string());
+    assert_eq!(keyword.r#type, "keyword");
+    assert_eq!(keyword.with_pair(Pair::new(2, 'b')).pair, Pair(2, 'b'));
+}
@@ -0,0 +1,87 @@
+// Token helpers for derive macros.
+// builder-derive and variants-derive don't use syn, so they pick their input apart straight from
+// the tokens. these are the pieces they both need, so there's only one copy of them.
+//
+// * a plain library (not a proc-macro one) can use proc_macro's types too, but only while a macro
+// is running, so nothing here works in a unit test: the derive crates' tests cover it instead.
+
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+
+// an attribute at position i: Some(its #[<name>(...)] arguments, if it is one; where it ends)
+pub fn attribute(tokens: &[TokenTree], i: usize, name: &str) -> Option<(Option<Vec<String>>, usize)> {
+    let (TokenTree::Punct(hash), TokenTree::Group(group)) = (tokens.get(i)?, tokens.get(i + 1)?) else {
+        return None;
+    };
+    if hash.as_char() != '#' || group.delimiter() != Delimiter::Bracket {
+        return None;
+    }
+
+    let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+    let args = match &inner[..] {
+        [TokenTree::Ident(ident), TokenTree::Group(args)] if ident.to_string() == name => {
+            Some(split_commas(args.stream()))
+        },
+        _ => None,  // someone else's attribute, like #[doc] or #[serde]
+    };
+
+    Some((args, i + 2))
+}
+
+
+// `pub`, `pub(crate)`, `pub(super)`... or nothing:
+pub fn visibility(tokens: &[TokenTree], i: usize) -> (String, usize) {
+    match (tokens.get(i), tokens.get(i + 1)) {
+        (Some(TokenTree::Ident(p)), Some(TokenTree::Group(g))) if p.to_string() == "pub" && g.delimiter() == Delimiter::Parenthesis => {
+            (format!("pub{g}"), i + 2)
+        },
+        (Some(TokenTree::Ident(p)), _) if p.to_string() == "pub" => ("pub".to_string(), i + 1),
+        _ => (String::new(), i),
+    }
+}
+
+
+// * the pieces between the top-level commas, like the fields in `x: i32, y: HashMap<K, V>`.
+// commas inside (), [] and {} are already hidden in their groups, but <...> isn't a group,
+// so without counting the brackets `HashMap<K, V>` or `Foo::<A, B>::new()` would be cut in two.
+// empty pieces (from a trailing comma, or two in a row) are skipped.
+pub fn split_top_level(tokens: &[TokenTree]) -> Vec<&[TokenTree]> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut depth = 0_usize;
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Punct(p) if p.as_char() == ',' && depth == 0 => {
+                parts.push(&tokens[start..i]);
+                start = i + 1;
+            },
+            TokenTree::Punct(p) if p.as_char() == '<' => depth += 1,
+            // `->` in a function pointer type isn't a closing bracket (and `a > b` in a default value isn't either):
+            TokenTree::Punct(p) if p.as_char() == '>' && !arrow(tokens, i) => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+    parts.push(&tokens[start..]);
+
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+
+pub fn to_string(tokens: &[TokenTree]) -> String {
+    tokens.iter().cloned().collect::<TokenStream>().to_string()
+}
+
+
+fn split_commas(stream: TokenStream) -> Vec<String> {
+    let tokens: Vec<TokenTree> = stream.into_iter().collect();
+    split_top_level(&tokens).into_iter().map(to_string).collect()
+}
+
+
+fn arrow(tokens: &[TokenTree], i: usize) -> bool {
+    i > 0 && matches!(&tokens[i - 1], TokenTree::Punct(p) if p.as_char() == '-')
+}
@@ -0,0 +1,119 @@
+// Policy files.
+// an INI-like text format, with a section per role and per user:
+//
+// ? # comments and blank lines are ignored
+// ? [role viewer]
+// ? allow = read posts/*, read comments/*
+// ?
+// ? [role editor]
+// ? inherits = viewer
+// ? allow = edit posts/*, delete comments/*
+// ?
+// ? [user alice]
+// ? roles = editor
+// ? grant = publish posts/*
+// ? deny = delete comments/pinned
+//
+// * Keys:
+// - roles:  allow (permissions), inherits (other roles).
+// - users:  roles, grant and deny.
+// every value is a comma separated list, and a key can be repeated to add more.
+// a permission is an action and a resource separated by a space ("edit posts/*").
+
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{Permission, Policy, PolicyError};
+
+
+enum Section {
+    None,
+    Role(String),
+    User(String),
+}
+
+
+impl Policy {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PolicyError> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+impl FromStr for Policy {
+    type Err = PolicyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut policy = Policy::new();
+        let mut section = Section::None;
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let syntax = |reason: String| PolicyError::Syntax { line: i + 1, reason };
+
+            if line.is_empty() {
+                continue;
+            }
+
+            // [role name] or [user name]:
+            if let Some(header) = line.strip_prefix('[') {
+                let header = header.strip_suffix(']').ok_or_else(|| syntax("missing `]`".to_string()))?;
+
+                section = match header.split_whitespace().collect::<Vec<_>>()[..] {
+                    ["role", name] => {
+                        policy.roles.entry(name.to_string()).or_default();
+                        Section::Role(name.to_string())
+                    },
+                    ["user", name] => {
+                        policy.access_mut(name);
+                        Section::User(name.to_string())
+                    },
+                    _ => return Err(syntax(format!("expected [role <name>] or [user <name>], found [{header}]"))),
+                };
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or_else(|| syntax(format!("expected `key = value`, found {line:?}")))?;
+            let values = value.split(',').map(str::trim).filter(|v| !v.is_empty());
+            let permissions = || values.clone().map(|v| permission(v).ok_or_else(|| {
+                syntax(format!("expected a permission like \"edit posts/*\", found {v:?}"))
+            }));
+
+            match (&section, key.trim()) {
+                (Section::Role(name), "allow") => {
+                    let permissions = permissions().collect::<Result<Vec<_>, _>>()?;
+                    policy.roles.get_mut(name).expect("added with its header").permissions.extend(permissions);
+                },
+                (Section::Role(name), "inherits") => {
+                    policy.roles.get_mut(name).expect("added with its header").inherits.extend(values.map(String::from));
+                },
+                (Section::User(name), "roles") => {
+                    policy.access_mut(name).roles.extend(values.map(String::from));
+                },
+                (Section::User(name), "grant") => {
+                    let permissions = permissions().collect::<Result<Vec<_>, _>>()?;
+                    policy.access_mut(name).grants.extend(permissions);
+                },
+                (Section::User(name), "deny") => {
+                    let permissions = permissions().collect::<Result<Vec<_>, _>>()?;
+                    policy.access_mut(name).denials.extend(permissions);
+                },
+                (Section::None, key) => return Err(syntax(format!("`{key}` has to be inside a [role] or [user] section"))),
+                (Section::Role(_), key) => return Err(syntax(format!("unknown role key `{key}` (expected allow or inherits)"))),
+                (Section::User(_), key) => return Err(syntax(format!("unknown user key `{key}` (expected roles, grant or deny)"))),
+            }
+        }
+
+        policy.validate()?;
+        Ok(policy)
+    }
+}
+
+
+// "edit posts/*" => Permission { action: "edit", resource: "posts/*" }
+fn permission(s: &str) -> Option<Permission> {
+    match s.split_whitespace().collect::<Vec<_>>()[..] {
+        [action, resource] => Some(Permission::new(action, resource)),
+        _ => None,
+    }
+}
@@ -0,0 +1,447 @@
+// Role-based access control.
+// `active` only says whether a user can do anything at all. a Policy says what they can do:
+//
+// * Pieces:
+// - Permission:  an action on a resource, like "edit" on "posts/42". either can use * wildcards,
+//                so "edit posts/*" covers every post, and "* *" covers everything.
+// - Role:        a named set of permissions, which can inherit other roles' permissions too.
+// - per user:    the roles they have, plus grants and denials just for them.
+//
+// * can(user, action, resource) checks, in this order:
+// 1) inactive users can't do anything.
+// 2) a denial for the user beats everything else.
+// 3) then a grant for the user,
+// 4) then a permission from one of their roles (or a role those inherit, and so on).
+// 5) anything else is denied.
+// the Decision says which rule decided it, so "why can't I...?" has an answer.
+//
+// policies are usually loaded from a file, see config.rs for the format.
+
+pub mod config;
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fmt;
+
+use crate::user::User;
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permission {
+    pub action:   String,
+    pub resource: String,
+}
+
+impl Permission {
+    pub fn new(action: &str, resource: &str) -> Self {
+        Self { action: action.to_string(), resource: resource.to_string() }
+    }
+
+    pub fn allows(&self, action: &str, resource: &str) -> bool {
+        wildcard_match(&self.action, action) && wildcard_match(&self.resource, resource)
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.action, self.resource)
+    }
+}
+
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Role {
+    pub permissions: Vec<Permission>,
+    pub inherits:    Vec<String>,
+}
+
+// what a policy says about one user (everything is empty for users it doesn't mention):
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserAccess {
+    pub roles:   Vec<String>,
+    pub grants:  Vec<Permission>,
+    pub denials: Vec<Permission>,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    Inactive,
+    UserDenial(Permission),
+    UserGrant(Permission),
+    // the roles from the one the user has to the one with the permission, e.g. [editor, author]:
+    Role { path: Vec<String>, permission: Permission },
+    NoPermission,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub reason:  Reason,
+}
+
+impl Decision {
+    fn allow(reason: Reason) -> Self {
+        Self { allowed: true, reason }
+    }
+
+    fn deny(reason: Reason) -> Self {
+        Self { allowed: false, reason }
+    }
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self.allowed { true => "allowed: ", false => "denied: " })?;
+
+        match &self.reason {
+            Reason::Inactive      => write!(f, "the account is deactivated"),
+            Reason::UserDenial(p) => write!(f, "the user is explicitly denied \"{p}\""),
+            Reason::UserGrant(p)  => write!(f, "the user is explicitly granted \"{p}\""),
+            Reason::NoPermission  => write!(f, "none of the user's roles or grants allow it"),
+            Reason::Role { path, permission } => {
+                write!(f, "role {}", path[0])?;
+                for role in &path[1..] {
+                    write!(f, " inherits {role}, which")?;
+                }
+                write!(f, " grants \"{permission}\"")
+            },
+        }
+    }
+}
+
+
+#[derive(Debug)]
+pub enum PolicyError {
+    Io(std::io::Error),
+    Syntax { line: usize, reason: String },
+    UnknownRole { role: String, used_by: String },
+    InheritanceCycle(Vec<String>),
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolicyError::Io(e)                         => write!(f, "couldn't read the policy file: {e}"),
+            PolicyError::Syntax { line, reason }       => write!(f, "policy file line {line}: {reason}"),
+            PolicyError::UnknownRole { role, used_by } => write!(f, "{used_by} uses the role {role:?}, which isn't defined"),
+            PolicyError::InheritanceCycle(roles)       => write!(f, "roles inherit from each other in a loop: {}", roles.join(" -> ")),
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+impl From<std::io::Error> for PolicyError {
+    fn from(e: std::io::Error) -> Self {
+        PolicyError::Io(e)
+    }
+}
+
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Policy {
+    roles: BTreeMap<String, Role>,
+    users: BTreeMap<String, UserAccess>,
+}
+
+impl Policy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_role(&mut self, name: &str, role: Role) {
+        self.roles.insert(name.to_string(), role);
+    }
+
+    pub fn role(&self, name: &str) -> Option<&Role> {
+        self.roles.get(name)
+    }
+
+    pub fn roles(&self) -> impl Iterator<Item = (&str, &Role)> {
+        self.roles.iter().map(|(name, role)| (name.as_str(), role))
+    }
+
+    pub fn access(&self, username: &str) -> Option<&UserAccess> {
+        self.users.get(username)
+    }
+
+    pub fn access_mut(&mut self, username: &str) -> &mut UserAccess {
+        self.users.entry(username.to_string()).or_default()
+    }
+
+    pub fn assign(&mut self, username: &str, role: &str) {
+        self.access_mut(username).roles.push(role.to_string());
+    }
+
+    pub fn grant(&mut self, username: &str, permission: Permission) {
+        self.access_mut(username).grants.push(permission);
+    }
+
+    pub fn deny(&mut self, username: &str, permission: Permission) {
+        self.access_mut(username).denials.push(permission);
+    }
+
+    // * every role that's used has to exist, and inheritance can't go round in a loop.
+    // (loading from a file checks this already, it's for policies built in code.)
+    pub fn validate(&self) -> Result<(), PolicyError> {
+        for (name, role) in &self.roles {
+            for parent in &role.inherits {
+                if !self.roles.contains_key(parent) {
+                    return Err(PolicyError::UnknownRole { role: parent.clone(), used_by: format!("role {name:?}") });
+                }
+            }
+        }
+
+        for (username, access) in &self.users {
+            for role in &access.roles {
+                if !self.roles.contains_key(role) {
+                    return Err(PolicyError::UnknownRole { role: role.clone(), used_by: format!("user {username:?}") });
+                }
+            }
+        }
+
+        let mut done = HashSet::new();
+        for name in self.roles.keys() {
+            self.find_cycle(name, &mut Vec::new(), &mut done)?;
+        }
+
+        Ok(())
+    }
+
+    // depth first from a role, through everything it inherits. `done` is the roles already checked
+    // (with everything they inherit), so shared ancestors are only walked through once:
+    fn find_cycle<'a>(&'a self, name: &'a str, path: &mut Vec<&'a str>, done: &mut HashSet<&'a str>) -> Result<(), PolicyError> {
+        if let Some(start) = path.iter().position(|&r| r == name) {
+            let mut cycle: Vec<String> = path[start..].iter().map(|r| r.to_string()).collect();
+            cycle.push(name.to_string());
+            return Err(PolicyError::InheritanceCycle(cycle));
+        }
+        if done.contains(name) {
+            return Ok(());
+        }
+
+        path.push(name);
+        for parent in self.roles.get(name).map_or(&[][..], |r| &r.inherits[..]) {
+            self.find_cycle(parent, path, done)?;
+        }
+        path.pop();
+
+        done.insert(name);
+        Ok(())
+    }
+
+    pub fn can(&self, user: &User, action: &str, resource: &str) -> Decision {
+        if !user.active {
+            return Decision::deny(Reason::Inactive);
+        }
+
+        let Some(access) = self.users.get(user.username.as_str()) else {
+            return Decision::deny(Reason::NoPermission);
+        };
+
+        if let Some(p) = access.denials.iter().find(|p| p.allows(action, resource)) {
+            return Decision::deny(Reason::UserDenial(p.clone()));
+        }
+        if let Some(p) = access.grants.iter().find(|p| p.allows(action, resource)) {
+            return Decision::allow(Reason::UserGrant(p.clone()));
+        }
+
+        match self.role_permission(&access.roles, action, resource) {
+            Some((path, permission)) => Decision::allow(Reason::Role { path, permission }),
+            None                     => Decision::deny(Reason::NoPermission),
+        }
+    }
+
+    // * breadth first through the roles and what they inherit, so the explanation
+    // uses the shortest chain of roles. each role is only looked at once.
+    fn role_permission(&self, roles: &[String], action: &str, resource: &str) -> Option<(Vec<String>, Permission)> {
+        let mut queue: VecDeque<Vec<String>> = roles.iter().map(|r| vec![r.clone()]).collect();
+        let mut seen = HashSet::new();
+
+        while let Some(path) = queue.pop_front() {
+            let name = path.last().expect("paths start with one role");
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+
+            let Some(role) = self.roles.get(name) else {
+                continue;
+            };
+
+            if let Some(p) = role.permissions.iter().find(|p| p.allows(action, resource)) {
+                return Some((path, p.clone()));
+            }
+
+            for parent in &role.inherits {
+                let mut next = path.clone();
+                next.push(parent.clone());
+                queue.push_back(next);
+            }
+        }
+
+        None
+    }
+}
+
+
+// * `*` matches any run of characters (including none, and including '/'):
+// ? wildcard_match("posts/*", "posts/42/comments") => true
+// ? wildcard_match("*.md", "notes/todo.md")        => true
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;  // the last star, and where its match ends so far
+
+    // greedy with backtracking: when something doesn't match, let the last star eat one more character.
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            },
+            Some(&c) if c == text[t] => {
+                p += 1;
+                t += 1;
+            },
+            _ => match backtrack {
+                Some((star, end)) => {
+                    backtrack = Some((star, end + 1));
+                    p = star + 1;
+                    t = end + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::{Email, Username};
+
+    const POLICY: &str = "
+        [role viewer]
+        allow = read posts/*, read comments/*
+
+        [role author]
+        inherits = viewer
+        allow = edit posts/drafts/*
+
+        [role editor]
+        inherits = author
+        allow = edit posts/*, delete comments/*
+
+        [role admin]
+        allow = * *
+
+        [user alice]
+        roles = editor
+        grant = publish posts/*
+        deny = delete comments/pinned
+
+        [user bob]
+        roles = author
+
+        [user carol]
+        roles = admin
+        deny = delete users/*
+
+        [user dave]
+        grant = read posts/public
+    ";
+
+    fn user(name: &str, active: bool) -> User {
+        let username = Username::parse(name).expect("valid username");
+        let email = Email::parse(&format!("{name}@example.com")).expect("valid email");
+        User::builder().username(username).email(email).active(active).build()
+    }
+
+    fn permission(s: &str) -> Permission {
+        let (action, resource) = s.split_once(' ').expect("an action and a resource");
+        Permission::new(action, resource)
+    }
+
+    fn role(path: &[&str], p: &str) -> Reason {
+        Reason::Role { path: path.iter().map(|r| r.to_string()).collect(), permission: permission(p) }
+    }
+
+    #[test]
+    fn decisions_follow_the_rules() {
+        let policy: Policy = POLICY.parse().expect("a valid policy");
+
+        // (user, active, action, resource, allowed, why)
+        let table = [
+            ("alice", true,  "read",    "posts/1",            true,  role(&["editor", "author", "viewer"], "read posts/*")),
+            ("alice", true,  "edit",    "posts/1",            true,  role(&["editor"], "edit posts/*")),
+            ("alice", true,  "edit",    "posts/drafts/2",     true,  role(&["editor"], "edit posts/*")),
+            ("alice", true,  "publish", "posts/1",            true,  Reason::UserGrant(permission("publish posts/*"))),
+            ("alice", true,  "delete",  "comments/3",         true,  role(&["editor"], "delete comments/*")),
+            ("alice", true,  "delete",  "comments/pinned",    false, Reason::UserDenial(permission("delete comments/pinned"))),
+            ("alice", true,  "delete",  "posts/1",            false, Reason::NoPermission),
+            ("alice", false, "read",    "posts/1",            false, Reason::Inactive),
+            ("bob",   true,  "read",    "comments/9",         true,  role(&["author", "viewer"], "read comments/*")),
+            ("bob",   true,  "edit",    "posts/drafts/2",     true,  role(&["author"], "edit posts/drafts/*")),
+            ("bob",   true,  "edit",    "posts/1",            false, Reason::NoPermission),
+            ("carol", true,  "drop",    "database",           true,  role(&["admin"], "* *")),
+            ("carol", true,  "delete",  "users/alice",        false, Reason::UserDenial(permission("delete users/*"))),
+            ("dave",  true,  "read",    "posts/public",       true,  Reason::UserGrant(permission("read posts/public"))),
+            ("dave",  true,  "read",    "posts/1",            false, Reason::NoPermission),
+            ("erin",  true,  "read",    "posts/1",            false, Reason::NoPermission),
+        ];
+
+        for (name, active, action, resource, allowed, reason) in table {
+            let decision = policy.can(&user(name, active), action, resource);
+            assert_eq!(decision, Decision { allowed, reason }, "{name} {action} {resource}");
+        }
+    }
+
+    #[test]
+    fn validation_finds_unknown_roles_and_cycles() {
+        let role = |inherits: &[&str]| Role { permissions: Vec::new(), inherits: inherits.iter().map(|r| r.to_string()).collect() };
+
+        // (roles and what they inherit, users and their roles, the error if there is one)
+        type Roles = &'static [(&'static str, &'static [&'static str])];
+        type Users = &'static [(&'static str, &'static str)];
+        let table: [(Roles, Users, Option<&str>); 6] = [
+            (&[("a", &[]), ("b", &["a"]), ("c", &["a", "b"])], &[("u", "c")], None),
+            (&[("a", &["a"])], &[], Some("roles inherit from each other in a loop: a -> a")),
+            (&[("a", &["b"]), ("b", &["c"]), ("c", &["a"])], &[], Some("roles inherit from each other in a loop: a -> b -> c -> a")),
+            (&[("a", &[]), ("b", &["a"]), ("c", &["b", "d"]), ("d", &["c"])], &[], Some("roles inherit from each other in a loop: c -> d -> c")),
+            (&[("a", &["missing"])], &[], Some("role \"a\" uses the role \"missing\", which isn't defined")),
+            (&[("a", &[])], &[("u", "b")], Some("user \"u\" uses the role \"b\", which isn't defined")),
+        ];
+
+        for (roles, users, expected) in table {
+            let mut policy = Policy::new();
+            for &(name, inherits) in roles {
+                policy.add_role(name, role(inherits));
+            }
+            for &(username, role) in users {
+                policy.assign(username, role);
+            }
+
+            assert_eq!(policy.validate().err().map(|e| e.to_string()).as_deref(), expected, "{roles:?}");
+        }
+    }
+
+    #[test]
+    fn shared_ancestors_are_only_checked_once() {
+        // a long ladder where every role inherits both of the ones below it: without remembering
+        // which roles are done, checking it takes about 2^n steps.
+        let mut policy = Policy::new();
+        policy.add_role("r0", Role::default());
+        policy.add_role("r1", Role { permissions: Vec::new(), inherits: vec!["r0".to_string()] });
+        for i in 2..60 {
+            policy.add_role(&format!("r{i}"), Role {
+                permissions: Vec::new(),
+                inherits: vec![format!("r{}", i - 1), format!("r{}", i - 2)],
+            });
+        }
+
+        assert!(policy.validate().is_ok());
+    }
+}
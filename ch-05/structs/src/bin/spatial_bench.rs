@@ -0,0 +1,65 @@
+// Times the QuadTree against brute force.
+// ? cargo run --release --bin spatial_bench
+//
+// the same queries are answered twice, once by the tree and once by checking every region.
+// (that they give the same answers is checked by the tests in geometry/spatial.rs.)
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use structs::geometry::Point;
+use structs::geometry::spatial::{QuadTree, Region, RegionId};
+use structs::test_support::Random;
+
+
+const WORLD: f64 = 10_000.0;
+const REGIONS: usize = 20_000;
+const QUERIES: usize = 2_000;
+
+
+fn main() {
+    let mut random = Random(0x5eed);
+
+    let regions: Vec<Region> = (0..REGIONS)
+        .map(|_| Region::new(random.float(WORLD), random.float(WORLD), random.float(200.0) + 1.0, random.float(200.0) + 1.0))
+        .collect();
+
+    let mut tree = QuadTree::new(Region::new(0.0, 0.0, WORLD, WORLD));
+    let ids: Vec<RegionId> = regions.iter().map(|&r| tree.insert(r)).collect();
+
+    // remove every tenth region, so the tree has had some taken out, like a real one would:
+    let mut alive = vec![true; REGIONS];
+    for i in (0..REGIONS).step_by(10) {
+        tree.remove(ids[i]);
+        alive[i] = false;
+    }
+
+    let live = || regions.iter().zip(&ids).zip(&alive).filter(|&(_, &a)| a).map(|(pair, _)| pair);
+
+    let points: Vec<Point> = (0..QUERIES).map(|_| Point::new(random.float(WORLD), random.float(WORLD))).collect();
+    let ranges: Vec<Region> = points.iter().map(|p| Region::new(p.x, p.y, 300.0, 300.0)).collect();
+
+    report("point queries", time(|| points.iter().map(|&p| tree.query_point(p).len()).sum()),
+        time(|| points.iter().map(|&p| live().filter(|(r, _)| r.contains(p)).count()).sum()));
+
+    report("range queries", time(|| ranges.iter().map(|r| tree.query_range(r).len()).sum()),
+        time(|| ranges.iter().map(|q| live().filter(|(r, _)| r.intersects(q)).count()).sum()));
+
+    report("nearest", time(|| points.iter().filter(|&&p| tree.nearest(p).is_some()).count()),
+        time(|| points.iter().filter(|&&p| live().map(|(r, _)| r.distance_to(p)).min_by(f64::total_cmp).is_some()).count()));
+}
+
+
+fn time(run: impl FnOnce() -> usize) -> Duration {
+    let start = Instant::now();
+    black_box(run());
+    start.elapsed()
+}
+
+
+fn report(name: &str, tree: Duration, brute_force: Duration) {
+    println!(
+        "{name:<14} quadtree: {tree:>10.2?}  brute force: {brute_force:>10.2?}  ({:.0}x faster)",
+        brute_force.as_secs_f64() / tree.as_secs_f64()
+    );
+}
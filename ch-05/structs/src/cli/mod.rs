@@ -0,0 +1,6 @@
+// Command line tools, run with `cargo run -- <tool> ...`:
+// - user: manage the saved users, see user.rs.
+// - pack: pack rectangles from a file into a container, see pack.rs.
+
+pub mod pack;
+pub mod user;
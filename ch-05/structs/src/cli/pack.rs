@@ -0,0 +1,115 @@
+// Command line interface for rectangle packing.
+// ? cargo run -- pack <file> [--algorithm shelf|guillotine|maxrects] [--no-rotate] [--svg <out.svg>]
+//
+// * the file has the container's size on the first line, then one rectangle per line:
+// ? # comments and blank lines are ignored
+// ? 100 x 60
+// ? 40 x 20
+// ? 25x25
+// ? 10 30
+//
+// the packing is drawn as ASCII art, and also saved as an SVG if --svg is given.
+
+use std::fs;
+use std::process;
+
+use structs::geometry::Rectangle;
+use structs::geometry::packing::{self, Algorithm, PackOptions};
+
+
+const USAGE: &str = "usage: pack <file> [--algorithm shelf|guillotine|maxrects] [--no-rotate] [--svg <out.svg>]";
+const ASCII_WIDTH: usize = 60;
+
+
+pub fn run(args: &[String]) {
+    if let Err(e) = execute(args) {
+        eprintln!("Error: {e}");
+        process::exit(1);
+    }
+}
+
+
+fn execute(args: &[String]) -> Result<(), String> {
+    let mut file = None;
+    let mut svg = None;
+    let mut options = PackOptions::default();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--algorithm" => {
+                options.algorithm = match args.next().map(String::as_str) {
+                    Some("shelf")      => Algorithm::Shelf,
+                    Some("guillotine") => Algorithm::Guillotine,
+                    Some("maxrects")   => Algorithm::MaxRects,
+                    other => return Err(format!("unknown algorithm {other:?}\n{USAGE}")),
+                };
+            },
+            "--no-rotate" => options.allow_rotation = false,
+            "--svg"       => svg = Some(args.next().ok_or(USAGE)?),
+            _ if file.is_none() => file = Some(arg),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let file = file.ok_or(USAGE)?;
+    let contents = fs::read_to_string(file).map_err(|e| format!("couldn't read {file}: {e}"))?;
+    let (container, items) = parse(&contents)?;
+
+    let packing = packing::pack(&container, &items, options);
+
+    print!("{}", packing.to_ascii(ASCII_WIDTH));
+    println!();
+
+    for p in &packing.placements {
+        println!(
+            "{:>3}: {} x {} at ({}, {}){}",
+            p.index + 1, p.width, p.height, p.x, p.y,
+            match p.rotated { true => " (rotated)", false => "" }
+        );
+    }
+    for &i in &packing.unplaced {
+        println!("{:>3}: {} x {} didn't fit", i + 1, items[i].width, items[i].height);
+    }
+
+    println!();
+    println!("Placed: {} of {}", packing.placements.len(), items.len());
+    println!("Wasted area: {:.2} ({:.1}% used)", packing.wasted_area(), packing.fill_ratio() * 100.0);
+
+    if let Some(path) = svg {
+        fs::write(path, packing.to_svg()).map_err(|e| format!("couldn't write {path}: {e}"))?;
+        println!("Saved {path}");
+    }
+
+    Ok(())
+}
+
+
+fn parse(contents: &str) -> Result<(Rectangle, Vec<Rectangle>), String> {
+    let mut sizes = contents
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.split('#').next().unwrap_or("").trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(number, line)| parse_size(line).ok_or(format!("line {number}: expected \"<width> x <height>\", found {line:?}")));
+
+    let container = sizes.next().ok_or("the file is empty, it needs at least the container's size")??;
+    let items = sizes.collect::<Result<Vec<_>, _>>()?;
+
+    Ok((container, items))
+}
+
+
+// "40 x 20", "40x20" or "40 20":
+fn parse_size(line: &str) -> Option<Rectangle> {
+    let numbers: Vec<f64> = line
+        .split(|c: char| c == 'x' || c == 'X' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect::<Option<_>>()?;
+
+    match numbers[..] {
+        [width, height] if width > 0.0 && height > 0.0 => Some(Rectangle::new(width, height)),
+        _ => None,
+    }
+}
@@ -0,0 +1,134 @@
+// Command line interface for the user store.
+// ? cargo run -- user add <username> <email>
+// ? cargo run -- user list [--format table|json|csv|yaml] [--columns username,email,...] [--sort -sign_in_count,...]
+// ? cargo run -- user show <username> [--format ...] [--columns ...]
+// ? cargo run -- user deactivate <username>
+// ? cargo run -- user passwd <username> <password>
+// ? cargo run -- user sign-in <username> <password>
+// ? cargo run -- user can <username> <action> <resource>
+//
+// users are saved to the file in the USER_STORE environment variable, or "users.tsv".
+// `can` checks the policy in the USER_POLICY environment variable, or "policy.conf",
+// and exits with status 3 if it's denied, so scripts can check it.
+// list and show print a table by default, see user::format for the other formats.
+
+use std::env;
+use std::process;
+
+use structs::access::Policy;
+use structs::store::{StoreError, UserStore};
+use structs::user::clock::SystemClock;
+use structs::user::format::FormatError;
+use structs::user::{Email, Format, SignInPolicy, Username, View};
+
+
+const DEFAULT_PATH: &str = "users.tsv";
+const DEFAULT_POLICY: &str = "policy.conf";
+const USAGE: &str = "usage: user <add <username> <email> | list | show <username> | deactivate <username> \
+                     | passwd <username> <password> | sign-in <username> <password> \
+                     | can <username> <action> <resource>>\n\
+                     list and show also take [--format table|json|csv|yaml] [--columns <a,b,...>] [--sort <[-]a,...>]";
+
+
+pub fn run(args: &[String]) {
+    if let Err(e) = execute(args) {
+        eprintln!("Error: {e}");
+        process::exit(1);
+    }
+}
+
+
+fn execute(args: &[String]) -> Result<(), StoreError> {
+    let path = env::var("USER_STORE").unwrap_or_else(|_| DEFAULT_PATH.to_string());
+    let mut store = UserStore::open(path)?;
+
+    let (args, output) = match output_options(args) {
+        Ok(split) => split,
+        Err(e) => {
+            eprintln!("Error: {e}\n{USAGE}");
+            process::exit(2);
+        },
+    };
+
+    match args[..] {
+        ["add", username, email] => {
+            store.create(Username::parse(username)?, Email::parse(email)?)?;
+            store.save()?;
+            println!("Added {username}.");
+        },
+        ["list"] => {
+            print!("{}", output.format.render(store.list(), &output.view));
+        },
+        ["show", username] => {
+            let user = store.get(username).ok_or_else(|| StoreError::NotFound(username.to_string()))?;
+            print!("{}", output.format.render([user], &output.view));
+        },
+        ["deactivate", username] => {
+            store.deactivate(username)?;
+            store.save()?;
+            println!("Deactivated {username}.");
+        },
+        ["passwd", username, password] => {
+            store.set_password(username, password)?;
+            store.save()?;
+            println!("Password set for {username}.");
+        },
+        ["sign-in", username, password] => {
+            let result = store.sign_in(username, password, &SystemClock, &SignInPolicy::default());
+            store.save()?;
+
+            let session = result?;
+            println!("Signed in as {}.", session.username);
+            println!("Session token: {}", session.token);
+        },
+        ["can", username, action, resource] => {
+            let user = store.get(username).ok_or_else(|| StoreError::NotFound(username.to_string()))?;
+            let path = env::var("USER_POLICY").unwrap_or_else(|_| DEFAULT_POLICY.to_string());
+
+            let policy = match Policy::load(&path) {
+                Ok(policy) => policy,
+                Err(e) => {
+                    eprintln!("Error: {path}: {e}");
+                    process::exit(1);
+                },
+            };
+
+            let decision = policy.can(user, action, resource);
+            println!("{username} {action} {resource}: {decision}");
+
+            if !decision.allowed {
+                process::exit(3);
+            }
+        },
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        },
+    }
+
+    Ok(())
+}
+
+
+struct Output {
+    format: Format,
+    view:   View,
+}
+
+// takes --format, --columns and --sort out of the arguments, leaving the rest:
+fn output_options(args: &[String]) -> Result<(Vec<&str>, Output), FormatError> {
+    let (mut format, mut columns, mut sort) = ("table", "", "");
+    let mut rest = Vec::new();
+    let mut args = args.iter().map(String::as_str);
+
+    while let Some(arg) = args.next() {
+        match arg {
+            "--format"  => format = args.next().unwrap_or_default(),
+            "--columns" => columns = args.next().unwrap_or_default(),
+            "--sort"    => sort = args.next().unwrap_or_default(),
+            _           => rest.push(arg),
+        }
+    }
+
+    Ok((rest, Output { format: format.parse()?, view: View::parse(columns, sort)? }))
+}
@@ -0,0 +1,119 @@
+// Alpha compositing and blend modes.
+// putting a see-through color on top of another one, the way image editors layer things.
+//
+// * two separate questions:
+// - compositing (over):  how much of each layer shows through, decided by their alphas.
+// - blending (mode):     what color the overlapping part becomes, before the alphas are applied.
+//   Normal just takes the top color, Multiply darkens, Screen lightens, and so on.
+//
+// the formulas are the ones from the W3C "Compositing and Blending" spec, so the results
+// match what a browser draws for `mix-blend-mode`.
+
+use super::Color;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    Normal,
+    Multiply,    // darker, white does nothing
+    Screen,      // lighter, black does nothing
+    Overlay,     // multiply the darks, screen the lights (of the bottom layer)
+    Darken,      // the darker of the two, channel by channel
+    Lighten,     // the lighter of the two
+    ColorDodge,  // brightens the bottom layer to reflect the top one
+    ColorBurn,   // darkens the bottom layer to reflect the top one
+    HardLight,   // overlay, but decided by the top layer
+    SoftLight,   // a gentler hard light
+    Difference,  // |bottom - top|
+    Exclusion,   // like difference, with less contrast
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 12] = [
+        BlendMode::Normal, BlendMode::Multiply, BlendMode::Screen, BlendMode::Overlay,
+        BlendMode::Darken, BlendMode::Lighten, BlendMode::ColorDodge, BlendMode::ColorBurn,
+        BlendMode::HardLight, BlendMode::SoftLight, BlendMode::Difference, BlendMode::Exclusion,
+    ];
+
+    // one channel, with the backdrop (bottom) and source (top) between 0 and 1:
+    fn channel(self, backdrop: f64, source: f64) -> f64 {
+        let (b, s) = (backdrop, source);
+
+        match self {
+            BlendMode::Normal     => s,
+            BlendMode::Multiply   => b * s,
+            BlendMode::Screen     => b + s - b * s,
+            BlendMode::Overlay    => BlendMode::HardLight.channel(s, b),
+            BlendMode::Darken     => b.min(s),
+            BlendMode::Lighten    => b.max(s),
+            BlendMode::ColorDodge => match (b, s) {
+                (0.0, _) => 0.0,
+                (_, 1.0) => 1.0,
+                _        => (b / (1.0 - s)).min(1.0),
+            },
+            BlendMode::ColorBurn => match (b, s) {
+                (1.0, _) => 1.0,
+                (_, 0.0) => 0.0,
+                _        => 1.0 - ((1.0 - b) / s).min(1.0),
+            },
+            BlendMode::HardLight => match s <= 0.5 {
+                true  => BlendMode::Multiply.channel(b, 2.0 * s),
+                false => BlendMode::Screen.channel(b, 2.0 * s - 1.0),
+            },
+            BlendMode::SoftLight => {
+                if s <= 0.5 {
+                    b - (1.0 - 2.0 * s) * b * (1.0 - b)
+                } else {
+                    let d = match b <= 0.25 {
+                        true  => ((16.0 * b - 12.0) * b + 4.0) * b,
+                        false => b.sqrt(),
+                    };
+                    b + (2.0 * s - 1.0) * (d - b)
+                }
+            },
+            BlendMode::Difference => (b - s).abs(),
+            BlendMode::Exclusion  => b + s - 2.0 * b * s,
+        }
+    }
+}
+
+
+impl Color {
+    // * source-over: this color painted on top of the backdrop.
+    // an opaque color just covers the backdrop, a transparent one leaves it as it was.
+    pub fn over(self, backdrop: Color) -> Color {
+        self.blend(backdrop, BlendMode::Normal)
+    }
+
+    // * this color painted on top of the backdrop with a blend mode.
+    // where both are opaque, the result is just the blended color; where the top layer is
+    // see-through, more of the backdrop shows through, and where the backdrop is see-through,
+    // more of the top layer's own color shows.
+    pub fn blend(self, backdrop: Color, mode: BlendMode) -> Color {
+        let [sr, sg, sb, sa] = self.to_unit();
+        let [br, bg, bb, ba] = backdrop.to_unit();
+
+        let alpha = sa + ba * (1.0 - sa);
+        if alpha == 0.0 {
+            return Color::TRANSPARENT;
+        }
+
+        let mix = |b: f64, s: f64| {
+            let blended = (1.0 - ba) * s + ba * mode.channel(b, s);
+            (sa * blended + ba * b * (1.0 - sa)) / alpha
+        };
+
+        Color::from_unit(mix(br, sr), mix(bg, sg), mix(bb, sb), alpha)
+    }
+
+    // * a straight mix between two colors, `t` of the way from this one to the other:
+    // ? Color::BLACK.mix(Color::WHITE, 0.5) => grey
+    pub fn mix(self, other: Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let [r1, g1, b1, a1] = self.to_unit();
+        let [r2, g2, b2, a2] = other.to_unit();
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+
+        Color::from_unit(lerp(r1, r2), lerp(g1, g2), lerp(b1, b2), lerp(a1, a2))
+    }
+}
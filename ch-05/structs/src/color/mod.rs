@@ -0,0 +1,268 @@
+// Colors!
+// The chapter's Color(i32, i32, i32) happily accepted Color(-5, 900, 0).
+// this Color is four u8s (red, green, blue and alpha), so every value is 0..=255 by construction,
+// and out of range numbers have to go through clamped() on the way in.
+//
+// * Modules:
+// - spaces:   HSL and HSV, the "human friendly" ways to describe a color.
+// - named:    the CSS color names ("rebeccapurple", "tomato", ...).
+// - blend:    alpha compositing and blend modes (multiply, screen, overlay...).
+// - palette:  color schemes, shades, tints and gradients.
+
+pub mod blend;
+pub mod named;
+pub mod palette;
+pub mod spaces;
+
+pub use blend::BlendMode;
+pub use spaces::{Hsl, Hsv};
+
+use std::fmt;
+use std::str::FromStr;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,  // 0 is fully transparent, 255 is fully opaque
+}
+
+impl Color {
+    pub const BLACK: Color       = Color::rgb(0, 0, 0);
+    pub const WHITE: Color       = Color::rgb(255, 255, 255);
+    pub const RED: Color         = Color::rgb(255, 0, 0);
+    pub const GREEN: Color       = Color::rgb(0, 255, 0);
+    pub const BLUE: Color        = Color::rgb(0, 0, 255);
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    // for numbers that might be out of range, like the old Color(-5, 900, 0) => rgb(0, 255, 0):
+    pub fn clamped(r: i32, g: i32, b: i32) -> Self {
+        let clamp = |n: i32| n.clamp(0, 255) as u8;
+        Self::rgb(clamp(r), clamp(g), clamp(b))
+    }
+
+    // from channels between 0.0 and 1.0, which is what most color math works with:
+    pub fn from_unit(r: f64, g: f64, b: f64, a: f64) -> Self {
+        let to_u8 = |n: f64| (n.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Self::rgba(to_u8(r), to_u8(g), to_u8(b), to_u8(a))
+    }
+
+    pub fn to_unit(self) -> [f64; 4] {
+        [self.r, self.g, self.b, self.a].map(|c| c as f64 / 255.0)
+    }
+
+    pub fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    // * hex strings, with or without the '#':
+    // ? "#f80"       => rgb(255, 136, 0)
+    // ? "#f80c"      => rgba(255, 136, 0, 204)
+    // ? "#ff8800"    => rgb(255, 136, 0)
+    // ? "#ff8800cc"  => rgba(255, 136, 0, 204)
+    pub fn from_hex(s: &str) -> Result<Self, ColorError> {
+        let digits = s.strip_prefix('#').unwrap_or(s);
+
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ColorError::InvalidHex(s.to_string()));
+        }
+
+        // the short forms repeat every digit: "f80" == "ff8800"
+        let channel = |i: usize, width: usize| {
+            let n = u8::from_str_radix(&digits[i * width..(i + 1) * width], 16).unwrap_or(0);
+            if width == 1 { n * 17 } else { n }
+        };
+
+        match digits.len() {
+            3 => Ok(Self::rgb(channel(0, 1), channel(1, 1), channel(2, 1))),
+            4 => Ok(Self::rgba(channel(0, 1), channel(1, 1), channel(2, 1), channel(3, 1))),
+            6 => Ok(Self::rgb(channel(0, 2), channel(1, 2), channel(2, 2))),
+            8 => Ok(Self::rgba(channel(0, 2), channel(1, 2), channel(2, 2), channel(3, 2))),
+            _ => Err(ColorError::InvalidHex(s.to_string())),
+        }
+    }
+
+    // "#rrggbb", or "#rrggbbaa" if it's not fully opaque:
+    pub fn to_hex(self) -> String {
+        match self.a {
+            255 => format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b),
+            a   => format!("#{:02x}{:02x}{:02x}{a:02x}", self.r, self.g, self.b),
+        }
+    }
+
+    pub fn named(name: &str) -> Option<Self> {
+        named::lookup(name)
+    }
+
+    pub fn name(self) -> Option<&'static str> {
+        named::name_of(self)
+    }
+
+    pub fn to_hsl(self) -> Hsl {
+        Hsl::from(self)
+    }
+
+    pub fn to_hsv(self) -> Hsv {
+        Hsv::from(self)
+    }
+
+    // * WCAG relative luminance: how bright the color looks, from 0 (black) to 1 (white).
+    // the channels are converted from sRGB to linear light first, and green counts
+    // the most because human eyes are most sensitive to it.
+    pub fn luminance(self) -> f64 {
+        let linear = |c: u8| {
+            let c = c as f64 / 255.0;
+            if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        };
+
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    // * WCAG contrast ratio, from 1:1 (same color) to 21:1 (black on white).
+    // alpha is ignored, composite the color over its background first if it's transparent.
+    pub fn contrast_ratio(self, other: Color) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    pub fn contrast_level(self, other: Color) -> ContrastLevel {
+        ContrastLevel::from_ratio(self.contrast_ratio(other))
+    }
+}
+
+
+// what a contrast ratio is good enough for, according to WCAG 2:
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ContrastLevel {
+    Fail,     // below 3:1
+    AaLarge,  // 3:1, only for large text (18pt, or 14pt bold)
+    Aa,       // 4.5:1, normal text
+    Aaa,      // 7:1, enhanced
+}
+
+impl ContrastLevel {
+    pub fn from_ratio(ratio: f64) -> Self {
+        match ratio {
+            r if r >= 7.0 => ContrastLevel::Aaa,
+            r if r >= 4.5 => ContrastLevel::Aa,
+            r if r >= 3.0 => ContrastLevel::AaLarge,
+            _             => ContrastLevel::Fail,
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColorError {
+    InvalidHex(String),
+    UnknownName(String),
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorError::InvalidHex(s)  => write!(f, "{s:?} isn't a hex color (expected #rgb, #rgba, #rrggbb or #rrggbbaa)"),
+            ColorError::UnknownName(s) => write!(f, "{s:?} isn't a color name"),
+        }
+    }
+}
+
+impl std::error::Error for ColorError {}
+
+
+// "#ff8800" or "tomato":
+impl FromStr for Color {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        match s.starts_with('#') {
+            true  => Self::from_hex(s),
+            false => Self::named(s).ok_or_else(|| ColorError::UnknownName(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trips() {
+        let cases = [
+            ("#ff8800",   Color::rgb(255, 136, 0),        "#ff8800"),
+            ("ff8800",    Color::rgb(255, 136, 0),        "#ff8800"),
+            ("#f80",      Color::rgb(255, 136, 0),        "#ff8800"),
+            ("#F80C",     Color::rgba(255, 136, 0, 204),  "#ff8800cc"),
+            ("#ff8800cc", Color::rgba(255, 136, 0, 204),  "#ff8800cc"),
+            ("#ff8800ff", Color::rgb(255, 136, 0),        "#ff8800"),  // opaque drops the alpha
+            ("#000",      Color::BLACK,                   "#000000"),
+            ("#00000000", Color::TRANSPARENT,             "#00000000"),
+        ];
+
+        for (hex, color, shown) in cases {
+            assert_eq!(Color::from_hex(hex), Ok(color), "{hex}");
+            assert_eq!(color.to_hex(), shown);
+            assert_eq!(Color::from_hex(&color.to_hex()), Ok(color));
+        }
+
+        for bad in ["", "#", "#ff", "#ff880", "#ff8800c", "#gg8800", "#+f8", "#ff 880", "##f80"] {
+            assert_eq!(Color::from_hex(bad), Err(ColorError::InvalidHex(bad.to_string())), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn black_on_white_is_21_to_1() {
+        assert_eq!(Color::BLACK.luminance(), 0.0);
+        assert!((Color::WHITE.luminance() - 1.0).abs() < 1e-12);
+
+        assert!((Color::BLACK.contrast_ratio(Color::WHITE) - 21.0).abs() < 1e-9);
+        assert!((Color::WHITE.contrast_ratio(Color::BLACK) - 21.0).abs() < 1e-9);
+        assert_eq!(Color::RED.contrast_ratio(Color::RED), 1.0);
+
+        assert_eq!(Color::BLACK.contrast_level(Color::WHITE), ContrastLevel::Aaa);
+        // #767676 is the classic lightest grey that still passes AA on white (4.54:1):
+        assert_eq!(Color::rgb(0x76, 0x76, 0x76).contrast_level(Color::WHITE), ContrastLevel::Aa);
+        assert_eq!(Color::rgb(0x77, 0x77, 0x77).contrast_level(Color::WHITE), ContrastLevel::AaLarge);
+        assert_eq!(Color::named("yellow").unwrap().contrast_level(Color::WHITE), ContrastLevel::Fail);
+    }
+
+    #[test]
+    fn css_names() {
+        let cases = [
+            ("tomato",        Color::rgb(255, 99, 71)),
+            ("RebeccaPurple", Color::rgb(102, 51, 153)),
+            ("  white ",      Color::WHITE),
+            ("#f80",          Color::rgb(255, 136, 0)),
+        ];
+        for (name, color) in cases {
+            assert_eq!(name.parse::<Color>(), Ok(color), "{name:?}");
+        }
+
+        assert_eq!("bluish".parse::<Color>(), Err(ColorError::UnknownName(String::from("bluish"))));
+
+        // colors with two names get the first one, and most colors don't have one:
+        assert_eq!(Color::rgb(0, 255, 255).name(), Some("aqua"));
+        assert_eq!(Color::rgb(128, 128, 128).name(), Some("gray"));
+        assert_eq!(Color::rgb(255, 136, 0).name(), None);
+        assert_eq!(Color::rgba(255, 99, 71, 128).name(), None);
+    }
+}
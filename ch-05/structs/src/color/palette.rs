@@ -0,0 +1,73 @@
+// Palettes.
+// sets of colors that go together, worked out on the HSL color wheel.
+//
+// * schemes (the base color always comes first):
+// - complementary:  the opposite side of the wheel.
+// - analogous:      the neighbours on either side.
+// - triadic:        three colors evenly spaced around the wheel.
+// - tetradic:       four colors, two pairs of complements.
+// - monochromatic:  one hue, from dark to light.
+//
+// * ramps:
+// - shades:    towards black.
+// - tints:     towards white.
+// - gradient:  evenly spaced steps between any two colors.
+
+use super::{Color, Hsl};
+
+
+pub fn complementary(base: Color) -> [Color; 2] {
+    rotations(base, [0.0, 180.0])
+}
+
+// `spread` degrees either side, 30 is the usual choice:
+pub fn analogous(base: Color, spread: f64) -> [Color; 3] {
+    rotations(base, [0.0, -spread, spread])
+}
+
+pub fn triadic(base: Color) -> [Color; 3] {
+    rotations(base, [0.0, 120.0, 240.0])
+}
+
+pub fn tetradic(base: Color) -> [Color; 4] {
+    rotations(base, [0.0, 90.0, 180.0, 270.0])
+}
+
+// `count` colors with the base's hue and saturation, from dark to light:
+pub fn monochromatic(base: Color, count: usize) -> Vec<Color> {
+    let hsl = base.to_hsl();
+
+    // skip pure black and pure white, they'd lose the hue entirely:
+    (1..=count)
+        .map(|i| Hsl { l: i as f64 / (count + 1) as f64, ..hsl }.to_color())
+        .collect()
+}
+
+// `count` steps from the base color towards black (not including black itself):
+pub fn shades(base: Color, count: usize) -> Vec<Color> {
+    ramp(base, Color::BLACK.with_alpha(base.a), count)
+}
+
+// `count` steps from the base color towards white (not including white itself):
+pub fn tints(base: Color, count: usize) -> Vec<Color> {
+    ramp(base, Color::WHITE.with_alpha(base.a), count)
+}
+
+// `steps` colors from `from` to `to`, both included:
+pub fn gradient(from: Color, to: Color, steps: usize) -> Vec<Color> {
+    match steps {
+        0 => Vec::new(),
+        1 => vec![from],
+        _ => (0..steps).map(|i| from.mix(to, i as f64 / (steps - 1) as f64)).collect(),
+    }
+}
+
+
+fn rotations<const N: usize>(base: Color, degrees: [f64; N]) -> [Color; N] {
+    let hsl = base.to_hsl();
+    degrees.map(|d| hsl.rotated(d).to_color())
+}
+
+fn ramp(base: Color, target: Color, count: usize) -> Vec<Color> {
+    (0..count).map(|i| base.mix(target, i as f64 / count as f64)).collect()
+}
@@ -0,0 +1,223 @@
+// HSL and HSV.
+// RGB says how much of each light to mix, which is how screens work but not how people think.
+// both of these describe a color by its hue (where it is on the color wheel, in degrees),
+// how colorful it is, and how light it is. they only differ in what "light" means:
+//
+// - HSL lightness:  0 is black, 0.5 is the pure color, 1 is white.
+// - HSV value:      0 is black, 1 is the pure color (add white by lowering the saturation).
+//
+// saturation and lightness/value are between 0 and 1. alpha is carried along unchanged.
+
+use super::Color;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    pub h: f64,  // degrees, 0..360
+    pub s: f64,
+    pub l: f64,
+    pub a: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsv {
+    pub h: f64,  // degrees, 0..360
+    pub s: f64,
+    pub v: f64,
+    pub a: f64,
+}
+
+
+impl Hsl {
+    pub fn new(h: f64, s: f64, l: f64) -> Self {
+        Self { h: wrap_hue(h), s: s.clamp(0.0, 1.0), l: l.clamp(0.0, 1.0), a: 1.0 }
+    }
+
+    // turned around the color wheel, 180 degrees is the opposite color:
+    pub fn rotated(self, degrees: f64) -> Self {
+        Self { h: wrap_hue(self.h + degrees), ..self }
+    }
+
+    pub fn to_color(self) -> Color {
+        Color::from(self)
+    }
+}
+
+impl Hsv {
+    pub fn new(h: f64, s: f64, v: f64) -> Self {
+        Self { h: wrap_hue(h), s: s.clamp(0.0, 1.0), v: v.clamp(0.0, 1.0), a: 1.0 }
+    }
+
+    pub fn to_color(self) -> Color {
+        Color::from(self)
+    }
+}
+
+
+fn wrap_hue(h: f64) -> f64 {
+    h.rem_euclid(360.0)
+}
+
+
+// * both models start from the same three numbers:
+// - the biggest and smallest channel, and the hue, which only depends on which channel is
+//   biggest and how far the other two are apart.
+fn hue_and_range(color: Color) -> (f64, f64, f64) {
+    let [r, g, b, _] = color.to_unit();
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let delta = max - min;
+
+    let hue = match delta {
+        0.0           => 0.0,  // grey, the hue doesn't matter
+        _ if max == r => 60.0 * ((g - b) / delta).rem_euclid(6.0),
+        _ if max == g => 60.0 * ((b - r) / delta + 2.0),
+        _             => 60.0 * ((r - g) / delta + 4.0),
+    };
+
+    (hue, max, min)
+}
+
+
+// the chroma (how far the color is from grey) and hue back to RGB, before adding the grey part back in:
+fn from_hue(h: f64, chroma: f64, grey: f64, alpha: f64) -> Color {
+    let h = wrap_hue(h) / 60.0;
+    let x = chroma * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    Color::from_unit(r + grey, g + grey, b + grey, alpha)
+}
+
+
+impl From<Color> for Hsl {
+    fn from(color: Color) -> Self {
+        let (h, max, min) = hue_and_range(color);
+        let l = (max + min) / 2.0;
+
+        let s = match max - min {
+            0.0   => 0.0,
+            delta => delta / (1.0 - (2.0 * l - 1.0).abs()),
+        };
+
+        Self { h, s, l, a: color.a as f64 / 255.0 }
+    }
+}
+
+impl From<Hsl> for Color {
+    fn from(hsl: Hsl) -> Self {
+        let chroma = (1.0 - (2.0 * hsl.l - 1.0).abs()) * hsl.s;
+        from_hue(hsl.h, chroma, hsl.l - chroma / 2.0, hsl.a)
+    }
+}
+
+impl From<Color> for Hsv {
+    fn from(color: Color) -> Self {
+        let (h, max, min) = hue_and_range(color);
+
+        let s = match max {
+            0.0 => 0.0,
+            _   => (max - min) / max,
+        };
+
+        Self { h, s, v: max, a: color.a as f64 / 255.0 }
+    }
+}
+
+impl From<Hsv> for Color {
+    fn from(hsv: Hsv) -> Self {
+        let chroma = hsv.v * hsv.s;
+        from_hue(hsv.h, chroma, hsv.v - chroma, hsv.a)
+    }
+}
+
+// straight between the two, without rounding to 8-bit RGB on the way:
+impl From<Hsl> for Hsv {
+    fn from(hsl: Hsl) -> Self {
+        let v = hsl.l + hsl.s * hsl.l.min(1.0 - hsl.l);
+        let s = match v {
+            0.0 => 0.0,
+            _   => 2.0 * (1.0 - hsl.l / v),
+        };
+
+        Self { h: hsl.h, s, v, a: hsl.a }
+    }
+}
+
+impl From<Hsv> for Hsl {
+    fn from(hsv: Hsv) -> Self {
+        let l = hsv.v * (1.0 - hsv.s / 2.0);
+        let s = match l.min(1.0 - l) {
+            0.0  => 0.0,
+            edge => (hsv.v - l) / edge,
+        };
+
+        Self { h: hsv.h, s, l, a: hsv.a }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Random;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn known_colors() {
+        let cases = [
+            // color                      h      s    l     (hsv) s    v
+            (Color::RED,                  0.0,   1.0, 0.5,  1.0, 1.0),
+            (Color::GREEN,                120.0, 1.0, 0.5,  1.0, 1.0),
+            (Color::BLUE,                 240.0, 1.0, 0.5,  1.0, 1.0),
+            (Color::rgb(0, 255, 255),     180.0, 1.0, 0.5,  1.0, 1.0),
+            (Color::rgb(255, 0, 255),     300.0, 1.0, 0.5,  1.0, 1.0),
+            (Color::WHITE,                0.0,   0.0, 1.0,  0.0, 1.0),
+            (Color::BLACK,                0.0,   0.0, 0.0,  0.0, 0.0),
+            (Color::rgb(102, 51, 153),    270.0, 0.5, 0.4,  2.0 / 3.0, 0.6),  // rebeccapurple
+        ];
+
+        for (color, h, s, l, hsv_s, v) in cases {
+            let (hsl, hsv) = (color.to_hsl(), color.to_hsv());
+
+            assert!(close(hsl.h, h) && close(hsl.s, s) && close(hsl.l, l), "{color}: {hsl:?}");
+            assert!(close(hsv.h, h) && close(hsv.s, hsv_s) && close(hsv.v, v), "{color}: {hsv:?}");
+            assert_eq!(Hsl::new(h, s, l).to_color(), color);
+            assert_eq!(Hsv::new(h, hsv_s, v).to_color(), color);
+        }
+    }
+
+    #[test]
+    fn rgb_to_hsl_and_hsv_and_back() {
+        let mut random = Random(36);
+
+        for _ in 0..10_000 {
+            let [r, g, b, a, ..] = random.bits().to_le_bytes();
+            let color = Color::rgba(r, g, b, a);
+
+            assert_eq!(color.to_hsl().to_color(), color);
+            assert_eq!(color.to_hsv().to_color(), color);
+
+            // and between the two, without going through RGB:
+            assert_eq!(Hsv::from(color.to_hsl()).to_color(), color);
+            assert_eq!(Hsl::from(color.to_hsv()).to_color(), color);
+        }
+    }
+
+    #[test]
+    fn hues_wrap_around() {
+        assert_eq!(Hsl::new(-90.0, 1.0, 0.5).h, 270.0);
+        assert_eq!(Hsl::new(720.0, 1.0, 0.5).h, 0.0);
+        assert_eq!(Hsl::new(0.0, 1.0, 0.5).rotated(180.0).to_color(), Color::rgb(0, 255, 255));
+        assert_eq!(Hsv::new(400.0, 2.0, -1.0), Hsv { h: 40.0, s: 1.0, v: 0.0, a: 1.0 });
+    }
+}
@@ -0,0 +1,55 @@
+// Circles.
+
+use std::f64::consts::PI;
+
+use super::{Outline, Point, Shape};
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center: Point,
+    pub radius: f64,
+}
+
+impl Circle {
+    pub fn new(center: Point, radius: f64) -> Self {
+        Self { center, radius }
+    }
+}
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * PI * self.radius
+    }
+
+    fn center(&self) -> Point {
+        self.center
+    }
+
+    fn outline(&self) -> Outline {
+        Outline::Circle { center: self.center, radius: self.radius }
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        self.center.distance(point) <= self.radius
+    }
+
+    fn translate(&mut self, dx: f64, dy: f64) {
+        self.center = self.center.translated(dx, dy);
+    }
+
+    fn scale(&mut self, factor: f64) {
+        self.radius *= factor.abs();
+    }
+
+    // a circle looks the same no matter how it's rotated around its center:
+    fn rotate(&mut self, _radians: f64) {}
+
+    fn to_svg(&self) -> String {
+        format!(r#"<circle cx="{}" cy="{}" r="{}"/>"#, self.center.x, self.center.y, self.radius)
+    }
+}
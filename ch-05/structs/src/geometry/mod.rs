@@ -0,0 +1,264 @@
+// Geometry!
+// Rectangle grew up: it's now one of several shapes behind a common Shape trait.
+//
+// * Shapes:
+// - Rectangle: width and height, centered somewhere, optionally rotated.
+// - Circle:    center and radius.
+// - Triangle:  three corners.
+// - Polygon:   any number of corners (a simple polygon: its edges don't cross each other).
+//
+// * Coordinates are f64s with the y axis pointing *down*, like SVG (and most screens),
+//   so a positive rotation turns a shape clockwise on screen. angles are in radians.
+
+pub mod circle;
+pub mod packing;
+pub mod polygon;
+pub mod rectangle;
+pub mod spatial;
+pub mod svg;
+pub mod triangle;
+
+pub use circle::Circle;
+pub use polygon::Polygon;
+pub use rectangle::Rectangle;
+pub use triangle::Triangle;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn distance(&self, other: Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+
+    pub fn translated(&self, dx: f64, dy: f64) -> Self {
+        Self::new(self.x + dx, self.y + dy)
+    }
+
+    // moves the point towards (or away from) a center, multiplying its distance by factor:
+    pub fn scaled_about(&self, center: Point, factor: f64) -> Self {
+        Self::new(
+            center.x + (self.x - center.x) * factor,
+            center.y + (self.y - center.y) * factor,
+        )
+    }
+
+    pub fn rotated_about(&self, center: Point, radians: f64) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        let (dx, dy) = (self.x - center.x, self.y - center.y);
+
+        Self::new(
+            center.x + dx * cos - dy * sin,
+            center.y + dx * sin + dy * cos,
+        )
+    }
+}
+
+
+// the smallest axis-aligned box around a shape:
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn around(points: &[Point]) -> Self {
+        let mut min = Point::new(f64::INFINITY, f64::INFINITY);
+        let mut max = Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY);
+
+        for p in points {
+            min = Point::new(min.x.min(p.x), min.y.min(p.y));
+            max = Point::new(max.x.max(p.x), max.y.max(p.y));
+        }
+
+        Self { min, max }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    pub fn center(&self) -> Point {
+        Point::new((self.min.x + self.max.x) / 2.0, (self.min.y + self.max.y) / 2.0)
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> Self {
+        BoundingBox::around(&[self.min, self.max, other.min, other.max])
+    }
+
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.x <= other.max.x && other.min.x <= self.max.x
+            && self.min.y <= other.max.y && other.min.y <= self.max.y
+    }
+}
+
+
+// * what a shape looks like to the intersection tests:
+// every shape is either a circle or a polygon (rectangles and triangles are polygons too),
+// so intersects() only has to handle three pairs instead of one per pair of shapes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outline {
+    Circle { center: Point, radius: f64 },
+    Polygon(Vec<Point>),
+}
+
+
+pub trait Shape {
+    fn area(&self) -> f64;
+    fn perimeter(&self) -> f64;
+    fn center(&self) -> Point;
+    fn outline(&self) -> Outline;
+
+    // points on the edge count as inside:
+    fn contains(&self, point: Point) -> bool;
+
+    fn translate(&mut self, dx: f64, dy: f64);
+    // scaling and rotating happen around the shape's center:
+    fn scale(&mut self, factor: f64);
+    fn rotate(&mut self, radians: f64);
+
+    // an SVG element for the shape, see svg::document() to put several of them in a file:
+    fn to_svg(&self) -> String;
+
+    fn bounding_box(&self) -> BoundingBox {
+        match self.outline() {
+            Outline::Circle { center, radius } => BoundingBox {
+                min: center.translated(-radius, -radius),
+                max: center.translated(radius, radius),
+            },
+            Outline::Polygon(points) => BoundingBox::around(&points),
+        }
+    }
+
+    // do the two shapes touch or overlap at all?
+    fn intersects(&self, other: &dyn Shape) -> bool {
+        // cheap check first: if the boxes don't touch, the shapes can't either.
+        if !self.bounding_box().intersects(&other.bounding_box()) {
+            return false;
+        }
+
+        match (self.outline(), other.outline()) {
+            (Outline::Circle { center: a, radius: ra }, Outline::Circle { center: b, radius: rb }) => {
+                a.distance(b) <= ra + rb
+            },
+            (Outline::Circle { center, radius }, Outline::Polygon(points))
+            | (Outline::Polygon(points), Outline::Circle { center, radius }) => {
+                polygon::contains(&points, center)
+                    || polygon::edges(&points).any(|(a, b)| segment_distance(center, a, b) <= radius)
+            },
+            (Outline::Polygon(a), Outline::Polygon(b)) => {
+                // either an edge of one crosses an edge of the other,
+                // or one of them is completely inside the other:
+                polygon::edges(&a).any(|(p1, p2)| polygon::edges(&b).any(|(q1, q2)| segments_intersect(p1, p2, q1, q2)))
+                    || polygon::contains(&a, b[0])
+                    || polygon::contains(&b, a[0])
+            },
+        }
+    }
+}
+
+
+// which side of the line a -> b is c on? positive, negative, or 0 if it's on the line.
+fn cross(a: Point, b: Point, c: Point) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+
+fn segments_intersect(p1: Point, p2: Point, q1: Point, q2: Point) -> bool {
+    let d1 = cross(q1, q2, p1);
+    let d2 = cross(q1, q2, p2);
+    let d3 = cross(p1, p2, q1);
+    let d4 = cross(p1, p2, q2);
+
+    // the segments properly cross if each one has the other's ends on opposite sides:
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        return true;
+    }
+
+    // otherwise they only touch if an end lies on the other segment:
+    (d1 == 0.0 && on_segment(q1, q2, p1))
+        || (d2 == 0.0 && on_segment(q1, q2, p2))
+        || (d3 == 0.0 && on_segment(p1, p2, q1))
+        || (d4 == 0.0 && on_segment(p1, p2, q2))
+}
+
+
+// assuming p is on the line a -> b, is it between a and b?
+fn on_segment(a: Point, b: Point, p: Point) -> bool {
+    p.x >= a.x.min(b.x) && p.x <= a.x.max(b.x) && p.y >= a.y.min(b.y) && p.y <= a.y.max(b.y)
+}
+
+
+// shortest distance from p to the segment a -> b:
+fn segment_distance(p: Point, a: Point, b: Point) -> f64 {
+    let length_squared = (b.x - a.x).powi(2) + (b.y - a.y).powi(2);
+
+    if length_squared == 0.0 {
+        return p.distance(a);
+    }
+
+    // how far along the segment the closest point is, from 0 (at a) to 1 (at b):
+    let t = (((p.x - a.x) * (b.x - a.x) + (p.y - a.y) * (b.y - a.y)) / length_squared).clamp(0.0, 1.0);
+    p.distance(Point::new(a.x + t * (b.x - a.x), a.y + t * (b.y - a.y)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_4;
+
+    fn point(x: f64, y: f64) -> Point {
+        Point::new(x, y)
+    }
+
+    #[test]
+    fn touching_shapes_intersect_and_gaps_dont() {
+        let square = Rectangle::new(10.0, 10.0);  // from (0, 0) to (10, 10)
+        // a diamond whose box overlaps the square's corner, but which is still 0.2 * sqrt(2) away from it:
+        let mut diamond = Rectangle::at(point(10.2, 10.2), 2.0, 2.0);
+        diamond.rotate(FRAC_PI_4);
+
+        let cases: [(&str, Box<dyn Shape>, bool); 12] = [
+            ("edge to edge",        Box::new(Rectangle::at(point(10.0, 0.0), 10.0, 10.0)), true),
+            ("a hair apart",        Box::new(Rectangle::at(point(10.001, 0.0), 10.0, 10.0)), false),
+            ("corner to corner",    Box::new(Rectangle::at(point(10.0, 10.0), 5.0, 5.0)), true),
+            ("inside it",           Box::new(Rectangle::at(point(2.0, 2.0), 1.0, 1.0)), true),
+            ("around it",           Box::new(Rectangle::at(point(-5.0, -5.0), 20.0, 20.0)), true),
+            ("boxes only",          Box::new(diamond), false),
+            ("circle touching",     Box::new(Circle::new(point(15.0, 5.0), 5.0)), true),
+            ("circle just short",   Box::new(Circle::new(point(15.0, 5.0), 4.99)), false),
+            ("circle inside",       Box::new(Circle::new(point(5.0, 5.0), 1.0)), true),
+            ("circle off a corner", Box::new(Circle::new(point(13.0, 14.0), 4.99)), false),
+            ("triangle's tip",      Box::new(Triangle::new(point(10.0, 10.0), point(12.0, 10.0), point(10.0, 12.0))), true),
+            ("triangle crossing",   Box::new(Triangle::new(point(5.0, -5.0), point(15.0, 5.0), point(20.0, -10.0))), true),
+        ];
+
+        for (name, shape, expected) in cases {
+            assert_eq!(square.intersects(shape.as_ref()), expected, "{name}");
+            assert_eq!(shape.intersects(&square), expected, "{name}, the other way round");
+        }
+    }
+
+    #[test]
+    fn circles_touch_at_the_sum_of_their_radii() {
+        let a = Circle::new(point(0.0, 0.0), 1.0);
+
+        assert!(a.intersects(&Circle::new(point(2.0, 0.0), 1.0)));
+        assert!(!a.intersects(&Circle::new(point(2.01, 0.0), 1.0)));
+        assert!(a.intersects(&Circle::new(point(0.1, 0.0), 0.2)));  // inside
+    }
+}
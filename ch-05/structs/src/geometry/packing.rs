@@ -0,0 +1,445 @@
+// Rectangle bin packing.
+// can_hold() says whether *one* rectangle fits inside another;
+// packing fits *many* of them into one container without overlapping.
+//
+// * Algorithms (from simplest and fastest to slowest and tightest):
+// - Shelf:       rows ("shelves") are filled left to right, a new one starts when a row is full.
+// - Guillotine:  every placement cuts the free space it used into two smaller free rectangles,
+//                like cutting paper with a guillotine.
+// - MaxRects:    keeps track of every *maximal* free rectangle, even overlapping ones,
+//                and picks the spot that leaves the least space on the shorter side.
+//
+// every algorithm sorts the rectangles biggest-first, which packs much better than input order.
+// optionally, rectangles can be turned 90 degrees to fit better.
+
+use super::{Point, Rectangle, Shape, svg};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Shelf,
+    Guillotine,
+    MaxRects,
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct PackOptions {
+    pub algorithm:      Algorithm,
+    pub allow_rotation: bool,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        Self { algorithm: Algorithm::MaxRects, allow_rotation: true }
+    }
+}
+
+
+// where one of the input rectangles ended up:
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub index:   usize,  // position in the input list
+    pub x:       f64,    // top-left corner, relative to the container's top-left corner
+    pub y:       f64,
+    pub width:   f64,    // already swapped if it was rotated
+    pub height:  f64,
+    pub rotated: bool,
+}
+
+impl Placement {
+    pub fn to_rectangle(&self) -> Rectangle {
+        Rectangle::at(Point::new(self.x, self.y), self.width, self.height)
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct Packing {
+    pub width:      f64,
+    pub height:     f64,
+    pub placements: Vec<Placement>,
+    pub unplaced:   Vec<usize>,  // indices of the rectangles that didn't fit
+}
+
+impl Packing {
+    pub fn used_area(&self) -> f64 {
+        self.placements.iter().map(|p| p.width * p.height).sum()
+    }
+
+    pub fn wasted_area(&self) -> f64 {
+        self.width * self.height - self.used_area()
+    }
+
+    // how much of the container is covered, from 0 to 1:
+    pub fn fill_ratio(&self) -> f64 {
+        self.used_area() / (self.width * self.height)
+    }
+
+    pub fn to_svg(&self) -> String {
+        let container = Rectangle::new(self.width, self.height);
+        let rects: Vec<Rectangle> = self.placements.iter().map(Placement::to_rectangle).collect();
+
+        let mut shapes: Vec<&dyn Shape> = vec![&container];
+        shapes.extend(rects.iter().map(|r| r as &dyn Shape));
+
+        svg::document(&shapes)
+    }
+
+    // * a text drawing of the packing, `columns` characters wide:
+    // every placed rectangle gets a letter (by its input position), and '.' is wasted space.
+    // each character is a cell of the container, and shows whatever covers the cell's center.
+    pub fn to_ascii(&self, columns: usize) -> String {
+        let cell = self.width / columns as f64;
+        // characters are about twice as tall as they are wide, so use half as many rows:
+        let rows = ((self.height / cell) / 2.0).ceil() as usize;
+        let mut out = String::new();
+
+        for row in 0..rows {
+            let y = (row as f64 + 0.5) * cell * 2.0;
+
+            for column in 0..columns {
+                let x = (column as f64 + 0.5) * cell;
+
+                let covering = self.placements.iter().find(|p| {
+                    x >= p.x && x < p.x + p.width && y >= p.y && y < p.y + p.height
+                });
+
+                out.push(match covering {
+                    Some(p) => LABELS[p.index % LABELS.len()] as char,
+                    None    => '.',
+                });
+            }
+
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+
+const LABELS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+
+pub fn pack(container: &Rectangle, items: &[Rectangle], options: PackOptions) -> Packing {
+    // biggest first: tallest (as it'll lie on its shelf) for shelves, largest area for the others.
+    let key = |rect: &Rectangle| match (options.algorithm, options.allow_rotation) {
+        (Algorithm::Shelf, true)  => rect.width.min(rect.height),
+        (Algorithm::Shelf, false) => rect.height,
+        _                         => rect.get_area(),
+    };
+
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| key(&items[b]).total_cmp(&key(&items[a])));
+
+    let free = Free { x: 0.0, y: 0.0, width: container.width, height: container.height };
+    let mut packer: Box<dyn Packer> = match options.algorithm {
+        Algorithm::Shelf      => Box::new(Shelves { width: container.width, height: container.height, shelves: Vec::new() }),
+        Algorithm::Guillotine => Box::new(Guillotine { free: vec![free] }),
+        Algorithm::MaxRects   => Box::new(MaxRects { free: vec![free] }),
+    };
+
+    let mut packing = Packing {
+        width:      container.width,
+        height:     container.height,
+        placements: Vec::new(),
+        unplaced:   Vec::new(),
+    };
+
+    for index in order {
+        match packer.place(&items[index], options.allow_rotation) {
+            Some((x, y, rotated)) => {
+                let (width, height) = oriented(&items[index], rotated);
+                packing.placements.push(Placement { index, x, y, width, height, rotated });
+            },
+            None => packing.unplaced.push(index),
+        }
+    }
+
+    packing.unplaced.sort();
+    packing
+}
+
+
+fn oriented(rect: &Rectangle, rotated: bool) -> (f64, f64) {
+    match rotated {
+        true  => (rect.height, rect.width),
+        false => (rect.width, rect.height),
+    }
+}
+
+
+// the orientations worth trying for a rectangle:
+fn orientations(rect: &Rectangle, allow_rotation: bool) -> Vec<bool> {
+    match allow_rotation && rect.width != rect.height {
+        true  => vec![false, true],
+        false => vec![false],
+    }
+}
+
+
+// a free area of the container:
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Free {
+    x:      f64,
+    y:      f64,
+    width:  f64,
+    height: f64,
+}
+
+impl Free {
+    fn size(&self) -> Rectangle {
+        Rectangle::new(self.width, self.height)
+    }
+
+    fn contains(&self, other: &Free) -> bool {
+        other.x >= self.x && other.y >= self.y
+            && other.x + other.width <= self.x + self.width
+            && other.y + other.height <= self.y + self.height
+    }
+}
+
+
+// every algorithm answers the same question: where does this rectangle go, if anywhere?
+// (x, y, rotated)
+trait Packer {
+    fn place(&mut self, rect: &Rectangle, allow_rotation: bool) -> Option<(f64, f64, bool)>;
+}
+
+
+struct Shelf {
+    y:      f64,
+    height: f64,
+    used:   f64,  // width already taken up
+}
+
+struct Shelves {
+    width:   f64,
+    height:  f64,
+    shelves: Vec<Shelf>,
+}
+
+impl Packer for Shelves {
+    fn place(&mut self, rect: &Rectangle, allow_rotation: bool) -> Option<(f64, f64, bool)> {
+        // lying down (wide side horizontal) keeps shelves short, so try that first:
+        let mut tries = orientations(rect, allow_rotation);
+        if rect.height > rect.width {
+            tries.reverse();
+        }
+
+        // 1) the first existing shelf with room:
+        for &rotated in &tries {
+            let (w, h) = oriented(rect, rotated);
+            let space = Rectangle::new(w, h);
+
+            if let Some(shelf) = self.shelves.iter_mut().find(|s| Rectangle::new(self.width - s.used, s.height).can_fit(&space)) {
+                let x = shelf.used;
+                shelf.used += w;
+                return Some((x, shelf.y, rotated));
+            }
+        }
+
+        // 2) a new shelf on top of the last one:
+        let top = self.shelves.last().map_or(0.0, |s| s.y + s.height);
+        for &rotated in &tries {
+            let (w, h) = oriented(rect, rotated);
+
+            if Rectangle::new(self.width, self.height - top).can_fit(&Rectangle::new(w, h)) {
+                self.shelves.push(Shelf { y: top, height: h, used: w });
+                return Some((0.0, top, rotated));
+            }
+        }
+
+        None
+    }
+}
+
+
+struct Guillotine {
+    free: Vec<Free>,
+}
+
+impl Packer for Guillotine {
+    fn place(&mut self, rect: &Rectangle, allow_rotation: bool) -> Option<(f64, f64, bool)> {
+        // best area fit: the smallest free rectangle it fits in.
+        let (i, rotated) = best_fit(&self.free, rect, allow_rotation, |free, _, _| free.width * free.height)?;
+
+        let free = self.free.swap_remove(i);
+        let (w, h) = oriented(rect, rotated);
+        let (right, below) = (free.width - w, free.height - h);
+
+        // cut along the shorter leftover side, which leaves one big piece instead of two slivers:
+        let (a, b) = if right < below {
+            (
+                Free { x: free.x + w, y: free.y, width: right, height: h },
+                Free { x: free.x, y: free.y + h, width: free.width, height: below },
+            )
+        } else {
+            (
+                Free { x: free.x + w, y: free.y, width: right, height: free.height },
+                Free { x: free.x, y: free.y + h, width: w, height: below },
+            )
+        };
+
+        self.free.extend([a, b].into_iter().filter(|f| f.width > 0.0 && f.height > 0.0));
+        Some((free.x, free.y, rotated))
+    }
+}
+
+
+struct MaxRects {
+    free: Vec<Free>,
+}
+
+impl Packer for MaxRects {
+    fn place(&mut self, rect: &Rectangle, allow_rotation: bool) -> Option<(f64, f64, bool)> {
+        // best short side fit: the spot that leaves the thinnest leftover strip.
+        let score = |free: &Free, w: f64, h: f64| (free.width - w).min(free.height - h);
+        let (i, rotated) = best_fit(&self.free, rect, allow_rotation, score)?;
+
+        let (w, h) = oriented(rect, rotated);
+        let used = Free { x: self.free[i].x, y: self.free[i].y, width: w, height: h };
+
+        // every free rectangle that overlaps the new one gets split into
+        // up to four smaller ones: the parts to its left, right, top and bottom.
+        let mut next = Vec::new();
+        for free in &self.free {
+            if !overlaps(free, &used) {
+                next.push(*free);
+                continue;
+            }
+
+            let pieces = [
+                Free { width: used.x - free.x, ..*free },
+                Free { x: used.x + used.width, width: free.x + free.width - used.x - used.width, ..*free },
+                Free { height: used.y - free.y, ..*free },
+                Free { y: used.y + used.height, height: free.y + free.height - used.y - used.height, ..*free },
+            ];
+
+            next.extend(pieces.into_iter().filter(|p| p.width > 0.0 && p.height > 0.0));
+        }
+
+        // drop free rectangles that are completely inside another one, they add nothing:
+        let mut maximal: Vec<Free> = Vec::new();
+        for (i, free) in next.iter().enumerate() {
+            let redundant = next.iter().enumerate().any(|(j, other)| {
+                i != j && other.contains(free) && (other != free || j < i)
+            });
+
+            if !redundant {
+                maximal.push(*free);
+            }
+        }
+
+        self.free = maximal;
+        Some((used.x, used.y, rotated))
+    }
+}
+
+
+fn overlaps(a: &Free, b: &Free) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+
+// the free rectangle (and orientation) with the lowest score that the rectangle fits in:
+fn best_fit(
+    free: &[Free],
+    rect: &Rectangle,
+    allow_rotation: bool,
+    score: impl Fn(&Free, f64, f64) -> f64,
+) -> Option<(usize, bool)> {
+    let mut best: Option<(f64, usize, bool)> = None;
+
+    for (i, space) in free.iter().enumerate() {
+        for rotated in orientations(rect, allow_rotation) {
+            let (w, h) = oriented(rect, rotated);
+
+            if !space.size().can_fit(&Rectangle::new(w, h)) {
+                continue;
+            }
+
+            let s = score(space, w, h);
+            if best.is_none_or(|(b, _, _)| s < b) {
+                best = Some((s, i, rotated));
+            }
+        }
+    }
+
+    best.map(|(_, i, rotated)| (i, rotated))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Random;
+
+    // whole numbers, so nothing is off by a rounding error and the checks can be exact:
+    fn random_items(random: &mut Random) -> Vec<Rectangle> {
+        let count = random.below(40);
+        let mut side = || 1.0 + random.below(60) as f64;
+        (0..count).map(|_| Rectangle::new(side(), side())).collect()
+    }
+
+    fn check(packing: &Packing, items: &[Rectangle], options: PackOptions) {
+        let context = format!("{options:?} {}x{}", packing.width, packing.height);
+
+        // every item is either placed or unplaced, exactly once:
+        let placed = packing.placements.iter().map(|p| p.index);
+        let mut seen: Vec<usize> = placed.chain(packing.unplaced.iter().copied()).collect();
+        seen.sort();
+        assert_eq!(seen, (0..items.len()).collect::<Vec<_>>(), "{context}");
+
+        for p in &packing.placements {
+            assert!(options.allow_rotation || !p.rotated, "{context}: rotated {p:?}");
+            assert_eq!((p.width, p.height), oriented(&items[p.index], p.rotated), "{context}");
+
+            let inside = p.x >= 0.0 && p.y >= 0.0 && p.x + p.width <= packing.width && p.y + p.height <= packing.height;
+            assert!(inside, "{context}: {p:?} is outside the container");
+        }
+
+        for (i, a) in packing.placements.iter().enumerate() {
+            for b in &packing.placements[i + 1..] {
+                let apart_x = a.x + a.width <= b.x || b.x + b.width <= a.x;
+                let apart_y = a.y + a.height <= b.y || b.y + b.height <= a.y;
+                let apart = apart_x || apart_y;
+                assert!(apart, "{context}: {a:?} overlaps {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn placements_never_overlap_or_leave_the_container() {
+        let mut random = Random(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..300 {
+            let container = Rectangle::new(20.0 + random.below(180) as f64, 20.0 + random.below(180) as f64);
+            let items = random_items(&mut random);
+
+            for algorithm in [Algorithm::Shelf, Algorithm::Guillotine, Algorithm::MaxRects] {
+                for allow_rotation in [false, true] {
+                    let options = PackOptions { algorithm, allow_rotation };
+                    check(&pack(&container, &items, options), &items, options);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rotation_fits_what_wouldnt_otherwise() {
+        let container = Rectangle::new(10.0, 2.0);
+        let plank = [Rectangle::new(2.0, 10.0)];
+
+        for algorithm in [Algorithm::Shelf, Algorithm::Guillotine, Algorithm::MaxRects] {
+            let fixed = pack(&container, &plank, PackOptions { algorithm, allow_rotation: false });
+            let turned = pack(&container, &plank, PackOptions { algorithm, allow_rotation: true });
+
+            assert_eq!(fixed.unplaced, [0], "{algorithm:?}");
+            assert_eq!(turned.placements.len(), 1, "{algorithm:?}");
+            assert!(turned.placements[0].rotated);
+            assert_eq!(turned.fill_ratio(), 1.0);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+// Polygons, plus the polygon math that Rectangle and Triangle reuse.
+
+use super::{Outline, Point, Shape, cross, on_segment, svg};
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    pub points: Vec<Point>,
+}
+
+impl Polygon {
+    // None if there aren't enough points to enclose any area:
+    pub fn new(points: Vec<Point>) -> Option<Self> {
+        (points.len() >= 3).then_some(Self { points })
+    }
+
+    // a regular polygon (all sides and angles equal), with its first corner straight up:
+    pub fn regular(center: Point, radius: f64, sides: usize) -> Option<Self> {
+        let step = std::f64::consts::TAU / sides as f64;
+        let top = center.translated(0.0, -radius);
+
+        Self::new((0..sides).map(|i| top.rotated_about(center, step * i as f64)).collect())
+    }
+}
+
+impl Shape for Polygon {
+    fn area(&self) -> f64 {
+        area(&self.points)
+    }
+
+    fn perimeter(&self) -> f64 {
+        perimeter(&self.points)
+    }
+
+    fn center(&self) -> Point {
+        centroid(&self.points)
+    }
+
+    fn outline(&self) -> Outline {
+        Outline::Polygon(self.points.clone())
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        contains(&self.points, point)
+    }
+
+    fn translate(&mut self, dx: f64, dy: f64) {
+        self.points.iter_mut().for_each(|p| *p = p.translated(dx, dy));
+    }
+
+    fn scale(&mut self, factor: f64) {
+        let center = self.center();
+        self.points.iter_mut().for_each(|p| *p = p.scaled_about(center, factor));
+    }
+
+    fn rotate(&mut self, radians: f64) {
+        let center = self.center();
+        self.points.iter_mut().for_each(|p| *p = p.rotated_about(center, radians));
+    }
+
+    fn to_svg(&self) -> String {
+        svg::polygon(&self.points)
+    }
+}
+
+
+// every edge of the polygon, including the one that closes it (last point -> first point):
+pub fn edges(points: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    points.iter().zip(points.iter().cycle().skip(1)).map(|(&a, &b)| (a, b))
+}
+
+
+// * shoelace formula: add up the cross products of every edge.
+// it comes out negative if the points go the other way around, so take the absolute value.
+pub fn area(points: &[Point]) -> f64 {
+    signed_area(points).abs()
+}
+
+
+fn signed_area(points: &[Point]) -> f64 {
+    edges(points).map(|(a, b)| a.x * b.y - b.x * a.y).sum::<f64>() / 2.0
+}
+
+
+pub fn perimeter(points: &[Point]) -> f64 {
+    edges(points).map(|(a, b)| a.distance(b)).sum()
+}
+
+
+// the center of mass of the polygon's area (not just the average of its corners):
+pub fn centroid(points: &[Point]) -> Point {
+    let area = signed_area(points);
+
+    // a degenerate polygon (all points on a line) has no area to balance, so average the points:
+    if area == 0.0 {
+        let n = points.len() as f64;
+        return Point::new(
+            points.iter().map(|p| p.x).sum::<f64>() / n,
+            points.iter().map(|p| p.y).sum::<f64>() / n,
+        );
+    }
+
+    let (x, y) = edges(points).fold((0.0, 0.0), |(x, y), (a, b)| {
+        let step = a.x * b.y - b.x * a.y;
+        (x + (a.x + b.x) * step, y + (a.y + b.y) * step)
+    });
+
+    Point::new(x / (6.0 * area), y / (6.0 * area))
+}
+
+
+// * ray casting: shoot a ray from the point to the right and count how many edges it crosses.
+// an odd number of crossings means the point is inside.
+pub fn contains(points: &[Point], point: Point) -> bool {
+    let mut inside = false;
+
+    for (a, b) in edges(points) {
+        // points exactly on an edge count as inside:
+        if cross(a, b, point) == 0.0 && on_segment(a, b, point) {
+            return true;
+        }
+
+        if (a.y > point.y) != (b.y > point.y) {
+            let x = a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+    }
+
+    inside
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_on_edges_and_corners_are_inside() {
+        // an L shape, so there's a concave corner too:
+        let corners = [(0.0, 0.0), (4.0, 0.0), (4.0, 2.0), (2.0, 2.0), (2.0, 4.0), (0.0, 4.0)];
+        let l = Polygon::new(corners.map(|(x, y)| Point::new(x, y)).to_vec()).expect("enough points");
+
+        let cases = [
+            ((1.0, 1.0),    true),
+            ((3.0, 3.0),    false),  // in the missing corner of the L
+            ((0.0, 0.0),    true),   // corners
+            ((4.0, 2.0),    true),
+            ((2.0, 2.0),    true),   // the concave one
+            ((2.0, 0.0),    true),   // edges
+            ((3.0, 2.0),    true),
+            ((2.0, 3.0),    true),
+            ((0.0, 4.0),    true),
+            ((4.001, 1.0),  false),  // just outside
+            ((1.0, -0.001), false),
+            ((3.0, 2.001),  false),
+            ((3.999, 1.0),  true),   // just inside
+            ((6.0, 0.0),    false),  // in line with an edge, but past it
+        ];
+
+        for ((x, y), inside) in cases {
+            assert_eq!(l.contains(Point::new(x, y)), inside, "({x}, {y})");
+        }
+    }
+}
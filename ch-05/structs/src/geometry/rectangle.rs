@@ -0,0 +1,166 @@
+// Rectangles.
+
+use super::{Outline, Point, Shape, polygon, svg};
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+    pub width: f64,
+    pub height: f64,
+
+    // where it is, and how much it's rotated around its center:
+    pub center: Point,
+    pub angle: f64,
+}
+
+// all associated functions and behaviors of the Rectangle struct go here:
+impl Rectangle {
+    // "Self"  = alias for the type of the impl block
+    // "&self" = alias for an immutable reference to the instance the method is being called on
+
+    pub fn new(width: f64, height: f64) -> Self {
+        // return a new Rectangle instance with the given parameters,
+        // with its top-left corner at (0, 0):
+        Self::at(Point::default(), width, height)
+    }
+
+    pub fn square(dimension: f64) -> Self {
+        // return a new Rectangle instance with the same width and height
+        Self::new(dimension, dimension)
+    }
+
+    // a rectangle with its (unrotated) top-left corner at the given point:
+    pub fn at(corner: Point, width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            center: corner.translated(width / 2.0, height / 2.0),
+            angle: 0.0,
+        }
+    }
+
+    pub fn get_area(&self) -> f64 {
+        // calculate the area of the instance the method is called on
+        self.width * self.height
+    }
+
+    pub fn can_hold(&self, other: &Rectangle) -> bool {
+        // can this instance hold another Rectangle inside of itself?
+        self.width > other.width && self.height > other.height
+    }
+
+    // like can_hold(), but an exact fit counts too (packing needs rectangles to sit flush):
+    pub fn can_fit(&self, other: &Rectangle) -> bool {
+        self.width >= other.width && self.height >= other.height
+    }
+
+    // * can the other rectangle fit inside if it's allowed to turn any amount?
+    // - turning it 90 degrees is the obvious one (swapping width and height).
+    // - a long, thin rectangle can also fit diagonally when it's too long to fit straight.
+    // for a p x q rectangle (p >= q) in an a x b one (a >= b), it fits diagonally when:
+    // ? p > a  and  b > (2pqa + (p² - q²) * sqrt(p² + q² - a²)) / (p² + q²)
+    // (Carver's condition; like can_hold(), the fit has to be strict.)
+    pub fn can_hold_rotated(&self, other: &Rectangle) -> bool {
+        let (a, b) = (self.width.max(self.height), self.width.min(self.height));
+        let (p, q) = (other.width.max(other.height), other.width.min(other.height));
+
+        if p < a && q < b {
+            return true;
+        }
+        if q >= b || p * p + q * q <= a * a {
+            return false;
+        }
+
+        let needed = (2.0 * p * q * a + (p * p - q * q) * (p * p + q * q - a * a).sqrt()) / (p * p + q * q);
+        b > needed
+    }
+
+    // the four corners, clockwise from the top-left (before rotation):
+    pub fn corners(&self) -> [Point; 4] {
+        let (w, h) = (self.width / 2.0, self.height / 2.0);
+
+        [(-w, -h), (w, -h), (w, h), (-w, h)]
+            .map(|(dx, dy)| self.center.translated(dx, dy).rotated_about(self.center, self.angle))
+    }
+}
+
+impl Shape for Rectangle {
+    fn area(&self) -> f64 {
+        self.get_area()
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.width + self.height)
+    }
+
+    fn center(&self) -> Point {
+        self.center
+    }
+
+    fn outline(&self) -> Outline {
+        Outline::Polygon(self.corners().to_vec())
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        // rotate the point the other way, so the rectangle can be treated as unrotated:
+        let p = point.rotated_about(self.center, -self.angle);
+
+        (p.x - self.center.x).abs() <= self.width / 2.0
+            && (p.y - self.center.y).abs() <= self.height / 2.0
+    }
+
+    fn translate(&mut self, dx: f64, dy: f64) {
+        self.center = self.center.translated(dx, dy);
+    }
+
+    fn scale(&mut self, factor: f64) {
+        self.width *= factor.abs();
+        self.height *= factor.abs();
+    }
+
+    fn rotate(&mut self, radians: f64) {
+        self.angle += radians;
+    }
+
+    fn to_svg(&self) -> String {
+        svg::polygon(&self.corners())
+    }
+}
+
+
+// a rectangle is a polygon too, so it can be turned into one (say, to skew it later):
+impl From<Rectangle> for polygon::Polygon {
+    fn from(rect: Rectangle) -> Self {
+        polygon::Polygon { points: rect.corners().to_vec() }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_hold_rotated_follows_carvers_condition() {
+        let cases = [
+            // (container, other, fits)
+            ((10.0, 10.0), (9.99, 9.99),  true),
+            ((10.0, 10.0), (10.0, 5.0),   false),  // the fit has to be strict
+            ((9.5, 6.0),   (5.0, 9.0),    true),   // turned 90 degrees
+            ((9.5, 6.0),   (6.0, 9.5),    false),
+            // a plank in a square: too long to fit straight, but it fits diagonally,
+            // up to a length of about 13.1421 for a 1 wide plank in a 10 x 10 square:
+            ((10.0, 10.0), (13.0, 1.0),   true),
+            ((10.0, 10.0), (1.0, 13.13),  true),
+            ((10.0, 10.0), (13.15, 1.0),  false),
+            ((10.0, 10.0), (14.0, 0.1),   true),   // thin enough to go nearly corner to corner
+            ((10.0, 10.0), (14.2, 0.1),   false),  // longer than the diagonal
+            ((10.0, 10.0), (11.0, 10.0),  false),  // as wide as the square
+        ];
+
+        for ((a, b), (p, q), fits) in cases {
+            let (container, other) = (Rectangle::new(a, b), Rectangle::new(p, q));
+            assert_eq!(container.can_hold_rotated(&other), fits, "{p} x {q} in {a} x {b}");
+        }
+    }
+}
@@ -0,0 +1,342 @@
+// Spatial index for rectangular regions.
+// Answering "which regions contain this point?" by checking every region is fine for
+// a dozen of them, but with thousands it gets slow. a quadtree splits space into
+// four quarters, then each busy quarter into four more, and so on, so a query only
+// has to look at the regions in the quarters it actually touches.
+//
+// * QuadTree:
+// - insert(region) -> RegionId
+// - remove(id)
+// - query_point(point):   regions that contain the point
+// - query_range(region):  regions that overlap the region
+// - nearest(point):       the closest region to the point (distance 0 if it's inside one)
+//
+// a region that straddles the line between two quarters stays in the bigger node above them.
+// regions outside the tree's bounds are fine too, they just stay in the root.
+
+use super::{Point, Rectangle};
+
+
+// an axis-aligned rectangle with a position: the top-left corner plus a size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+    pub origin: Point,
+    pub width:  f64,
+    pub height: f64,
+}
+
+impl Region {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self { origin: Point::new(x, y), width, height }
+    }
+
+    pub fn right(&self) -> f64 {
+        self.origin.x + self.width
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.origin.y + self.height
+    }
+
+    // edges count as inside:
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= self.origin.x && point.x <= self.right()
+            && point.y >= self.origin.y && point.y <= self.bottom()
+    }
+
+    pub fn contains_region(&self, other: &Region) -> bool {
+        other.origin.x >= self.origin.x && other.right() <= self.right()
+            && other.origin.y >= self.origin.y && other.bottom() <= self.bottom()
+    }
+
+    // touching edges count as overlapping:
+    pub fn intersects(&self, other: &Region) -> bool {
+        self.origin.x <= other.right() && other.origin.x <= self.right()
+            && self.origin.y <= other.bottom() && other.origin.y <= self.bottom()
+    }
+
+    // 0 if the point is inside:
+    pub fn distance_to(&self, point: Point) -> f64 {
+        let dx = (self.origin.x - point.x).max(point.x - self.right()).max(0.0);
+        let dy = (self.origin.y - point.y).max(point.y - self.bottom()).max(0.0);
+        dx.hypot(dy)
+    }
+
+    fn quarters(&self) -> [Region; 4] {
+        let (w, h) = (self.width / 2.0, self.height / 2.0);
+        let Point { x, y } = self.origin;
+
+        [
+            Region::new(x, y, w, h),
+            Region::new(x + w, y, w, h),
+            Region::new(x, y + h, w, h),
+            Region::new(x + w, y + h, w, h),
+        ]
+    }
+}
+
+impl From<Region> for Rectangle {
+    fn from(region: Region) -> Self {
+        Rectangle::at(region.origin, region.width, region.height)
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RegionId(usize);
+
+
+#[derive(Debug, Clone)]
+struct Node {
+    bounds:   Region,
+    depth:    usize,
+    items:    Vec<RegionId>,
+    children: Option<[usize; 4]>,  // indices into QuadTree::nodes
+}
+
+
+#[derive(Debug, Clone)]
+pub struct QuadTree {
+    nodes:     Vec<Node>,
+    regions:   Vec<Option<Region>>,  // indexed by RegionId, None once removed
+    locations: Vec<usize>,           // which node each region is stored in
+    len:       usize,
+    max_items: usize,                // a node splits once it has more than this
+    max_depth: usize,                // ...unless it's already this deep
+}
+
+impl QuadTree {
+    pub fn new(bounds: Region) -> Self {
+        Self::with_limits(bounds, 8, 10)
+    }
+
+    pub fn with_limits(bounds: Region, max_items: usize, max_depth: usize) -> Self {
+        Self {
+            nodes:     vec![Node { bounds, depth: 0, items: Vec::new(), children: None }],
+            regions:   Vec::new(),
+            locations: Vec::new(),
+            len:       0,
+            max_items,
+            max_depth,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, id: RegionId) -> Option<&Region> {
+        self.regions.get(id.0)?.as_ref()
+    }
+
+    pub fn insert(&mut self, region: Region) -> RegionId {
+        let id = RegionId(self.regions.len());
+        self.regions.push(Some(region));
+        self.locations.push(0);
+        self.len += 1;
+
+        let node = self.deepest_fit(0, &region);
+        self.nodes[node].items.push(id);
+        self.locations[id.0] = node;
+        self.split_if_full(node);
+
+        id
+    }
+
+    pub fn remove(&mut self, id: RegionId) -> Option<Region> {
+        let region = self.regions.get_mut(id.0)?.take()?;
+        let node = self.locations[id.0];
+
+        self.nodes[node].items.retain(|&item| item != id);
+        self.len -= 1;
+
+        Some(region)
+    }
+
+    pub fn query_point(&self, point: Point) -> Vec<RegionId> {
+        let mut found = Vec::new();
+        let mut stack = vec![0];
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+
+            found.extend(node.items.iter().filter(|&&id| self.region(id).contains(point)));
+
+            if let Some(children) = node.children {
+                stack.extend(children.into_iter().filter(|&c| self.nodes[c].bounds.contains(point)));
+            }
+        }
+
+        found
+    }
+
+    pub fn query_range(&self, range: &Region) -> Vec<RegionId> {
+        let mut found = Vec::new();
+        let mut stack = vec![0];
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+
+            found.extend(node.items.iter().filter(|&&id| self.region(id).intersects(range)));
+
+            if let Some(children) = node.children {
+                stack.extend(children.into_iter().filter(|&c| self.nodes[c].bounds.intersects(range)));
+            }
+        }
+
+        found
+    }
+
+    // * branch and bound:
+    // visit the closest quarters first, and skip any quarter that's further away
+    // than the best region found so far, since nothing inside it could be closer.
+    pub fn nearest(&self, point: Point) -> Option<(RegionId, f64)> {
+        let mut best: Option<(RegionId, f64)> = None;
+        self.nearest_in(0, point, &mut best);
+        best
+    }
+
+    fn nearest_in(&self, node: usize, point: Point, best: &mut Option<(RegionId, f64)>) {
+        let node = &self.nodes[node];
+
+        for &id in &node.items {
+            let distance = self.region(id).distance_to(point);
+            if best.is_none_or(|(_, d)| distance < d) {
+                *best = Some((id, distance));
+            }
+        }
+
+        let Some(children) = node.children else {
+            return;
+        };
+
+        let mut children = children.map(|c| (c, self.nodes[c].bounds.distance_to(point)));
+        children.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        for (child, distance) in children {
+            if best.is_none_or(|(_, d)| distance < d) {
+                self.nearest_in(child, point, best);
+            }
+        }
+    }
+
+    fn region(&self, id: RegionId) -> &Region {
+        self.regions[id.0].as_ref().expect("removed regions aren't stored in any node")
+    }
+
+    // walks down from a node to the deepest existing node that fully contains the region:
+    fn deepest_fit(&self, mut node: usize, region: &Region) -> usize {
+        while let Some(children) = self.nodes[node].children {
+            match children.into_iter().find(|&c| self.nodes[c].bounds.contains_region(region)) {
+                Some(child) => node = child,
+                None        => break,
+            }
+        }
+
+        node
+    }
+
+    fn split_if_full(&mut self, node: usize) {
+        let Node { bounds, depth, ref items, children } = self.nodes[node];
+
+        if children.is_some() || items.len() <= self.max_items || depth >= self.max_depth {
+            return;
+        }
+
+        let first = self.nodes.len();
+        for quarter in bounds.quarters() {
+            self.nodes.push(Node { bounds: quarter, depth: depth + 1, items: Vec::new(), children: None });
+        }
+        self.nodes[node].children = Some([first, first + 1, first + 2, first + 3]);
+
+        // move every region that fits in a quarter down into it:
+        let items = std::mem::take(&mut self.nodes[node].items);
+        for id in items {
+            let target = self.deepest_fit(node, self.region(id));
+            self.nodes[target].items.push(id);
+            self.locations[id.0] = target;
+        }
+
+        for child in first..first + 4 {
+            self.split_if_full(child);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Random;
+
+    const WORLD: f64 = 1_000.0;
+
+    // * every query is answered twice, once by the tree and once by checking every region,
+    // and the answers have to match exactly:
+    fn cross_check(mut tree: QuadTree, seed: u64) {
+        let mut random = Random(seed);
+
+        let regions: Vec<Region> = (0..2_000)
+            .map(|_| Region::new(random.float(WORLD), random.float(WORLD), random.float(40.0) + 1.0, random.float(40.0) + 1.0))
+            .collect();
+        let ids: Vec<RegionId> = regions.iter().map(|&r| tree.insert(r)).collect();
+
+        // remove every tenth region, so removal gets checked too:
+        let mut alive = vec![true; regions.len()];
+        for i in (0..regions.len()).step_by(10) {
+            assert_eq!(tree.remove(ids[i]), Some(regions[i]));
+            assert_eq!(tree.remove(ids[i]), None);
+            alive[i] = false;
+        }
+        assert_eq!(tree.len(), alive.iter().filter(|&&a| a).count());
+
+        let live = || regions.iter().zip(&ids).zip(&alive).filter(|&(_, &a)| a).map(|(pair, _)| pair);
+
+        for _ in 0..500 {
+            let point = Point::new(random.float(WORLD), random.float(WORLD));
+            let range = Region::new(point.x, point.y, 60.0, 60.0);
+
+            let mut expected: Vec<RegionId> = live().filter(|(r, _)| r.contains(point)).map(|(_, &id)| id).collect();
+            let mut found = tree.query_point(point);
+            expected.sort();
+            found.sort();
+            assert_eq!(found, expected, "point query at {point:?}");
+
+            let mut expected: Vec<RegionId> = live().filter(|(r, _)| r.intersects(&range)).map(|(_, &id)| id).collect();
+            let mut found = tree.query_range(&range);
+            expected.sort();
+            found.sort();
+            assert_eq!(found, expected, "range query {range:?}");
+
+            // several regions can be equally close (say, two that both contain the point),
+            // so compare distances instead of ids:
+            let expected = live().map(|(r, _)| r.distance_to(point)).min_by(f64::total_cmp);
+            let found = tree.nearest(point).map(|(_, distance)| distance);
+            assert_eq!(found, expected, "nearest to {point:?}");
+        }
+    }
+
+    #[test]
+    fn queries_match_brute_force() {
+        cross_check(QuadTree::new(Region::new(0.0, 0.0, WORLD, WORLD)), 0x5eed);
+    }
+
+    #[test]
+    fn queries_match_brute_force_with_tiny_nodes() {
+        // splitting after every couple of regions makes a deep tree, with lots of regions on the lines between nodes:
+        cross_check(QuadTree::with_limits(Region::new(0.0, 0.0, WORLD, WORLD), 2, 12), 0xfeed);
+    }
+
+    #[test]
+    fn an_empty_tree_finds_nothing() {
+        let tree = QuadTree::new(Region::new(0.0, 0.0, WORLD, WORLD));
+
+        assert!(tree.is_empty());
+        assert_eq!(tree.query_point(Point::new(1.0, 1.0)), []);
+        assert_eq!(tree.nearest(Point::new(1.0, 1.0)), None);
+    }
+}
@@ -0,0 +1,53 @@
+// SVG output.
+// Puts shapes in an SVG file so the results of transformations can be looked at in a browser.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::{BoundingBox, Point, Shape};
+
+
+// the space left around the shapes, so the outlines aren't cut off at the edges:
+const MARGIN: f64 = 10.0;
+
+
+// an SVG document with every shape in it, sized to fit all of them:
+pub fn document(shapes: &[&dyn Shape]) -> String {
+    let bounds = shapes
+        .iter()
+        .map(|s| s.bounding_box())
+        .reduce(|a, b| a.union(&b))
+        .unwrap_or(BoundingBox { min: Point::default(), max: Point::default() });
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        bounds.min.x - MARGIN,
+        bounds.min.y - MARGIN,
+        bounds.width() + 2.0 * MARGIN,
+        bounds.height() + 2.0 * MARGIN,
+    );
+    svg.push('\n');
+    svg.push_str(r#"<g fill="none" stroke="black" stroke-width="1">"#);
+    svg.push('\n');
+
+    for shape in shapes {
+        svg.push_str("  ");
+        svg.push_str(&shape.to_svg());
+        svg.push('\n');
+    }
+
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+
+pub fn save(path: impl AsRef<Path>, shapes: &[&dyn Shape]) -> io::Result<()> {
+    fs::write(path, document(shapes))
+}
+
+
+pub(crate) fn polygon(points: &[Point]) -> String {
+    let points: Vec<String> = points.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+    format!(r#"<polygon points="{}"/>"#, points.join(" "))
+}
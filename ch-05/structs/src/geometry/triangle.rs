@@ -0,0 +1,69 @@
+// Triangles.
+
+use super::{Outline, Point, Shape, polygon, svg};
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+    pub a: Point,
+    pub b: Point,
+    pub c: Point,
+}
+
+impl Triangle {
+    pub fn new(a: Point, b: Point, c: Point) -> Self {
+        Self { a, b, c }
+    }
+
+    fn points(&self) -> [Point; 3] {
+        [self.a, self.b, self.c]
+    }
+
+    fn map(&mut self, f: impl Fn(Point) -> Point) {
+        [self.a, self.b, self.c] = self.points().map(f);
+    }
+}
+
+impl Shape for Triangle {
+    fn area(&self) -> f64 {
+        polygon::area(&self.points())
+    }
+
+    fn perimeter(&self) -> f64 {
+        polygon::perimeter(&self.points())
+    }
+
+    // for a triangle, the centroid is just the average of the corners:
+    fn center(&self) -> Point {
+        Point::new(
+            (self.a.x + self.b.x + self.c.x) / 3.0,
+            (self.a.y + self.b.y + self.c.y) / 3.0,
+        )
+    }
+
+    fn outline(&self) -> Outline {
+        Outline::Polygon(self.points().to_vec())
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        polygon::contains(&self.points(), point)
+    }
+
+    fn translate(&mut self, dx: f64, dy: f64) {
+        self.map(|p| p.translated(dx, dy));
+    }
+
+    fn scale(&mut self, factor: f64) {
+        let center = self.center();
+        self.map(|p| p.scaled_about(center, factor));
+    }
+
+    fn rotate(&mut self, radians: f64) {
+        let center = self.center();
+        self.map(|p| p.rotated_about(center, radians));
+    }
+
+    fn to_svg(&self) -> String {
+        svg::polygon(&self.points())
+    }
+}
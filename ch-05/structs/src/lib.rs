@@ -0,0 +1,11 @@
+// Structs!
+// The User, Rectangle, Color and Point types from the chapter, grown into small libraries.
+
+pub mod access;
+pub mod color;
+pub mod geometry;
+pub mod store;
+#[doc(hidden)]
+pub mod test_support;
+pub mod user;
+pub mod vector;
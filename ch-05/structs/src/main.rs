@@ -1,22 +1,26 @@
 // Structs!
 
-struct User {
-    active: bool,
-    sign_in_count: u64,
-
-    // ? why do these have to be of type String and not &str?
-    // - to deliberately make instances of this own all of their data.
-    // - structs can store references to values they don't own, but it gets complicated.
-    username: String,
-    email: String,
-}
+mod cli;
+
+use std::env;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+use std::time::Duration;
+
+use structs::access::Policy;
+use structs::color::{BlendMode, Color, Hsl, palette};
+use structs::geometry::spatial::{QuadTree, Region};
+use structs::geometry::{Circle, Point, Polygon, Rectangle, Shape, Triangle, svg};
+use structs::user::clock::ManualClock;
+use structs::user::{Email, Format, SignInPolicy, SignInState, User, Username, View, build_user};
+use structs::vector::{Mat4, Point3, Quaternion, Vec3};
+
 
 // tuple struct declataration:
 // - no attribute names, just types.
 // - even if all the attribute types are the same, Color and Point are separate types.
-
-struct Color(i32, i32, i32);
-struct Point(i32, i32, i32);
+// ? struct Color(i32, i32, i32);
+// ? struct Point(i32, i32, i32);
+// (both grew into proper library types: structs::color::Color and structs::vector::Vec3)
 
 
 // * unit-like struct with no field:
@@ -24,82 +28,170 @@ struct Point(i32, i32, i32);
 // ? struct AlwaysEqual;
 
 
-struct Rectangle {
-    width: f64,
-    height: f64
-}
-
-// all associated functions and behaviors of the Rectangle struct go here:
-impl Rectangle {
-    // "Self"  = alias for the type of the impl block
-    // "&self" = alias for an immutable reference to the instance the method is being called on
-
-    fn new(width: f64, height: f64) -> Self {
-        // return a new Rectangle instance with the given parameters
-        Self {
-            width,
-            height
-        }
-    }
-
-    fn square(dimension: f64) -> Self {
-        // return a new Rectangle instance with the same width and height
-        Self {
-            width: dimension,
-            height: dimension
-        }
-    }
-
-    fn get_area(&self) -> f64 {
-        // calculate the area of the instance the method is called on
-        self.width * self.height
-    }
+fn main() {
+    // * `cargo run -- <tool> ...` runs one of the command line tools instead of the examples:
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    fn can_hold(&self, other: &Rectangle) -> bool {
-        // can this instance hold another Rectangle inside of itself?
-        self.width > other.width && self.height > other.height
+    match args.first().map(String::as_str) {
+        Some("user") => return cli::user::run(&args[1..]),
+        Some("pack") => return cli::pack::run(&args[1..]),
+        _            => (),
     }
-}
-
-
-fn main() {
 
     let user1 = User {
         active: true,
         sign_in_count: 11,
-        username: String::from("my_user123"),
-        email: String::from("my_email@example.com"),
+        username: Username::parse("my_user123").expect("valid username"),
+        email: Email::parse("my_email@example.com").expect("valid email"),
+        auth: SignInState::default(),
     };
 
     // * This would only work if the entire user is mutable;
-    // ? user1.username = String::from("new_name321");
+    // ? user1.username = Username::parse("new_name321")?;
     // - a struct can't mix and match mutable and immutable attributes.
 
     let user2 = build_user(
-        String::from("user-2"),
-        String::from("email_two@example.com")
+        Username::parse("user-2").expect("valid username"),
+        Email::parse("email_two@example.com").expect("valid email"),
     );
 
-    let user3 = User {
-        email: String::from("new_name@email.com"),
-        ..user1  // fill unspecified attributes with values from another instance
-    };
-
-    print_user(&user2);
-    print_user(&user3);
+    // build_user() only takes validated values, so garbage gets caught before it's a User
+    // (the tests in user/email.rs and user/username.rs go through every way it can be wrong):
+    if let Err(e) = Email::parse("my_email@example") {
+        println!("\"my_email@example\" isn't an email: {e}");
+    }
+    if let Err(e) = Username::parse("9lives") {
+        println!("\"9lives\" isn't a username: {e}");
+    }
+    println!();
 
-    // * because user1 contains a String, when user3 is created with values from user1,
-    // * the username String is *moved*, not copied, so it can't be used anymore:
+    // * struct update syntax fills unspecified attributes with values from another instance:
+    // ? let user3 = User { email: Email::parse("new_name@email.com")?, ..user1 };
+    // because user1 contains a Username (which holds a String), when user3 is created
+    // with values from user1, the username is *moved*, not copied, so it can't be used anymore:
     // ! println!("{}", user1.username);  // borrows a moved value!
 
-    // - this would still be valid, since the int and bool attributes were *copied* to user2
+    // - this would still be valid, since the int and bool attributes were *copied* to user3
     // ? println!("{}", user1.sign_in_count);
     // - if part of user1 is now invalidated, should the whole instance be invalidated?
 
-    let black = Color(0, 0, 0);
-    let origin = Point(black.0, black.1, black.2);
+    // * with_email() clones the other fields instead, so user1 stays whole:
+    let user3 = user1.with_email(Email::parse("new_name@email.com").expect("valid email"));
+
+    // * and the builder starts from scratch (or from a template, with to_builder()).
+    // leaving out a required field is caught by the compiler:
+    // ! User::builder().username(name).build();  // error: can't build a `User` without `email`
+    let user4 = user1
+        .to_builder()
+        .username(Username::parse("user_four").expect("valid username"))
+        .sign_in_count(0)
+        .build();
+
+    // * a list of users can be printed as a table for people, or JSON, CSV or YAML for scripts:
+    let users = [&user2, &user3, &user4];
+    let view = View::parse("username,email,sign_in_count", "-sign_in_count").expect("valid columns");
+
+    print!("{}", Format::Table.render(users, &view));
+    println!();
+    print!("{}", Format::Json.render(users, &View::parse("username,active", "username").expect("valid columns")));
+    println!("user1 is still {}", user1.username);
+    println!();
+
+    // * what can they do? the policy gives users roles, and roles give permissions:
+    let policy: Policy = include_str!("../policy.conf").parse().expect("the example policy is valid");
+    let checks = [
+        (&user1, "publish", "posts/42"),
+        (&user1, "read", "comments/3"),
+        (&user1, "delete", "comments/pinned/7"),
+        (&user2, "create", "comments/9"),
+        (&user2, "edit", "posts/42"),
+        (&user4, "read", "posts/42"),
+    ];
 
-    println!("Test Point: {}, {}, {}", origin.0, origin.1, origin.2);
+    for (user, action, resource) in checks {
+        println!("Can {} {action} {resource}? {}", user.username, policy.can(user, action, resource));
+    }
+    println!();
+
+    // * signing in finally makes sign_in_count count something.
+    // the clock is a ManualClock, so the lockout can be waited out instantly:
+    let mut user2 = user2;
+    let clock = ManualClock::at_unix(1_700_000_000);
+    let policy = SignInPolicy { max_attempts: 3, ..SignInPolicy::default() };
+
+    user2.set_password("correct horse battery staple");
+
+    for attempt in ["hunter2", "password123", "letmein", "correct horse battery staple"] {
+        match user2.sign_in_with(attempt, &clock, &policy) {
+            Ok(session) => println!("Signed in! Session expires in {:?}", session.remaining(&clock)),
+            Err(e)      => println!("Sign-in failed: {e}"),
+        }
+    }
+
+    clock.advance(policy.cooldown + Duration::from_secs(1));
+
+    if let Ok(session) = user2.sign_in_with("correct horse battery staple", &clock, &policy) {
+        println!("Signed in after the cooldown, sign-in count: {}", user2.sign_in_count);
+
+        clock.advance(policy.session_ttl);
+        println!("Is the session still valid a day later? {}", session.is_valid(&clock));
+    }
+    println!();
+
+    let black = Color::BLACK;
+    let origin = Point3::new(i32::from(black.r), i32::from(black.g), i32::from(black.b));
+
+    println!("Test Point: {origin}");
+
+    // * points can be calculated with now, whole numbers or decimals:
+    let a = Vec3::new(1, 2, 3);
+    let b = Vec3::new(4, -5, 6);
+
+    println!("{a} + {b} = {}, {a} x {b} = {}, {a} . {b} = {}", a + b, a.cross(b), a.dot(b));
+    println!("Distance from {origin} to {b}: {:.3} (manhattan: {})", origin.distance(b), (b - origin).manhattan());
+
+    // rotate a quarter turn around z, then move up 10:
+    let transform = Mat4::translation(Vec3::new(0.0, 0.0, 10.0)) * Mat4::rotation_z(FRAC_PI_2);
+    let point = a.to_f64();
+
+    println!("{point} transformed: {:.3}", transform * point);
+    println!("...and back: {:.3}", transform.inverse().expect("rotations can be undone") * (transform * point));
+
+    // the same rotation as a quaternion, and halfway there:
+    let spin = Quaternion::from_axis_angle(Vec3::Z, FRAC_PI_2).expect("Z is a real axis");
+    println!("Quaternion rotation: {:.3}", spin * point);
+    println!("Halfway: {:.3}", Quaternion::IDENTITY.slerp(spin, 0.5) * point);
+
+    // * colors can't go out of range anymore, out of range numbers get clamped on the way in:
+    println!("Color(-5, 900, 0) is now {}", Color::clamped(-5, 900, 0));
+
+    let tomato: Color = "tomato".parse().expect("a CSS color name");
+    let sky = Color::from_hex("#87ceeb").expect("valid hex");
+    let hsl = tomato.to_hsl();
+
+    println!("tomato is {tomato}, hsl({:.0}, {:.0}%, {:.0}%)", hsl.h, hsl.s * 100.0, hsl.l * 100.0);
+    println!("#87ceeb is called {:?}", sky.name());
+    println!("hsl(270, 50%, 40%) is {}", Hsl::new(270.0, 0.5, 0.4).to_color());
+
+    // half see-through tomato over sky blue, then the same two with a few blend modes:
+    println!("tomato at 50% over sky blue: {}", tomato.with_alpha(128).over(sky));
+    for mode in [BlendMode::Multiply, BlendMode::Screen, BlendMode::Overlay, BlendMode::Difference] {
+        println!("- {mode:?}: {}", tomato.blend(sky, mode));
+    }
+
+    // is white text readable on these?
+    for background in [tomato, sky, Color::BLACK] {
+        println!(
+            "White on {background}: {:.2}:1 ({:?})",
+            Color::WHITE.contrast_ratio(background), Color::WHITE.contrast_level(background)
+        );
+    }
+
+    let hexes = |colors: &[Color]| colors.iter().map(|c| c.to_hex()).collect::<Vec<_>>().join(" ");
+    println!("Triadic: {}", hexes(&palette::triadic(tomato)));
+    println!("Tints: {}", hexes(&palette::tints(tomato, 4)));
+    println!("Gradient: {}", hexes(&palette::gradient(tomato, sky, 5)));
+    println!();
 
     // methods that don't require an existing instance are essentially namespaced to the struct
     let rect = Rectangle::new(3.1, 4.8);
@@ -110,25 +202,59 @@ fn main() {
         "Can the rectangle fit inside the square? {}",
         match square.can_hold(&rect) { true => "Yes!", false => "Nope." }
     );
-}
 
+    // * a long, thin plank doesn't fit in the square straight, but it does diagonally:
+    let plank = Rectangle::new(5.0, 0.3);
+    println!("Plank fits straight? {}", square.can_hold(&plank));
+    println!("Plank fits at an angle? {}", square.can_hold_rotated(&plank));
+    println!();
 
-fn build_user(username: String, email: String) -> User {
-    // because the username and email parameters have the same
-    // name as the struct attributes, they don't have to be repeated
-    User {
-        active: true,
-        username,
-        email,
-        sign_in_count: 1,
+    // * Rectangle is one of several shapes now, and they can all be handled the same way:
+    let mut tilted = Rectangle::at(Point::new(0.0, 0.0), 40.0, 20.0);
+    tilted.rotate(FRAC_PI_4);
+
+    let mut circle = Circle::new(Point::new(50.0, 10.0), 12.0);
+    circle.translate(5.0, 0.0);
+
+    let mut triangle = Triangle::new(Point::new(0.0, 40.0), Point::new(30.0, 40.0), Point::new(15.0, 70.0));
+    triangle.scale(1.5);
+
+    let hexagon = Polygon::regular(Point::new(60.0, 35.0), 15.0, 6).expect("6 sides is enough");
+
+    let shapes: [(&str, &dyn Shape); 4] = [
+        ("Rectangle", &tilted),
+        ("Circle", &circle),
+        ("Triangle", &triangle),
+        ("Hexagon", &hexagon),
+    ];
+
+    for (name, shape) in shapes {
+        let bounds = shape.bounding_box();
+        println!(
+            "{name:<9} area: {:>7.2}  perimeter: {:>6.2}  box: {:.1} x {:.1}",
+            shape.area(), shape.perimeter(), bounds.width(), bounds.height()
+        );
     }
-}
 
+    println!("Does the circle contain (50, 10)? {}", circle.contains(Point::new(50.0, 10.0)));
+    println!("Do the rectangle and triangle touch? {}", tilted.intersects(&triangle));
+    println!("Do the circle and hexagon touch? {}", circle.intersects(&hexagon));
 
-fn print_user(user: &User) {
-    println!("User: {}", user.username);
-    println!("- Email: {}", user.email);
-    println!("- Active: {}", match user.active { true => "Yes", false => "No"});
-    println!("- Sign-in Count: {}", user.sign_in_count);
-    println!();
+    // * a quadtree finds which regions are under the cursor without checking every single one:
+    let mut index = QuadTree::new(Region::new(0.0, 0.0, 100.0, 100.0));
+    let button = index.insert(Region::new(10.0, 10.0, 30.0, 10.0));
+    let panel = index.insert(Region::new(0.0, 0.0, 50.0, 50.0));
+    index.insert(Region::new(60.0, 60.0, 20.0, 20.0));
+
+    let cursor = Point::new(15.0, 12.0);
+    let hits = index.query_point(cursor);
+    println!("Under the cursor: button? {} panel? {}", hits.contains(&button), hits.contains(&panel));
+    println!("Closest to (90, 10): {:?}", index.nearest(Point::new(90.0, 10.0)));
+
+    // open it in a browser to see the shapes:
+    match svg::save("shapes.svg", &shapes.map(|(_, shape)| shape)) {
+        Ok(()) => println!("Saved shapes.svg"),
+        Err(e) => println!("Couldn't save shapes.svg: {e}"),
+    }
 }
+
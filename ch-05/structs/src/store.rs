@@ -0,0 +1,368 @@
+// Persistent user store.
+// Keeps every User in memory and saves them to a plain text file, one user per line:
+// ? active<TAB>sign_in_count<TAB>username<TAB>email<TAB>password<TAB>last_sign_in<TAB>failed_attempts<TAB>locked_until
+// - times are seconds since the unix epoch, and "-" means there's no value.
+// - files from before sign-ins existed only have the first 4 fields, they still load fine.
+//
+// * Rules:
+// - usernames and emails are validated when they're parsed, see user::{Username, Email}.
+//   the store has UsernameRules of its own (the defaults, unless it's opened with open_with()),
+//   and checks new usernames and the ones in the file against them, so custom rules survive a reload.
+// - usernames are unique.
+// - emails are unique too, but compared case-insensitively ("A@x.com" == "a@X.COM").
+// - users are never deleted, only deactivated, so their usernames and emails stay taken.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::user::clock::{Clock, from_unix, to_unix};
+use crate::user::{Email, EmailError, Session, SignInError, SignInPolicy, SignInState, User, Username, UsernameError, UsernameRules, build_user};
+
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    Corrupt { line: usize, reason: String },
+    InvalidUsername(UsernameError),
+    InvalidEmail(EmailError),
+    UsernameTaken(String),
+    EmailTaken(String),
+    NotFound(String),
+    SignIn(SignInError),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::Io(e)                         => write!(f, "couldn't access the user file: {e}"),
+            StoreError::Corrupt { line, reason }      => write!(f, "user file is corrupt (line {line}): {reason}"),
+            StoreError::InvalidUsername(e)            => write!(f, "invalid username: {e}"),
+            StoreError::InvalidEmail(e)               => write!(f, "invalid email: {e}"),
+            StoreError::UsernameTaken(name)           => write!(f, "username {name:?} is already taken"),
+            StoreError::EmailTaken(email)             => write!(f, "email {email:?} is already in use"),
+            StoreError::NotFound(name)                => write!(f, "no user named {name:?}"),
+            StoreError::SignIn(e)                     => write!(f, "couldn't sign in: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+// lets the ? operator turn io::Errors into StoreErrors automatically:
+impl From<io::Error> for StoreError {
+    fn from(e: io::Error) -> Self {
+        StoreError::Io(e)
+    }
+}
+
+impl From<UsernameError> for StoreError {
+    fn from(e: UsernameError) -> Self {
+        StoreError::InvalidUsername(e)
+    }
+}
+
+impl From<EmailError> for StoreError {
+    fn from(e: EmailError) -> Self {
+        StoreError::InvalidEmail(e)
+    }
+}
+
+impl From<SignInError> for StoreError {
+    fn from(e: SignInError) -> Self {
+        StoreError::SignIn(e)
+    }
+}
+
+
+// the fields to change in update(), None means "leave it as is":
+#[derive(Debug, Clone, Default)]
+pub struct UserUpdate {
+    pub username: Option<Username>,
+    pub email:    Option<Email>,
+}
+
+
+#[derive(Debug)]
+pub struct UserStore {
+    path:  PathBuf,
+    users: Vec<User>,
+    rules: UsernameRules,
+}
+
+impl UserStore {
+    // loads the users saved in the file, or starts empty if it doesn't exist yet:
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        Self::open_with(path, UsernameRules::default())
+    }
+
+    // the same, with other rules for usernames. they aren't saved in the file,
+    // so it has to be opened with the same rules every time:
+    pub fn open_with(path: impl AsRef<Path>, rules: UsernameRules) -> Result<Self, StoreError> {
+        let path = path.as_ref().to_path_buf();
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut store = Self { path, users: Vec::new(), rules };
+
+        for (i, line) in contents.lines().enumerate().filter(|(_, l)| !l.is_empty()) {
+            let user = parse_line(line, &store.rules).map_err(|reason| StoreError::Corrupt { line: i + 1, reason })?;
+
+            // a hand-edited file could break the uniqueness rules:
+            store
+                .check_available(user.username.as_str(), user.email.as_str(), None)
+                .map_err(|e| StoreError::Corrupt { line: i + 1, reason: e.to_string() })?;
+
+            store.users.push(user);
+        }
+
+        Ok(store)
+    }
+
+    // writes to a temporary file first and then renames it over the real one,
+    // so a crash halfway through can't leave a half-written user file behind:
+    pub fn save(&self) -> Result<(), StoreError> {
+        let mut contents = String::new();
+
+        for user in &self.users {
+            let auth = &user.auth;
+            let time = |t: Option<SystemTime>| t.map_or(String::from("-"), |t| to_unix(t).to_string());
+
+            contents.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                user.active,
+                user.sign_in_count,
+                user.username,
+                user.email,
+                auth.password.as_ref().map_or(String::from("-"), |p| p.to_string()),
+                time(auth.last_sign_in),
+                auth.failed_attempts,
+                time(auth.locked_until),
+            ));
+        }
+
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, &self.path)?;
+
+        Ok(())
+    }
+
+    pub fn rules(&self) -> &UsernameRules {
+        &self.rules
+    }
+
+    // the username was parsed with some rules already, but they might not be this store's:
+    pub fn create(&mut self, username: Username, email: Email) -> Result<&User, StoreError> {
+        self.rules.check(username.as_str())?;
+        self.check_available(username.as_str(), email.as_str(), None)?;
+
+        self.users.push(build_user(username, email));
+        Ok(&self.users[self.users.len() - 1])
+    }
+
+    pub fn get(&self, username: &str) -> Option<&User> {
+        self.users.iter().find(|u| u.username.as_str() == username)
+    }
+
+    pub fn find_by_email(&self, email: &str) -> Option<&User> {
+        self.users.iter().find(|u| u.email.eq_ignore_case(email))
+    }
+
+    pub fn list(&self) -> &[User] {
+        &self.users
+    }
+
+    pub fn update(&mut self, username: &str, changes: UserUpdate) -> Result<&User, StoreError> {
+        let index = self.index_of(username)?;
+
+        if let Some(new) = &changes.username {
+            self.rules.check(new.as_str())?;
+        }
+
+        let new_username = changes.username.as_ref().map_or(username, Username::as_str);
+        let new_email = changes.email.as_ref().unwrap_or(&self.users[index].email).as_str();
+
+        // the user being updated can keep its own username and email:
+        self.check_available(new_username, new_email, Some(index))?;
+
+        let user = &mut self.users[index];
+
+        if let Some(username) = changes.username {
+            user.username = username;
+        }
+        if let Some(email) = changes.email {
+            user.email = email;
+        }
+
+        Ok(user)
+    }
+
+    pub fn set_password(&mut self, username: &str, password: &str) -> Result<&User, StoreError> {
+        let index = self.index_of(username)?;
+        let user = &mut self.users[index];
+
+        user.set_password(password);
+        Ok(user)
+    }
+
+    // failed attempts and lockouts change the user too, so save() whether this succeeds or not:
+    pub fn sign_in(
+        &mut self,
+        username: &str,
+        password: &str,
+        clock: &impl Clock,
+        policy: &SignInPolicy,
+    ) -> Result<Session, StoreError> {
+        let index = self.index_of(username)?;
+        Ok(self.users[index].sign_in_with(password, clock, policy)?)
+    }
+
+    // the "delete" of this store: the user stays, but can't be used anymore.
+    pub fn deactivate(&mut self, username: &str) -> Result<&User, StoreError> {
+        self.set_active(username, false)
+    }
+
+    pub fn reactivate(&mut self, username: &str) -> Result<&User, StoreError> {
+        self.set_active(username, true)
+    }
+
+    fn set_active(&mut self, username: &str, active: bool) -> Result<&User, StoreError> {
+        let index = self.index_of(username)?;
+        let user = &mut self.users[index];
+
+        user.active = active;
+        Ok(user)
+    }
+
+    fn index_of(&self, username: &str) -> Result<usize, StoreError> {
+        self.users
+            .iter()
+            .position(|u| u.username.as_str() == username)
+            .ok_or_else(|| StoreError::NotFound(username.to_string()))
+    }
+
+    // is this username and email free? `skip` ignores one user, for updates.
+    fn check_available(&self, username: &str, email: &str, skip: Option<usize>) -> Result<(), StoreError> {
+        let others = self.users.iter().enumerate().filter(|&(i, _)| Some(i) != skip);
+
+        for (_, user) in others {
+            if user.username.as_str() == username {
+                return Err(StoreError::UsernameTaken(username.to_string()));
+            }
+            if user.email.eq_ignore_case(email) {
+                return Err(StoreError::EmailTaken(email.to_string()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+fn parse_line(line: &str, rules: &UsernameRules) -> Result<User, String> {
+    let fields: Vec<&str> = line.split('\t').collect();
+
+    let (user, auth) = match fields[..] {
+        [active, count, username, email] => ([active, count, username, email], ["-", "-", "0", "-"]),
+        [active, count, username, email, password, last, failed, locked] => {
+            ([active, count, username, email], [password, last, failed, locked])
+        },
+        _ => return Err(format!("expected 4 or 8 fields, found {}", fields.len())),
+    };
+
+    let [active, sign_in_count, username, email] = user;
+    let [password, last_sign_in, failed_attempts, locked_until] = auth;
+
+    Ok(User {
+        active: active.parse().map_err(|_| format!("invalid active flag {active:?}"))?,
+        sign_in_count: sign_in_count.parse().map_err(|_| format!("invalid sign-in count {sign_in_count:?}"))?,
+        username: Username::parse_with(username, rules).map_err(|e| e.to_string())?,
+        email: Email::parse(email).map_err(|e| e.to_string())?,
+        auth: SignInState {
+            password: optional(password, |p| p.parse())?,
+            last_sign_in: optional(last_sign_in, parse_time)?,
+            failed_attempts: failed_attempts.parse().map_err(|_| format!("invalid failed attempts {failed_attempts:?}"))?,
+            locked_until: optional(locked_until, parse_time)?,
+        },
+    })
+}
+
+
+// "-" is None, anything else gets parsed:
+fn optional<T>(field: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Option<T>, String> {
+    match field {
+        "-" => Ok(None),
+        _   => parse(field).map(Some),
+    }
+}
+
+
+fn parse_time(field: &str) -> Result<SystemTime, String> {
+    field.parse().map(from_unix).map_err(|_| format!("invalid timestamp {field:?}"))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a file of its own in the temp dir for each test, so they can run in parallel:
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("structs-store-{}-{name}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn loose_rules() -> UsernameRules {
+        UsernameRules { min_len: 1, allow_unicode: true, must_start_with_letter: false, ..UsernameRules::default() }
+    }
+
+    #[test]
+    fn custom_rules_survive_a_reload() {
+        let path = temp_path("custom-rules");
+        let names = ["7up", "zoë", "x"];
+
+        let mut store = UserStore::open_with(&path, loose_rules()).unwrap();
+        for (i, name) in names.iter().enumerate() {
+            let username = Username::parse_with(name, store.rules()).unwrap();
+            store.create(username, Email::parse(&format!("user{i}@example.com")).unwrap()).unwrap();
+        }
+        store.save().unwrap();
+
+        let reopened = UserStore::open_with(&path, loose_rules()).unwrap();
+        let loaded: Vec<&str> = reopened.list().iter().map(|u| u.username.as_str()).collect();
+        assert_eq!(loaded, names);
+
+        // with the default rules, the same file doesn't pass:
+        assert!(matches!(UserStore::open(&path), Err(StoreError::Corrupt { line: 1, .. })));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn new_usernames_are_checked_against_the_stores_rules() {
+        let path = temp_path("strict-rules");
+        let strict = UsernameRules { max_len: 5, ..UsernameRules::default() };
+        let mut store = UserStore::open_with(&path, strict).unwrap();
+
+        // fine by the default rules, too long for this store's:
+        let long = Username::parse("ferris_the_crab").unwrap();
+        let email = Email::parse("ferris@example.com").unwrap();
+        assert!(matches!(
+            store.create(long.clone(), email.clone()),
+            Err(StoreError::InvalidUsername(UsernameError::TooLong { max: 5, .. })),
+        ));
+
+        store.create(Username::parse("crab").unwrap(), email).unwrap();
+        let rename = UserUpdate { username: Some(long), email: None };
+        assert!(matches!(store.update("crab", rename), Err(StoreError::InvalidUsername(_))));
+        assert!(store.get("crab").is_some());
+    }
+}
@@ -0,0 +1,30 @@
+// Helpers shared by the tests (and the benchmarks in src/bin, which is why it isn't only built for tests).
+
+// * xorshift: not good randomness, but plenty for test data, and the same every run.
+// the seed can be anything but 0 (which only ever gives 0s).
+pub struct Random(pub u64);
+
+impl Random {
+    pub fn bits(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // 0 to max - 1:
+    pub fn below(&mut self, max: usize) -> usize {
+        (self.bits() % max as u64) as usize
+    }
+
+    // from 0 up to (not including) max:
+    pub fn float(&mut self, max: f64) -> f64 {
+        (self.bits() >> 11) as f64 / (1u64 << 53) as f64 * max
+    }
+
+    // from -100 to 100: small enough that sums and products of a few of them don't overflow,
+    // and are exact even as f32s.
+    pub fn small(&mut self) -> i64 {
+        (self.bits() % 201) as i64 - 100
+    }
+}
@@ -0,0 +1,67 @@
+// Clocks.
+// Anything time-based (lockouts, session expiry) asks a Clock for the time
+// instead of calling SystemTime::now() directly, so the time can be faked:
+// - SystemClock: the real time.
+// - ManualClock: stays put until it's told to move, for deterministic tests and examples.
+
+use std::cell::Cell;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+
+pub trait Clock {
+    fn now(&self) -> SystemTime;
+}
+
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+
+// Cell lets advance() change the time through a shared reference,
+// so the same clock can be handed to everything that needs it:
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Cell<SystemTime>,
+}
+
+impl ManualClock {
+    pub fn new(now: SystemTime) -> Self {
+        Self { now: Cell::new(now) }
+    }
+
+    // a clock stopped at this many seconds after 1970-01-01:
+    pub fn at_unix(secs: u64) -> Self {
+        Self::new(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+
+    pub fn set(&self, now: SystemTime) {
+        self.now.set(now);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        self.now.get()
+    }
+}
+
+
+// the store saves times as whole seconds since the unix epoch:
+pub fn to_unix(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+
+pub fn from_unix(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
@@ -0,0 +1,257 @@
+// Email newtype.
+// An Email can only be made by parsing, so holding one means the address is valid.
+//
+// * Accepted grammar (the common "dot-atom" subset of RFC 5322):
+// ? local-part@domain
+// - local part: 1 to 64 chars out of  A-Z a-z 0-9 ! # $ % & ' * + / = ? ^ _ ` { | } ~ -
+//   plus dots, but never at the start, the end, or two in a row.
+// - domain: at least two labels separated by dots ("example.com"), each one 1 to 63
+//   letters, digits or hyphens that don't start or end with a hyphen.
+//   the last label (the TLD) can't be all digits.
+// - the whole address is at most 254 chars.
+// * not supported: quoted local parts ("john doe"@x.com), comments, and IP literals (a@[1.2.3.4]).
+
+use std::fmt;
+use std::str::FromStr;
+
+
+const MAX_LEN: usize = 254;
+const MAX_LOCAL_LEN: usize = 64;
+const MAX_DOMAIN_LEN: usize = 253;
+const MAX_LABEL_LEN: usize = 63;
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmailError {
+    Empty,
+    TooLong(usize),
+    MissingAt,
+    MultipleAt,
+    EmptyLocalPart,
+    LocalPartTooLong(usize),
+    InvalidLocalChar(char),
+    MisplacedDot,
+    EmptyDomain,
+    DomainTooLong(usize),
+    InvalidDomainChar(char),
+    EmptyLabel,
+    LabelTooLong(String),
+    LabelHyphen(String),
+    SingleLabelDomain,
+    NumericTld(String),
+}
+
+impl fmt::Display for EmailError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EmailError::Empty                => write!(f, "email is empty"),
+            EmailError::TooLong(len)         => write!(f, "email is {len} chars long, the limit is {MAX_LEN}"),
+            EmailError::MissingAt            => write!(f, "email is missing an '@'"),
+            EmailError::MultipleAt           => write!(f, "email has more than one '@'"),
+            EmailError::EmptyLocalPart       => write!(f, "nothing before the '@'"),
+            EmailError::LocalPartTooLong(n)  => write!(f, "part before the '@' is {n} chars long, the limit is {MAX_LOCAL_LEN}"),
+            EmailError::InvalidLocalChar(c)  => write!(f, "{c:?} isn't allowed before the '@'"),
+            EmailError::MisplacedDot         => write!(f, "dots can't start or end the part before the '@', or be next to each other"),
+            EmailError::EmptyDomain          => write!(f, "nothing after the '@'"),
+            EmailError::DomainTooLong(n)     => write!(f, "domain is {n} chars long, the limit is {MAX_DOMAIN_LEN}"),
+            EmailError::InvalidDomainChar(c) => write!(f, "{c:?} isn't allowed in the domain"),
+            EmailError::EmptyLabel           => write!(f, "domain has an empty part (two dots in a row, or a dot at the start or end)"),
+            EmailError::LabelTooLong(label)  => write!(f, "domain part {label:?} is longer than {MAX_LABEL_LEN} chars"),
+            EmailError::LabelHyphen(label)   => write!(f, "domain part {label:?} starts or ends with a hyphen"),
+            EmailError::SingleLabelDomain    => write!(f, "domain needs at least one dot (like \"example.com\")"),
+            EmailError::NumericTld(tld)      => write!(f, "top-level domain {tld:?} can't be all digits"),
+        }
+    }
+}
+
+impl std::error::Error for EmailError {}
+
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Email(String);
+
+impl Email {
+    pub fn parse(s: &str) -> Result<Self, EmailError> {
+        if s.is_empty() {
+            return Err(EmailError::Empty);
+        }
+
+        let len = s.chars().count();
+        if len > MAX_LEN {
+            return Err(EmailError::TooLong(len));
+        }
+
+        let (local, domain) = match s.split_once('@') {
+            Some((_, domain)) if domain.contains('@') => return Err(EmailError::MultipleAt),
+            Some(parts) => parts,
+            None        => return Err(EmailError::MissingAt),
+        };
+
+        check_local_part(local)?;
+        check_domain(domain)?;
+
+        Ok(Self(s.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // emails are compared case-insensitively, this is the form to compare:
+    pub fn normalized(&self) -> String {
+        self.0.to_ascii_lowercase()
+    }
+
+    pub fn eq_ignore_case(&self, other: &str) -> bool {
+        self.0.eq_ignore_ascii_case(other)
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// lets emails be parsed with "a@b.com".parse::<Email>():
+impl FromStr for Email {
+    type Err = EmailError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+
+fn check_local_part(local: &str) -> Result<(), EmailError> {
+    if local.is_empty() {
+        return Err(EmailError::EmptyLocalPart);
+    }
+    if local.len() > MAX_LOCAL_LEN {
+        return Err(EmailError::LocalPartTooLong(local.len()));
+    }
+    if local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+        return Err(EmailError::MisplacedDot);
+    }
+
+    match local.chars().find(|&c| !is_atext(c) && c != '.') {
+        Some(c) => Err(EmailError::InvalidLocalChar(c)),
+        None    => Ok(()),
+    }
+}
+
+
+fn check_domain(domain: &str) -> Result<(), EmailError> {
+    if domain.is_empty() {
+        return Err(EmailError::EmptyDomain);
+    }
+    if domain.len() > MAX_DOMAIN_LEN {
+        return Err(EmailError::DomainTooLong(domain.len()));
+    }
+    if let Some(c) = domain.chars().find(|&c| !c.is_ascii_alphanumeric() && c != '-' && c != '.') {
+        return Err(EmailError::InvalidDomainChar(c));
+    }
+
+    for label in domain.split('.') {
+        if label.is_empty() {
+            return Err(EmailError::EmptyLabel);
+        }
+        if label.len() > MAX_LABEL_LEN {
+            return Err(EmailError::LabelTooLong(label.to_string()));
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(EmailError::LabelHyphen(label.to_string()));
+        }
+    }
+
+    // rsplit_once only fails if there's no dot at all:
+    let Some((_, tld)) = domain.rsplit_once('.') else {
+        return Err(EmailError::SingleLabelDomain);
+    };
+
+    if tld.chars().all(|c| c.is_ascii_digit()) {
+        return Err(EmailError::NumericTld(tld.to_string()));
+    }
+
+    Ok(())
+}
+
+
+// the chars RFC 5322 calls "atext":
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+/=?^_`{|}~-".contains(c)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn good_addresses_parse() {
+        let local_64 = format!("{}@example.com", "a".repeat(64));
+        let label_63 = format!("a@{}.com", "b".repeat(63));
+
+        for good in [
+            "email_two@example.com",
+            "a@b.co",
+            "first.last+tag@mail.example.org",
+            "!#$%&'*+/=?^_`{|}~-@example.com",  // every atext char
+            "a@123.example.com",                // digits are fine, just not in the TLD
+            "a@x-y.example",
+            &local_64,
+            &label_63,
+        ] {
+            assert_eq!(Email::parse(good).map(|e| e.to_string()).as_deref(), Ok(good));
+        }
+    }
+
+    #[test]
+    fn every_way_an_address_can_be_wrong() {
+        let too_long = "a".repeat(255);
+        let long_local = format!("{}@example.com", "a".repeat(65));
+        let long_label = format!("a@{}.com", "b".repeat(64));
+        // 254 chars is the limit for the whole address, so the domain can only be too long in bytes:
+        let long_domain = format!("a@{}", "é".repeat(127));
+
+        let cases = [
+            ("",                 EmailError::Empty),
+            (&too_long,          EmailError::TooLong(255)),
+            ("example.com",      EmailError::MissingAt),
+            ("two@@example.com", EmailError::MultipleAt),
+            ("a@b@example.com",  EmailError::MultipleAt),
+            ("@example.com",     EmailError::EmptyLocalPart),
+            (&long_local,        EmailError::LocalPartTooLong(65)),
+            ("no spaces@x.com",  EmailError::InvalidLocalChar(' ')),
+            ("a(b)@x.com",       EmailError::InvalidLocalChar('(')),
+            (".dot@example.com", EmailError::MisplacedDot),
+            ("dot.@example.com", EmailError::MisplacedDot),
+            ("a..b@example.com", EmailError::MisplacedDot),
+            ("a@",               EmailError::EmptyDomain),
+            (&long_domain,       EmailError::DomainTooLong(254)),
+            ("a@exa_mple.com",   EmailError::InvalidDomainChar('_')),
+            ("a@[1.2.3.4]",      EmailError::InvalidDomainChar('[')),
+            ("a@example..com",   EmailError::EmptyLabel),
+            ("a@.example.com",   EmailError::EmptyLabel),
+            ("a@example.com.",   EmailError::EmptyLabel),
+            (&long_label,        EmailError::LabelTooLong("b".repeat(64))),
+            ("a@-example.com",   EmailError::LabelHyphen(String::from("-example"))),
+            ("a@example-.com",   EmailError::LabelHyphen(String::from("example-"))),
+            ("my_email@example", EmailError::SingleLabelDomain),
+            ("a@example.123",    EmailError::NumericTld(String::from("123"))),
+        ];
+
+        for (bad, error) in cases {
+            assert_eq!(Email::parse(bad), Err(error), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn case_doesnt_matter_when_comparing() {
+        let email: Email = "Someone@Example.COM".parse().unwrap();
+
+        assert_eq!(email.as_str(), "Someone@Example.COM");
+        assert_eq!(email.normalized(), "someone@example.com");
+        assert!(email.eq_ignore_case("someone@example.com"));
+    }
+}
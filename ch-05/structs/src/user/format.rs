@@ -0,0 +1,513 @@
+// Output formats for lists of users.
+// people want an aligned table, scripts want something they can parse:
+//
+// * Formats:
+// - table:  aligned columns for the terminal.
+// - json:   an array of objects.
+// - csv:    a header row, then one row per user (RFC 4180 quoting).
+// - yaml:   a sequence of mappings.
+//
+// * a View picks which columns to show (in order) and how to sort the rows:
+// ? View::parse("username,active", "-sign_in_count,username")?
+// sorts by sign-in count, highest first ("-"), then by username.
+//
+// every format renders a value the same way: `active` is always true/false,
+// times are seconds since the unix epoch, and a missing value is empty (null in JSON and YAML).
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use super::User;
+use super::clock::to_unix;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+    Yaml,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Username,
+    Email,
+    Active,
+    SignInCount,
+    LastSignIn,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct View {
+    pub columns: Vec<Column>,
+    pub sort:    Vec<(Column, Order)>,  // first key first, ties go to the next one
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    UnknownFormat(String),
+    UnknownColumn(String),
+    NoColumns,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::UnknownFormat(s) => write!(f, "unknown format {s:?} (expected table, json, csv or yaml)"),
+            FormatError::UnknownColumn(s) => write!(f, "unknown column {s:?} (expected one of: {})", Column::names()),
+            FormatError::NoColumns        => write!(f, "at least one column has to be shown"),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+
+impl Format {
+    pub fn render<'a>(self, users: impl IntoIterator<Item = &'a User>, view: &View) -> String {
+        let rows = view.rows(users);
+
+        match self {
+            Format::Table => table(&view.columns, &rows),
+            Format::Json  => json(&view.columns, &rows),
+            Format::Csv   => csv(&view.columns, &rows),
+            Format::Yaml  => yaml(&view.columns, &rows),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "table"        => Ok(Format::Table),
+            "json"         => Ok(Format::Json),
+            "csv"          => Ok(Format::Csv),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            _              => Err(FormatError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+
+impl Column {
+    pub const ALL: [Column; 5] = [
+        Column::Username,
+        Column::Email,
+        Column::Active,
+        Column::SignInCount,
+        Column::LastSignIn,
+    ];
+
+    // the key in JSON, CSV and YAML, and what --columns and --sort take:
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Username    => "username",
+            Column::Email       => "email",
+            Column::Active      => "active",
+            Column::SignInCount => "sign_in_count",
+            Column::LastSignIn  => "last_sign_in",
+        }
+    }
+
+    // the header in a table:
+    pub fn label(self) -> &'static str {
+        match self {
+            Column::Username    => "Username",
+            Column::Email       => "Email",
+            Column::Active      => "Active",
+            Column::SignInCount => "Sign-in Count",
+            Column::LastSignIn  => "Last Sign-in",
+        }
+    }
+
+    fn names() -> String {
+        Column::ALL.map(Column::name).join(", ")
+    }
+
+    fn value(self, user: &User) -> Value {
+        match self {
+            Column::Username    => Value::Text(user.username.to_string()),
+            Column::Email       => Value::Text(user.email.to_string()),
+            Column::Active      => Value::Bool(user.active),
+            Column::SignInCount => Value::Number(user.sign_in_count),
+            Column::LastSignIn  => user.auth.last_sign_in.map_or(Value::Missing, |t| Value::Number(to_unix(t))),
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = FormatError;
+
+    // "sign_in_count", "sign-in-count" and "SignInCount" all work:
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wanted: String = s.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+
+        Column::ALL
+            .into_iter()
+            .find(|c| c.name().replace('_', "") == wanted)
+            .ok_or_else(|| FormatError::UnknownColumn(s.to_string()))
+    }
+}
+
+
+impl View {
+    // both comma separated. an empty `columns` means all of them, an empty `sort` keeps the original order:
+    pub fn parse(columns: &str, sort: &str) -> Result<Self, FormatError> {
+        let columns = match columns.trim() {
+            "" => Column::ALL.to_vec(),
+            s  => split(s).map(str::parse).collect::<Result<Vec<_>, _>>()?,
+        };
+        if columns.is_empty() {
+            return Err(FormatError::NoColumns);
+        }
+
+        let sort = split(sort)
+            .map(|key| match key.strip_prefix('-') {
+                Some(key) => Ok((key.parse()?, Order::Descending)),
+                None      => Ok((key.strip_prefix('+').unwrap_or(key).parse()?, Order::Ascending)),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { columns, sort })
+    }
+
+    fn rows<'a>(&self, users: impl IntoIterator<Item = &'a User>) -> Vec<Vec<Value>> {
+        let mut users: Vec<&User> = users.into_iter().collect();
+
+        // stable, so users that tie on every key stay in their original order:
+        users.sort_by(|a, b| {
+            self.sort.iter().fold(Ordering::Equal, |ordering, &(column, order)| {
+                ordering.then_with(|| {
+                    let ordering = column.value(a).cmp(&column.value(b));
+                    match order {
+                        Order::Ascending  => ordering,
+                        Order::Descending => ordering.reverse(),
+                    }
+                })
+            })
+        });
+
+        users
+            .into_iter()
+            .map(|user| self.columns.iter().map(|c| c.value(user)).collect())
+            .collect()
+    }
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self { columns: Column::ALL.to_vec(), sort: Vec::new() }
+    }
+}
+
+
+fn split(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',').map(str::trim).filter(|s| !s.is_empty())
+}
+
+
+// one cell. the variant order matters: it's how mixed values sort (missing ones first).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Value {
+    Missing,
+    Bool(bool),
+    Number(u64),
+    Text(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Missing   => Ok(()),
+            Value::Bool(b)   => write!(f, "{b}"),
+            Value::Number(n) => write!(f, "{n}"),
+            Value::Text(s)   => f.write_str(s),
+        }
+    }
+}
+
+
+// * Username     Email                 Active  Sign-in Count
+// * -----------  --------------------  ------  -------------
+// * my_user123   my_email@example.com  true               11
+fn table(columns: &[Column], rows: &[Vec<Value>]) -> String {
+    let cells: Vec<Vec<String>> = rows.iter().map(|row| row.iter().map(Value::to_string).collect()).collect();
+
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            cells.iter().map(|row| row[i].chars().count()).chain([column.label().len()]).max().unwrap_or(0)
+        })
+        .collect();
+
+    // numbers line up on the right, like they do in a spreadsheet:
+    let right = |i: usize| matches!(columns[i], Column::SignInCount | Column::LastSignIn);
+
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .into_iter()
+            .enumerate()
+            .map(|(i, cell)| match right(i) {
+                true  => format!("{cell:>width$}", width = widths[i]),
+                false => format!("{cell:<width$}", width = widths[i]),
+            })
+            .collect();
+
+        padded.join("  ").trim_end().to_string() + "\n"
+    };
+
+    let mut out = line(columns.iter().map(|c| c.label()).collect());
+    let dashes: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+    out += &line(dashes.iter().map(String::as_str).collect());
+
+    for row in &cells {
+        out += &line(row.iter().map(String::as_str).collect());
+    }
+
+    out
+}
+
+
+// * [
+// *   {
+// *     "username": "my_user123",
+// *     "active": true
+// *   }
+// * ]
+fn json(columns: &[Column], rows: &[Vec<Value>]) -> String {
+    if rows.is_empty() {
+        return "[]\n".to_string();
+    }
+
+    let objects: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let fields: Vec<String> = columns
+                .iter()
+                .zip(row)
+                .map(|(column, value)| format!("    {}: {}", json_string(column.name()), json_value(value)))
+                .collect();
+
+            format!("  {{\n{}\n  }}", fields.join(",\n"))
+        })
+        .collect();
+
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
+
+fn json_value(value: &Value) -> String {
+    match value {
+        Value::Missing => "null".to_string(),
+        Value::Text(s) => json_string(s),
+        other          => other.to_string(),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from('"');
+
+    for c in s.chars() {
+        match c {
+            '"'  => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c    => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+
+// * username,email,active
+// * my_user123,my_email@example.com,true
+fn csv(columns: &[Column], rows: &[Vec<Value>]) -> String {
+    let line = |cells: Vec<String>| cells.iter().map(|c| csv_field(c)).collect::<Vec<_>>().join(",") + "\n";
+
+    let mut out = line(columns.iter().map(|c| c.name().to_string()).collect());
+    for row in rows {
+        out += &line(row.iter().map(Value::to_string).collect());
+    }
+
+    out
+}
+
+// quoted only when it has to be, with quotes inside doubled:
+fn csv_field(s: &str) -> String {
+    match s.contains([',', '"', '\n', '\r']) {
+        true  => format!("\"{}\"", s.replace('"', "\"\"")),
+        false => s.to_string(),
+    }
+}
+
+
+// * - username: my_user123
+// *   active: true
+// * - username: user-2
+// *   active: false
+fn yaml(columns: &[Column], rows: &[Vec<Value>]) -> String {
+    if rows.is_empty() {
+        return "[]\n".to_string();
+    }
+
+    let mut out = String::new();
+
+    for row in rows {
+        for (i, (column, value)) in columns.iter().zip(row).enumerate() {
+            let indent = match i {
+                0 => "- ",
+                _ => "  ",
+            };
+
+            let value = match value {
+                Value::Missing => "null".to_string(),
+                Value::Text(s) => yaml_string(s),
+                other          => other.to_string(),
+            };
+
+            out += &format!("{indent}{}: {value}\n", column.name());
+        }
+    }
+
+    out
+}
+
+// plain if it's unambiguous, otherwise double quoted (which uses the same escapes as JSON).
+// "true", "null", "123" or "no" would be read back as something other than a string.
+fn yaml_string(s: &str) -> String {
+    const RESERVED: [&str; 11] = ["true", "false", "null", "yes", "no", "on", "off", "y", "n", "~", ""];
+
+    let plain = s.starts_with(|c: char| c.is_alphabetic())
+        && s.chars().all(|c| c.is_alphanumeric() || "._-@+".contains(c))
+        && !RESERVED.contains(&s.to_lowercase().as_str());
+
+    match plain {
+        true  => s.to_string(),
+        false => json_string(s),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::{Email, Username, build_user};
+
+    #[test]
+    fn csv_quotes_only_when_it_has_to() {
+        let cases = [
+            ("plain",          "plain"),
+            ("",               ""),
+            ("a,b",            "\"a,b\""),
+            ("say \"hi\"",     "\"say \"\"hi\"\"\""),
+            ("\"",             "\"\"\"\""),
+            ("two\nlines",     "\"two\nlines\""),
+            ("cr\r",           "\"cr\r\""),
+            (" spaces kept ",  " spaces kept "),
+            ("it's; fine",     "it's; fine"),
+        ];
+
+        for (s, field) in cases {
+            assert_eq!(csv_field(s), field, "{s:?}");
+        }
+    }
+
+    #[test]
+    fn json_escapes() {
+        let cases = [
+            ("plain",        r#""plain""#),
+            ("say \"hi\"",   r#""say \"hi\"""#),
+            ("back\\slash",  r#""back\\slash""#),
+            ("a\nb\r\tc",    r#""a\nb\r\tc""#),
+            ("bell\u{7}",    r#""bell\u0007""#),
+            ("é, ü → ✓",     r#""é, ü → ✓""#),  // non-ASCII goes through as it is
+        ];
+
+        for (s, string) in cases {
+            assert_eq!(json_string(s), string, "{s:?}");
+        }
+    }
+
+    #[test]
+    fn yaml_quotes_anything_ambiguous() {
+        let cases = [
+            ("my_user123",        "my_user123"),
+            ("user-2",            "user-2"),
+            ("a@example.com",     "a@example.com"),
+            ("-dash",             r#""-dash""#),       // would start a list item
+            (":colon",            r#"":colon""#),
+            ("key: value",        r#""key: value""#),  // would be a mapping
+            ("a,b",               r#""a,b""#),
+            ("# comment",         r##""# comment""##),
+            ("123",               r#""123""#),         // would be a number
+            ("true",              r#""true""#),        // ...or a bool, or null
+            ("No",                r#""No""#),
+            ("NULL",              r#""NULL""#),
+            ("~",                 r#""~""#),
+            ("",                  r#""""#),
+            ("say \"hi\"\n",      r#""say \"hi\"\n""#),
+        ];
+
+        for (s, string) in cases {
+            assert_eq!(yaml_string(s), string, "{s:?}");
+        }
+    }
+
+    #[test]
+    fn every_format_renders_the_same_values() {
+        let mut ferris = build_user(Username::parse("ferris").unwrap(), Email::parse("ferris@example.com").unwrap());
+        ferris.sign_in_count = 11;
+        let mut corro = build_user(Username::parse("corro").unwrap(), Email::parse("o'corro+x@example.com").unwrap());
+        corro.active = false;
+
+        let users = [ferris, corro];
+        let view = View::parse("username,email,active,last_sign_in", "-sign_in_count").unwrap();
+
+        assert_eq!(Format::Csv.render(&users, &view), concat!(
+            "username,email,active,last_sign_in\n",
+            "ferris,ferris@example.com,true,\n",
+            "corro,o'corro+x@example.com,false,\n",
+        ));
+
+        assert_eq!(Format::Yaml.render(&users, &view), concat!(
+            "- username: ferris\n",
+            "  email: ferris@example.com\n",
+            "  active: true\n",
+            "  last_sign_in: null\n",
+            "- username: corro\n",
+            "  email: \"o'corro+x@example.com\"\n",
+            "  active: false\n",
+            "  last_sign_in: null\n",
+        ));
+
+        assert_eq!(Format::Json.render(&users[1..], &view), concat!(
+            "[\n",
+            "  {\n",
+            "    \"username\": \"corro\",\n",
+            "    \"email\": \"o'corro+x@example.com\",\n",
+            "    \"active\": false,\n",
+            "    \"last_sign_in\": null\n",
+            "  }\n",
+            "]\n",
+        ));
+
+        assert_eq!(Format::Json.render(&[], &view), "[]\n");
+        assert_eq!(Format::Yaml.render(&[], &view), "[]\n");
+    }
+}
@@ -0,0 +1,56 @@
+// User accounts.
+// the username and email are validated newtypes, so an invalid User can't even be built:
+// ? build_user(Username::parse("my_user123")?, Email::parse("my_email@example.com")?)
+//
+// * or with the builder, which can set the optional fields too:
+// ? User::builder().username(username).email(email).sign_in_count(11).build()
+// leaving out the username or email doesn't compile. a user can also be copied with one
+// field changed, without moving anything out of the original: user1.with_email(email)
+
+pub mod clock;
+pub mod email;
+pub mod format;
+pub mod password;
+pub mod session;
+pub mod username;
+
+use builder_derive::Builder;
+
+pub use email::{Email, EmailError};
+pub use format::{Format, View};
+pub use session::{Session, SignInError, SignInPolicy, SignInState};
+pub use username::{Username, UsernameError, UsernameRules};
+
+
+#[derive(Debug, Clone, Builder)]
+#[builder(with)]
+pub struct User {
+    #[builder(default = true)]
+    pub active: bool,
+    #[builder(default = 1)]
+    pub sign_in_count: u64,
+
+    // ? why do these have to be of type String and not &str?
+    // - to deliberately make instances of this own all of their data.
+    // - structs can store references to values they don't own, but it gets complicated.
+    // * they're not plain Strings anymore, but Username and Email still own their data.
+    pub username: Username,
+    pub email: Email,
+
+    // password hash, last sign-in, failed attempts and lockout, see session.rs:
+    #[builder(default)]
+    pub auth: SignInState,
+}
+
+
+pub fn build_user(username: Username, email: Email) -> User {
+    // because the username and email parameters have the same
+    // name as the struct attributes, they don't have to be repeated
+    User {
+        active: true,
+        username,
+        email,
+        sign_in_count: 1,
+        auth: SignInState::default(),
+    }
+}
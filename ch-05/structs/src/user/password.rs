@@ -0,0 +1,307 @@
+// Salted password hashing.
+// Passwords are never stored, only a PBKDF2-HMAC-SHA256 hash of them:
+// - the salt is random per password, so two users with the same password get different hashes,
+//   and precomputed tables of common passwords are useless.
+// - PBKDF2 runs the hash thousands of times, which makes guessing passwords slow.
+//
+// * stored as a single string:
+// ? pbkdf2-sha256$<iterations>$<salt in hex>$<hash in hex>
+//
+// SHA-256 is implemented here from its spec (FIPS 180-4) since this crate has no dependencies.
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::fs::File;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Read;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+
+pub const DEFAULT_ITERATIONS: u32 = 10_000;
+const SALT_LEN: usize = 16;
+const PREFIX: &str = "pbkdf2-sha256";
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordHash {
+    iterations: u32,
+    salt:       [u8; SALT_LEN],
+    hash:       [u8; 32],
+}
+
+impl PasswordHash {
+    // hashes a password with a fresh random salt:
+    pub fn new(password: &str) -> Self {
+        Self::with_salt(password, random_bytes(), DEFAULT_ITERATIONS)
+    }
+
+    pub fn with_salt(password: &str, salt: [u8; SALT_LEN], iterations: u32) -> Self {
+        let hash = pbkdf2(password.as_bytes(), &salt, iterations);
+        Self { iterations, salt, hash }
+    }
+
+    pub fn verify(&self, password: &str) -> bool {
+        let hash = pbkdf2(password.as_bytes(), &self.salt, self.iterations);
+
+        // compare every byte, even after a mismatch, so the time it takes
+        // doesn't leak how much of the hash was right:
+        hash.iter().zip(self.hash).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+}
+
+impl fmt::Display for PasswordHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{PREFIX}${}${}${}", self.iterations, to_hex(&self.salt), to_hex(&self.hash))
+    }
+}
+
+impl FromStr for PasswordHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('$').collect();
+
+        let [PREFIX, iterations, salt, hash] = parts[..] else {
+            return Err(format!("expected \"{PREFIX}$<iterations>$<salt>$<hash>\""));
+        };
+
+        Ok(Self {
+            // 0 isn't a count PBKDF2 can run with (and would mean almost no work for someone guessing):
+            iterations: iterations.parse().ok().filter(|&n| n > 0)
+                .ok_or_else(|| format!("invalid iteration count {iterations:?}"))?,
+            salt: from_hex(salt).ok_or_else(|| format!("invalid salt {salt:?}"))?,
+            hash: from_hex(hash).ok_or_else(|| format!("invalid hash {hash:?}"))?,
+        })
+    }
+}
+
+
+// random bytes from the operating system, for salts and session tokens.
+// if /dev/urandom isn't available, std's randomly seeded hasher is mixed with the time instead:
+pub fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0; N];
+
+    if File::open("/dev/urandom").and_then(|mut f| f.read_exact(&mut bytes)).is_ok() {
+        return bytes;
+    }
+
+    for (i, chunk) in bytes.chunks_mut(8).enumerate() {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_usize(i);
+        hasher.write_u128(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_nanos());
+
+        let random = hasher.finish().to_le_bytes();
+        chunk.copy_from_slice(&random[..chunk.len()]);
+    }
+
+    bytes
+}
+
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+
+fn from_hex<const N: usize>(s: &str) -> Option<[u8; N]> {
+    if s.len() != N * 2 || !s.is_ascii() {
+        return None;
+    }
+
+    let mut bytes = [0; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+    }
+
+    Some(bytes)
+}
+
+
+// * PBKDF2 (RFC 8018), with HMAC-SHA256 and a single 32 byte block of output:
+// ? U1 = HMAC(password, salt || 1)
+// ? Un = HMAC(password, Un-1)
+// ? hash = U1 ^ U2 ^ ... ^ Un
+fn pbkdf2(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut block = salt.to_vec();
+    block.extend_from_slice(&1u32.to_be_bytes());
+
+    let mut u = hmac_sha256(password, &block);
+    let mut result = u;
+
+    for _ in 1..iterations {
+        u = hmac_sha256(password, &u);
+        result.iter_mut().zip(u).for_each(|(r, b)| *r ^= b);
+    }
+
+    result
+}
+
+
+// * HMAC (RFC 2104):
+// ? HMAC(key, message) = H((key ^ opad) || H((key ^ ipad) || message))
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    // keys longer than a block get hashed first, shorter ones are padded with zeroes:
+    let mut block = [0u8; 64];
+    if key.len() > 64 {
+        block[..32].copy_from_slice(&sha256(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = block.map(|b| b ^ 0x36).to_vec();
+    inner.extend_from_slice(message);
+
+    let mut outer = block.map(|b| b ^ 0x5c).to_vec();
+    outer.extend_from_slice(&sha256(&inner));
+
+    sha256(&outer)
+}
+
+
+// first 32 bits of the fractional parts of the cube roots of the first 64 primes:
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// first 32 bits of the fractional parts of the square roots of the first 8 primes:
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+
+pub fn sha256(message: &[u8]) -> [u8; 32] {
+    // pad the message: a 1 bit, zeroes, and the length in bits, up to a multiple of 64 bytes.
+    let mut data = message.to_vec();
+    data.push(0x80);
+    while data.len() % 64 != 56 {
+        data.push(0);
+    }
+    data.extend_from_slice(&((message.len() as u64) * 8).to_be_bytes());
+
+    let mut h = H0;
+
+    for chunk in data.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in chunk.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(h) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+
+    digest
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // * known answers, from the specs:
+    #[test]
+    fn sha256_matches_fips_180_4() {
+        let cases = [
+            ("",    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+            ("abc", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+            (
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+        ];
+
+        for (message, digest) in cases {
+            assert_eq!(to_hex(&sha256(message.as_bytes())), digest, "sha256({message:?})");
+        }
+    }
+
+    #[test]
+    fn hmac_matches_rfc_4231() {
+        assert_eq!(
+            to_hex(&hmac_sha256(&[0x0b; 20], b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+        );
+        assert_eq!(
+            to_hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        );
+    }
+
+    #[test]
+    fn pbkdf2_matches_the_known_vectors() {
+        let cases = [
+            (1,    "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b"),
+            (2,    "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43"),
+            (4096, "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"),
+        ];
+
+        for (iterations, hash) in cases {
+            assert_eq!(to_hex(&pbkdf2(b"password", b"salt", iterations)), hash, "{iterations} iterations");
+        }
+    }
+
+    #[test]
+    fn hashes_round_trip_and_verify() {
+        let hash = PasswordHash::with_salt("hunter2", [7; SALT_LEN], 3);
+        let text = hash.to_string();
+
+        assert!(text.starts_with("pbkdf2-sha256$3$0707"));
+        assert_eq!(text.parse(), Ok(hash.clone()));
+        assert!(hash.verify("hunter2"));
+        assert!(!hash.verify("hunter3"));
+    }
+
+    #[test]
+    fn bad_hashes_are_rejected() {
+        let salt = "07".repeat(SALT_LEN);
+        let digest = "00".repeat(32);
+        let parse = |s: String| s.parse::<PasswordHash>();
+
+        assert!(parse(format!("{PREFIX}$1${salt}${digest}")).is_ok());
+        assert_eq!(parse(format!("{PREFIX}$0${salt}${digest}")), Err("invalid iteration count \"0\"".to_string()));
+        assert!(parse(format!("{PREFIX}$-1${salt}${digest}")).is_err());
+        assert!(parse(format!("md5$1${salt}${digest}")).is_err());
+        assert!(parse(format!("{PREFIX}$1$07${digest}")).is_err());
+        assert!(parse(format!("{PREFIX}$1${salt}$zz{}", &digest[2..])).is_err());
+        assert!(parse(format!("{PREFIX}$1${salt}")).is_err());
+    }
+}
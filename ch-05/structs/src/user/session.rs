@@ -0,0 +1,233 @@
+// Signing in.
+// sign_in_count used to be a number nothing ever touched, now signing in actually does something:
+// 1) inactive users and locked accounts are turned away before the password is even checked.
+// 2) the password is checked against the user's salted hash.
+// 3) a wrong password counts as a failed attempt; too many in a row lock the account for a while.
+// 4) a right password resets the failed attempts, bumps sign_in_count,
+//    records when it happened, and hands out a Session that expires after a while.
+
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use super::clock::{Clock, SystemClock};
+use super::password::{PasswordHash, random_bytes, to_hex};
+use super::{User, Username};
+
+
+#[derive(Debug, Clone)]
+pub struct SignInPolicy {
+    pub max_attempts: u32,       // failed attempts in a row before the account gets locked
+    pub cooldown:     Duration,  // how long it stays locked
+    pub session_ttl:  Duration,  // how long a session lasts
+}
+
+impl Default for SignInPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            cooldown:     Duration::from_secs(15 * 60),
+            session_ttl:  Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+
+// everything a User needs to sign in, kept together so User stays readable:
+#[derive(Debug, Clone, Default)]
+pub struct SignInState {
+    pub password:        Option<PasswordHash>,
+    pub last_sign_in:    Option<SystemTime>,
+    pub failed_attempts: u32,
+    pub locked_until:    Option<SystemTime>,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignInError {
+    Inactive,
+    NoPassword,
+    WrongPassword { attempts_left: u32 },
+    Locked { retry_in: Duration },
+}
+
+impl fmt::Display for SignInError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignInError::Inactive                           => write!(f, "account is deactivated"),
+            SignInError::NoPassword                         => write!(f, "account has no password set"),
+            SignInError::WrongPassword { attempts_left: 0 } => write!(f, "wrong password, the account is now locked"),
+            SignInError::WrongPassword { attempts_left }    => write!(f, "wrong password ({attempts_left} attempts left)"),
+            SignInError::Locked { retry_in }                => write!(f, "account is locked, try again in {}s", retry_in.as_secs()),
+        }
+    }
+}
+
+impl std::error::Error for SignInError {}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub token:      String,
+    pub username:   Username,
+    pub issued_at:  SystemTime,
+    pub expires_at: SystemTime,
+}
+
+impl Session {
+    pub fn is_valid(&self, clock: &impl Clock) -> bool {
+        clock.now() < self.expires_at
+    }
+
+    // None once the session has expired:
+    pub fn remaining(&self, clock: &impl Clock) -> Option<Duration> {
+        self.expires_at.duration_since(clock.now()).ok().filter(|d| !d.is_zero())
+    }
+}
+
+
+impl User {
+    pub fn set_password(&mut self, password: &str) {
+        self.auth.password = Some(PasswordHash::new(password));
+    }
+
+    // signs in with the real clock and the default policy:
+    pub fn sign_in(&mut self, password: &str) -> Result<Session, SignInError> {
+        self.sign_in_with(password, &SystemClock, &SignInPolicy::default())
+    }
+
+    pub fn sign_in_with(
+        &mut self,
+        password: &str,
+        clock: &impl Clock,
+        policy: &SignInPolicy,
+    ) -> Result<Session, SignInError> {
+        let now = clock.now();
+
+        if !self.active {
+            return Err(SignInError::Inactive);
+        }
+        if let Some(retry_in) = self.locked_for(clock) {
+            return Err(SignInError::Locked { retry_in });
+        }
+
+        let Some(hash) = &self.auth.password else {
+            return Err(SignInError::NoPassword);
+        };
+
+        if !hash.verify(password) {
+            self.auth.failed_attempts += 1;
+            let attempts_left = policy.max_attempts.saturating_sub(self.auth.failed_attempts);
+
+            if attempts_left == 0 {
+                self.auth.failed_attempts = 0;
+                self.auth.locked_until = Some(now + policy.cooldown);
+            }
+
+            return Err(SignInError::WrongPassword { attempts_left });
+        }
+
+        self.auth.failed_attempts = 0;
+        self.auth.locked_until = None;
+        self.auth.last_sign_in = Some(now);
+        self.sign_in_count += 1;
+
+        Ok(Session {
+            token:      to_hex(&random_bytes::<32>()),
+            username:   self.username.clone(),
+            issued_at:  now,
+            expires_at: now + policy.session_ttl,
+        })
+    }
+
+    // how much longer the account stays locked, if it's locked at all:
+    pub fn locked_for(&self, clock: &impl Clock) -> Option<Duration> {
+        let until = self.auth.locked_until?;
+        until.duration_since(clock.now()).ok().filter(|d| !d.is_zero())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::clock::ManualClock;
+    use crate::user::{Email, build_user};
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    fn policy() -> SignInPolicy {
+        SignInPolicy { max_attempts: 3, cooldown: Duration::from_secs(60), session_ttl: Duration::from_secs(3600) }
+    }
+
+    fn user() -> User {
+        let mut user = build_user(Username::parse("ferris").unwrap(), Email::parse("ferris@example.com").unwrap());
+        user.set_password("correct horse");
+        user
+    }
+
+    #[test]
+    fn sessions_expire_after_their_ttl() {
+        let clock = ManualClock::at_unix(1_000_000);
+        let mut user = user();
+
+        let session = user.sign_in_with("correct horse", &clock, &policy()).unwrap();
+        assert_eq!(session.expires_at, session.issued_at + policy().session_ttl);
+        assert_eq!(user.sign_in_count, 2);
+        assert_eq!(user.auth.last_sign_in, Some(clock.now()));
+
+        clock.advance(policy().session_ttl - SECOND);
+        assert!(session.is_valid(&clock));
+        assert_eq!(session.remaining(&clock), Some(SECOND));
+
+        clock.advance(SECOND);
+        assert!(!session.is_valid(&clock));
+        assert_eq!(session.remaining(&clock), None);
+    }
+
+    #[test]
+    fn too_many_wrong_passwords_lock_the_account_for_the_cooldown() {
+        let clock = ManualClock::at_unix(1_000_000);
+        let mut user = user();
+
+        assert_eq!(user.sign_in_with("wrong", &clock, &policy()), Err(SignInError::WrongPassword { attempts_left: 2 }));
+        assert_eq!(user.sign_in_with("wrong", &clock, &policy()), Err(SignInError::WrongPassword { attempts_left: 1 }));
+        assert_eq!(user.sign_in_with("wrong", &clock, &policy()), Err(SignInError::WrongPassword { attempts_left: 0 }));
+
+        // locked, even with the right password, and it counts down as time passes:
+        assert_eq!(user.sign_in_with("correct horse", &clock, &policy()), Err(SignInError::Locked { retry_in: policy().cooldown }));
+        clock.advance(policy().cooldown - SECOND);
+        assert_eq!(user.locked_for(&clock), Some(SECOND));
+        assert_eq!(user.sign_in_with("correct horse", &clock, &policy()), Err(SignInError::Locked { retry_in: SECOND }));
+
+        // and then it's unlocked again, with a fresh set of attempts:
+        clock.advance(SECOND);
+        assert_eq!(user.locked_for(&clock), None);
+        assert_eq!(user.sign_in_with("wrong", &clock, &policy()), Err(SignInError::WrongPassword { attempts_left: 2 }));
+        assert!(user.sign_in_with("correct horse", &clock, &policy()).is_ok());
+        assert_eq!(user.auth.failed_attempts, 0);
+        assert_eq!(user.auth.locked_until, None);
+    }
+
+    #[test]
+    fn a_right_password_resets_the_failed_attempts() {
+        let clock = ManualClock::at_unix(1_000_000);
+        let mut user = user();
+
+        for _ in 0..2 {
+            assert!(user.sign_in_with("wrong", &clock, &policy()).is_err());
+            assert!(user.sign_in_with("correct horse", &clock, &policy()).is_ok());
+        }
+        assert_eq!(user.auth.locked_until, None);
+    }
+
+    #[test]
+    fn inactive_users_and_missing_passwords_are_turned_away() {
+        let clock = ManualClock::at_unix(1_000_000);
+
+        let mut inactive = user().with_active(false);
+        assert_eq!(inactive.sign_in_with("correct horse", &clock, &policy()), Err(SignInError::Inactive));
+
+        let mut no_password = build_user(Username::parse("crab").unwrap(), Email::parse("crab@example.com").unwrap());
+        assert_eq!(no_password.sign_in_with("anything", &clock, &policy()), Err(SignInError::NoPassword));
+    }
+}
@@ -0,0 +1,182 @@
+// Username newtype.
+// Like Email, a Username can only be made by parsing, so holding one means it's valid.
+// what counts as valid is up to UsernameRules; the defaults are:
+// - 3 to 32 chars long.
+// - ASCII letters, digits, and the extra chars "_-.".
+// - starts with a letter.
+// - not a reserved name like "admin" or "root" (compared case-insensitively).
+
+use std::fmt;
+use std::str::FromStr;
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UsernameError {
+    TooShort { min: usize, len: usize },
+    TooLong { max: usize, len: usize },
+    InvalidChar(char),
+    MustStartWithLetter,
+    Reserved(String),
+}
+
+impl fmt::Display for UsernameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UsernameError::TooShort { min, len } => write!(f, "username is {len} chars long, it needs at least {min}"),
+            UsernameError::TooLong { max, len }  => write!(f, "username is {len} chars long, the limit is {max}"),
+            UsernameError::InvalidChar(c)        => write!(f, "{c:?} isn't allowed in usernames"),
+            UsernameError::MustStartWithLetter   => write!(f, "username has to start with a letter"),
+            UsernameError::Reserved(name)        => write!(f, "{name:?} is a reserved name"),
+        }
+    }
+}
+
+impl std::error::Error for UsernameError {}
+
+
+#[derive(Debug, Clone)]
+pub struct UsernameRules {
+    pub min_len:                usize,
+    pub max_len:                usize,
+    pub allow_unicode:          bool,    // any unicode letter or digit instead of just ASCII
+    pub extra_chars:            String,  // allowed on top of letters and digits
+    pub must_start_with_letter: bool,
+    pub reserved:               Vec<String>,
+}
+
+impl Default for UsernameRules {
+    fn default() -> Self {
+        Self {
+            min_len:                3,
+            max_len:                32,
+            allow_unicode:          false,
+            extra_chars:            String::from("_-."),
+            must_start_with_letter: true,
+            reserved: ["admin", "administrator", "root", "system", "support", "null"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+impl UsernameRules {
+    pub fn check(&self, s: &str) -> Result<(), UsernameError> {
+        let len = s.chars().count();
+
+        if len < self.min_len {
+            return Err(UsernameError::TooShort { min: self.min_len, len });
+        }
+        if len > self.max_len {
+            return Err(UsernameError::TooLong { max: self.max_len, len });
+        }
+
+        let allowed = |c: char| match self.allow_unicode {
+            true  => c.is_alphanumeric() || self.extra_chars.contains(c),
+            false => c.is_ascii_alphanumeric() || self.extra_chars.contains(c),
+        };
+
+        if let Some(c) = s.chars().find(|&c| !allowed(c)) {
+            return Err(UsernameError::InvalidChar(c));
+        }
+        if self.must_start_with_letter && !s.starts_with(char::is_alphabetic) {
+            return Err(UsernameError::MustStartWithLetter);
+        }
+        if self.reserved.iter().any(|r| r.to_lowercase() == s.to_lowercase()) {
+            return Err(UsernameError::Reserved(s.to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Username(String);
+
+impl Username {
+    // parses with the default rules:
+    pub fn parse(s: &str) -> Result<Self, UsernameError> {
+        Self::parse_with(s, &UsernameRules::default())
+    }
+
+    pub fn parse_with(s: &str, rules: &UsernameRules) -> Result<Self, UsernameError> {
+        rules.check(s)?;
+        Ok(Self(s.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Username {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Username {
+    type Err = UsernameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_default_rules() {
+        let longest = "a".repeat(32);
+        let too_long = "a".repeat(33);
+
+        let cases = [
+            ("abc",           Ok(())),
+            ("user-2",        Ok(())),
+            ("First.Last_9",  Ok(())),
+            (&longest,        Ok(())),
+            ("ab",            Err(UsernameError::TooShort { min: 3, len: 2 })),
+            ("",              Err(UsernameError::TooShort { min: 3, len: 0 })),
+            (&too_long,       Err(UsernameError::TooLong { max: 32, len: 33 })),
+            ("no spaces",     Err(UsernameError::InvalidChar(' '))),
+            ("émile",         Err(UsernameError::InvalidChar('é'))),
+            ("9lives",        Err(UsernameError::MustStartWithLetter)),
+            ("_underscore",   Err(UsernameError::MustStartWithLetter)),
+            ("admin",         Err(UsernameError::Reserved(String::from("admin")))),
+            ("Root",          Err(UsernameError::Reserved(String::from("Root")))),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(Username::parse(name).map(|_| ()), expected, "{name:?}");
+        }
+    }
+
+    #[test]
+    fn rules_can_be_changed() {
+        let rules = UsernameRules {
+            min_len: 2,
+            allow_unicode: true,
+            extra_chars: String::new(),
+            must_start_with_letter: false,
+            reserved: vec![String::from("émile")],
+            ..UsernameRules::default()
+        };
+
+        let cases = [
+            ("zoë",    Ok(())),
+            ("9lives", Ok(())),
+            ("admin",  Ok(())),
+            ("éé",     Ok(())),  // lengths are in chars, not bytes
+            ("é",      Err(UsernameError::TooShort { min: 2, len: 1 })),
+            ("user-2", Err(UsernameError::InvalidChar('-'))),
+            ("ÉMILE",  Err(UsernameError::Reserved(String::from("ÉMILE")))),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(Username::parse_with(name, &rules).map(|_| ()), expected, "{name:?}");
+        }
+    }
+}
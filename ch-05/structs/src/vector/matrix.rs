@@ -0,0 +1,313 @@
+// 4x4 transformation matrices.
+// a 3x3 matrix can rotate and scale, but it can't move things (translation isn't linear).
+// the usual trick is to give every point a fourth coordinate, w = 1, and use 4x4 matrices:
+// the last column then adds an offset. directions get w = 0, so moving them does nothing.
+//
+// * matrices combine by multiplying, and the one on the *right* happens first:
+// ? let m = Mat4::translation(v) * Mat4::rotation_z(angle);  // rotate, then move
+// ? m * point
+
+use std::fmt;
+use std::ops::Mul;
+
+use super::{Float, Scalar, Vec3};
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4<T> {
+    pub rows: [[T; 4]; 4],
+}
+
+impl<T: Scalar> Mat4<T> {
+    pub const IDENTITY: Self = Self::diagonal(T::ONE, T::ONE, T::ONE);
+
+    pub const fn new(rows: [[T; 4]; 4]) -> Self {
+        Self { rows }
+    }
+
+    const fn diagonal(x: T, y: T, z: T) -> Self {
+        let (o, i) = (T::ZERO, T::ONE);
+
+        Self::new([
+            [x, o, o, o],
+            [o, y, o, o],
+            [o, o, z, o],
+            [o, o, o, i],
+        ])
+    }
+
+    pub fn translation(offset: Vec3<T>) -> Self {
+        let mut m = Self::IDENTITY;
+        m.rows[0][3] = offset.x;
+        m.rows[1][3] = offset.y;
+        m.rows[2][3] = offset.z;
+        m
+    }
+
+    // a different scale for each axis (a negative one mirrors along that axis):
+    pub fn scaling(factors: Vec3<T>) -> Self {
+        Self::diagonal(factors.x, factors.y, factors.z)
+    }
+
+    pub fn column(&self, i: usize) -> [T; 4] {
+        self.rows.map(|row| row[i])
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::new([0, 1, 2, 3].map(|i| self.column(i)))
+    }
+
+    // moves, rotates and scales a position (w = 1):
+    pub fn transform_point(&self, p: Vec3<T>) -> Vec3<T> {
+        let [x, y, z, _] = self.apply([p.x, p.y, p.z, T::ONE]);
+        Vec3::new(x, y, z)
+    }
+
+    // rotates and scales a direction, without moving it (w = 0):
+    pub fn transform_vector(&self, v: Vec3<T>) -> Vec3<T> {
+        let [x, y, z, _] = self.apply([v.x, v.y, v.z, T::ZERO]);
+        Vec3::new(x, y, z)
+    }
+
+    fn apply(&self, v: [T; 4]) -> [T; 4] {
+        self.rows.map(|row| dot(row, v))
+    }
+
+    // * how much the matrix scales volumes (negative if it mirrors, 0 if it flattens things).
+    // expanded along the top row, with each 3x3 minor worked out by the rule of Sarrus.
+    pub fn determinant(&self) -> T {
+        let m = &self.rows;
+
+        let minor = |col: usize| {
+            let c: Vec<usize> = (0..4).filter(|&c| c != col).collect();
+            let at = |r: usize, i: usize| m[r][c[i]];
+
+            at(1, 0) * (at(2, 1) * at(3, 2) - at(2, 2) * at(3, 1))
+                - at(1, 1) * (at(2, 0) * at(3, 2) - at(2, 2) * at(3, 0))
+                + at(1, 2) * (at(2, 0) * at(3, 1) - at(2, 1) * at(3, 0))
+        };
+
+        m[0][0] * minor(0) - m[0][1] * minor(1) + m[0][2] * minor(2) - m[0][3] * minor(3)
+    }
+}
+
+impl<T: Float> Mat4<T> {
+    pub fn uniform_scaling(factor: T) -> Self {
+        Self::diagonal(factor, factor, factor)
+    }
+
+    // * rotations by `radians` around each axis, counter-clockwise when looking
+    // down the axis towards the origin (the right-hand rule):
+    pub fn rotation_x(radians: T) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        let (o, i) = (T::ZERO, T::ONE);
+
+        Self::new([
+            [i, o,   o,    o],
+            [o, cos, -sin, o],
+            [o, sin, cos,  o],
+            [o, o,   o,    i],
+        ])
+    }
+
+    pub fn rotation_y(radians: T) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        let (o, i) = (T::ZERO, T::ONE);
+
+        Self::new([
+            [cos,  o, sin, o],
+            [o,    i, o,   o],
+            [-sin, o, cos, o],
+            [o,    o, o,   i],
+        ])
+    }
+
+    pub fn rotation_z(radians: T) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        let (o, i) = (T::ZERO, T::ONE);
+
+        Self::new([
+            [cos, -sin, o, o],
+            [sin, cos,  o, o],
+            [o,   o,    i, o],
+            [o,   o,    o, i],
+        ])
+    }
+
+    // around any axis through the origin (Rodrigues' rotation formula):
+    pub fn rotation(axis: Vec3<T>, radians: T) -> Option<Self> {
+        let Vec3 { x, y, z } = axis.normalized()?;
+        let (sin, cos) = radians.sin_cos();
+        let (o, i) = (T::ZERO, T::ONE);
+        let t = i - cos;
+
+        Some(Self::new([
+            [t * x * x + cos,     t * x * y - sin * z, t * x * z + sin * y, o],
+            [t * x * y + sin * z, t * y * y + cos,     t * y * z - sin * x, o],
+            [t * x * z - sin * y, t * y * z + sin * x, t * z * z + cos,     o],
+            [o,                   o,                   o,                   i],
+        ]))
+    }
+
+    // * the matrix that undoes this one, or None if it flattens space (determinant 0),
+    // since the squashed dimension can't be brought back.
+    // Gauss-Jordan elimination: row operations that turn the matrix into the identity
+    // turn an identity matrix beside it into the inverse.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut m = self.rows;
+        let mut inv = Self::IDENTITY.rows;
+
+        for col in 0..4 {
+            // the biggest pivot is the most accurate one to divide by:
+            let pivot = (col..4).max_by(|&a, &b| {
+                m[a][col].abs().partial_cmp(&m[b][col].abs()).unwrap_or(std::cmp::Ordering::Equal)
+            })?;
+
+            if m[pivot][col].abs() <= T::EPSILON {
+                return None;
+            }
+
+            m.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = m[col][col];
+            for j in 0..4 {
+                m[col][j] = m[col][j] / scale;
+                inv[col][j] = inv[col][j] / scale;
+            }
+
+            for row in (0..4).filter(|&r| r != col) {
+                let factor = m[row][col];
+                for j in 0..4 {
+                    m[row][j] = m[row][j] - factor * m[col][j];
+                    inv[row][j] = inv[row][j] - factor * inv[col][j];
+                }
+            }
+        }
+
+        Some(Self::new(inv))
+    }
+
+    pub fn approx_eq(&self, other: &Self, tolerance: T) -> bool {
+        (0..4).all(|r| (0..4).all(|c| (self.rows[r][c] - other.rows[r][c]).abs() <= tolerance))
+    }
+}
+
+
+fn dot<T: Scalar>(a: [T; 4], b: [T; 4]) -> T {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3]
+}
+
+
+impl<T: Scalar> Mul for Mat4<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(self.rows.map(|row| [0, 1, 2, 3].map(|c| dot(row, other.column(c)))))
+    }
+}
+
+// matrix * point transforms it as a position:
+impl<T: Scalar> Mul<Vec3<T>> for Mat4<T> {
+    type Output = Vec3<T>;
+
+    fn mul(self, point: Vec3<T>) -> Vec3<T> {
+        self.transform_point(point)
+    }
+}
+
+impl<T: Scalar> Default for Mat4<T> {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Mat4<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in &self.rows {
+            match f.precision() {
+                Some(p) => writeln!(f, "[{:>8.p$} {:>8.p$} {:>8.p$} {:>8.p$}]", row[0], row[1], row[2], row[3])?,
+                None    => writeln!(f, "[{:>8} {:>8} {:>8} {:>8}]", row[0], row[1], row[2], row[3])?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Random;
+
+    // small enough that a product of three matrices can't overflow an i64:
+    fn matrix(random: &mut Random) -> Mat4<i64> {
+        Mat4::new([[0; 4]; 4].map(|row| row.map(|_: i64| random.small())))
+    }
+
+    #[test]
+    fn multiplying_follows_the_matrix_laws() {
+        let mut random = Random(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..1000 {
+            let (a, b, c) = (matrix(&mut random), matrix(&mut random), matrix(&mut random));
+
+            assert_eq!(a * Mat4::IDENTITY, a);
+            assert_eq!(Mat4::IDENTITY * a, a);
+            assert_eq!((a * b) * c, a * (b * c));
+            assert_eq!((a * b).transpose(), b.transpose() * a.transpose());
+            assert_eq!(a.transpose().transpose(), a);
+
+            // transforming points drops w, so this only works for affine matrices (a bottom row of 0 0 0 1):
+            let affine = |mut m: Mat4<i64>| {
+                m.rows[3] = [0, 0, 0, 1];
+                m
+            };
+            let p = Vec3::new(random.small(), random.small(), random.small());
+            assert_eq!((affine(a) * affine(b)) * p, affine(a) * (affine(b) * p));
+        }
+    }
+
+    #[test]
+    fn multiplying_by_the_inverse_is_the_identity() {
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..1000 {
+            let m = Mat4::new(matrix(&mut random).rows.map(|row| row.map(|n| n as f64 / 10.0)));
+
+            // nearly singular ones lose too much precision to compare:
+            if m.determinant().abs() < 1.0 {
+                continue;
+            }
+
+            let inverse = m.inverse().expect("the determinant isn't 0");
+            assert!((m * inverse).approx_eq(&Mat4::IDENTITY, 1e-6), "{m}");
+            assert!((inverse * m).approx_eq(&Mat4::IDENTITY, 1e-6), "{m}");
+        }
+    }
+    #[test]
+    fn axis_rotations_match_the_general_one() {
+        let mut random = Random(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..1000 {
+            let angle = random.float(std::f64::consts::TAU);
+
+            assert!(Mat4::rotation_x(angle).approx_eq(&Mat4::rotation(Vec3::X, angle).unwrap(), 1e-12));
+            assert!(Mat4::rotation_y(angle).approx_eq(&Mat4::rotation(Vec3::Y, angle).unwrap(), 1e-12));
+            assert!(Mat4::rotation_z(angle).approx_eq(&Mat4::rotation(Vec3::Z * 2.0, angle).unwrap(), 1e-12));
+
+            // a rotation's inverse is its transpose, and it keeps lengths:
+            let m = Mat4::rotation(Vec3::new(1.0, -2.0, 0.5), angle).unwrap();
+            assert!((m * m.transpose()).approx_eq(&Mat4::IDENTITY, 1e-12));
+            let v = Vec3::new(random.small() as f64, random.small() as f64, random.small() as f64);
+            assert!(((m * v).length() - v.length()).abs() < 1e-9);
+        }
+
+        let quarter = std::f64::consts::FRAC_PI_2;
+        assert!((Mat4::rotation_x(quarter) * Vec3::Y).approx_eq(Vec3::Z, 1e-12));
+        assert!((Mat4::rotation_y(quarter) * Vec3::Z).approx_eq(Vec3::X, 1e-12));
+        assert!((Mat4::rotation_z(quarter) * Vec3::X).approx_eq(Vec3::Y, 1e-12));
+        assert_eq!(Mat4::rotation(Vec3::ZERO, quarter), None);
+    }
+}
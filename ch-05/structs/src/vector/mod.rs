@@ -0,0 +1,345 @@
+// 3-D vectors!
+// The chapter's Point(i32, i32, i32) could only be printed. Vec3 is a point (or a direction)
+// in 3-D space that can actually be calculated with, using the normal math operators.
+//
+// * it's generic over its Scalar type, so the same Vec3 works for whole numbers and decimals:
+// ? Vec3::new(1, 2, 3) + Vec3::new(4, 5, 6)      => (5, 7, 9)
+// ? Vec3::new(1.0, 0.0, 0.0).cross(Vec3::Y)      => (0, 0, 1)
+// anything that needs square roots or angles (normalizing, rotating...) needs a Float scalar.
+//
+// * Modules:
+// - scalar:      the Scalar and Float traits, implemented for i32, i64, f32 and f64.
+// - matrix:      4x4 transformation matrices (translate, scale, rotate, and combinations of them).
+// - quaternion:  rotations that can be combined and smoothly interpolated without gimbal lock.
+//
+// the axes are right-handed: x to the right, y up, z towards the viewer.
+
+pub mod matrix;
+pub mod quaternion;
+pub mod scalar;
+
+pub use matrix::Mat4;
+pub use quaternion::Quaternion;
+pub use scalar::{Float, Scalar};
+
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, DivAssign, Index, Mul, MulAssign, Neg, Sub, SubAssign};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+// a point is a position, a vector is a direction and length, but the math is the same:
+pub type Point3<T> = Vec3<T>;
+
+impl<T: Scalar> Vec3<T> {
+    pub const ZERO: Self = Self { x: T::ZERO, y: T::ZERO, z: T::ZERO };
+    pub const X: Self    = Self { x: T::ONE, y: T::ZERO, z: T::ZERO };
+    pub const Y: Self    = Self { x: T::ZERO, y: T::ONE, z: T::ZERO };
+    pub const Z: Self    = Self { x: T::ZERO, y: T::ZERO, z: T::ONE };
+
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn splat(n: T) -> Self {
+        Self::new(n, n, n)
+    }
+
+    // * dot product: how much two vectors point the same way.
+    // positive if they're less than 90 degrees apart, 0 if perpendicular, negative if more.
+    pub fn dot(self, other: Self) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    // * cross product: a vector perpendicular to both, as long as the area of the
+    // parallelogram they make. X.cross(Y) == Z, and swapping them flips the result.
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    // no square root, so it stays exact for integers (and it's enough for comparing lengths):
+    pub fn length_squared(self) -> T {
+        self.dot(self)
+    }
+
+    // * norms (different ways of measuring "how long"):
+    // - length (L2):     the straight line distance.
+    // - manhattan (L1):  the distance along the grid, like walking city blocks.
+    // - chebyshev (L∞):  the biggest single step, like a king moving on a chessboard.
+    pub fn length(self) -> f64 {
+        self.length_squared().to_f64().sqrt()
+    }
+
+    pub fn manhattan(self) -> T {
+        self.x.abs() + self.y.abs() + self.z.abs()
+    }
+
+    pub fn chebyshev(self) -> T {
+        let max = |a: T, b: T| if a > b { a } else { b };
+        max(max(self.x.abs(), self.y.abs()), self.z.abs())
+    }
+
+    pub fn distance(self, other: Self) -> f64 {
+        (other - self).length()
+    }
+
+    pub fn distance_squared(self, other: Self) -> T {
+        (other - self).length_squared()
+    }
+
+    // multiplies channel by channel, for scaling each axis differently:
+    pub fn scale(self, other: Self) -> Self {
+        Self::new(self.x * other.x, self.y * other.y, self.z * other.z)
+    }
+
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Vec3<U> {
+        Vec3 { x: f(self.x), y: f(self.y), z: f(self.z) }
+    }
+
+    // an integer vector as decimals, for the math that needs square roots:
+    pub fn to_f64(self) -> Vec3<f64> {
+        self.map(Scalar::to_f64)
+    }
+
+    pub fn to_array(self) -> [T; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+impl<T: Float> Vec3<T> {
+    // the same direction, with a length of 1 (or None for the zero vector, which has no direction):
+    pub fn normalized(self) -> Option<Self> {
+        let length = self.length_squared().sqrt();
+
+        match length > T::EPSILON {
+            true  => Some(self / length),
+            false => None,
+        }
+    }
+
+    // in radians, between 0 and pi:
+    pub fn angle_to(self, other: Self) -> T {
+        // atan2 of |a x b| and a . b stays accurate for nearly (anti)parallel vectors, where acos doesn't:
+        let cross = self.cross(other).length_squared().sqrt();
+        cross.atan2(self.dot(other))
+    }
+
+    // the part of this vector that points along the other one:
+    pub fn project_onto(self, other: Self) -> Self {
+        other * (self.dot(other) / other.length_squared())
+    }
+
+    // bounced off a surface with the given normal (which should be normalized):
+    pub fn reflect(self, normal: Self) -> Self {
+        self - normal * (self.dot(normal) * T::from_f64(2.0))
+    }
+
+    // `t` of the way from this point to the other:
+    pub fn lerp(self, other: Self, t: T) -> Self {
+        self + (other - self) * t
+    }
+
+    pub fn approx_eq(self, other: Self, tolerance: T) -> bool {
+        (self - other).chebyshev() <= tolerance
+    }
+}
+
+
+// * operators:
+// vector + vector, vector - vector, -vector, vector * scalar, vector / scalar,
+// and the assigning versions (+=, -=, *=, /=).
+
+impl<T: Scalar> Add for Vec3<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl<T: Scalar> Sub for Vec3<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl<T: Scalar> Neg for Vec3<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Scalar> Mul<T> for Vec3<T> {
+    type Output = Self;
+
+    fn mul(self, n: T) -> Self {
+        Self::new(self.x * n, self.y * n, self.z * n)
+    }
+}
+
+// dividing integer vectors rounds each part towards zero, like integer division does:
+impl<T: Scalar> Div<T> for Vec3<T> {
+    type Output = Self;
+
+    fn div(self, n: T) -> Self {
+        Self::new(self.x / n, self.y / n, self.z / n)
+    }
+}
+
+impl<T: Scalar> AddAssign for Vec3<T> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Scalar> SubAssign for Vec3<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<T: Scalar> MulAssign<T> for Vec3<T> {
+    fn mul_assign(&mut self, n: T) {
+        *self = *self * n;
+    }
+}
+
+impl<T: Scalar> DivAssign<T> for Vec3<T> {
+    fn div_assign(&mut self, n: T) {
+        *self = *self / n;
+    }
+}
+
+// scalar * vector too, not just vector * scalar. a generic `impl<T> Mul<Vec3<T>> for T`
+// isn't allowed (T could be a type from another crate), so it's one impl per scalar type:
+macro_rules! scalar_times_vector {
+    ($($t:ty),*) => {$(
+        impl Mul<Vec3<$t>> for $t {
+            type Output = Vec3<$t>;
+
+            fn mul(self, v: Vec3<$t>) -> Vec3<$t> {
+                v * self
+            }
+        }
+    )*};
+}
+
+scalar_times_vector!(i32, i64, f32, f64);
+
+impl<T: Scalar> Sum for Vec3<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl<T> Index<usize> for Vec3<T> {
+    type Output = T;
+
+    fn index(&self, axis: usize) -> &T {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("a Vec3 has 3 axes, there's no axis {axis}"),
+        }
+    }
+}
+
+impl<T> From<[T; 3]> for Vec3<T> {
+    fn from([x, y, z]: [T; 3]) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T> From<(T, T, T)> for Vec3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Vec3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // passes the precision on, so {:.2} works for float vectors:
+        match f.precision() {
+            Some(p) => write!(f, "({:.p$}, {:.p$}, {:.p$})", self.x, self.y, self.z),
+            None    => write!(f, "({}, {}, {})", self.x, self.y, self.z),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Random;
+
+    // * the laws every Scalar's vectors should follow. with small whole numbers, they're exact for floats too:
+    fn check_laws<T: Scalar>(from: impl Fn(i32) -> T) {
+        let mut random = Random(0x9e37_79b9_7f4a_7c15);
+        let small = |random: &mut Random| from(random.small() as i32);
+        let vector = |random: &mut Random| Vec3::new(small(random), small(random), small(random));
+
+        for _ in 0..1000 {
+            let (u, v, w) = (vector(&mut random), vector(&mut random), vector(&mut random));
+            let (a, b) = (small(&mut random), small(&mut random));
+
+            assert_eq!(u + v, v + u);
+            assert_eq!((u + v) + w, u + (v + w));
+            assert_eq!(u + Vec3::splat(T::ZERO), u);
+            assert_eq!(u + -u, Vec3::splat(T::ZERO));
+            assert_eq!(u - v, u + -v);
+
+            // scaling distributes over adding, both ways round:
+            assert_eq!((u + v) * a, u * a + v * a);
+            assert_eq!(u * (a + b), u * a + u * b);
+            assert_eq!(u * T::ONE, u);
+
+            assert_eq!(u.dot(v), v.dot(u));
+            assert_eq!(u.dot(v + w), u.dot(v) + u.dot(w));
+            assert_eq!(u.cross(v), -v.cross(u));
+            assert_eq!(u.cross(v + w), u.cross(v) + u.cross(w));
+            assert_eq!(u.cross(v).dot(u), T::ZERO);
+        }
+    }
+
+    #[test]
+    fn vector_laws_hold_for_every_scalar() {
+        check_laws(|n| n);
+        check_laws(|n| n as i64);
+        check_laws(|n| n as f32);
+        check_laws(|n| n as f64);
+    }
+
+    // fractions get rounded, so floats only follow the laws approximately:
+    #[test]
+    fn vector_laws_hold_roughly_for_fractions() {
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+        let mut fraction = || random.small() as f64 / 7.0;
+
+        for _ in 0..1000 {
+            let u = Vec3::new(fraction(), fraction(), fraction());
+            let v = Vec3::new(fraction(), fraction(), fraction());
+            let w = Vec3::new(fraction(), fraction(), fraction());
+            let a = fraction();
+
+            assert_eq!(u + v, v + u);
+            assert!(((u + v) + w).approx_eq(u + (v + w), 1e-9));
+            assert!(((u + v) * a).approx_eq(u * a + v * a, 1e-9));
+            assert!((u.dot(v + w) - (u.dot(v) + u.dot(w))).abs() < 1e-6);
+        }
+    }
+}
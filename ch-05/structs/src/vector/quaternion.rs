@@ -0,0 +1,284 @@
+// Quaternions.
+// a rotation stored as four numbers: how far to turn (w) and around which axis (x, y, z).
+// compared to rotation matrices they're smaller, they don't drift out of shape when
+// multiplied many times (just normalize), and two rotations can be smoothly blended (slerp).
+//
+// * a rotation of `angle` around a unit `axis` is:
+// ? w = cos(angle / 2),  (x, y, z) = axis * sin(angle / 2)
+// multiplying two quaternions combines their rotations (the right one happens first, like matrices).
+
+use std::fmt;
+use std::ops::Mul;
+
+use super::{Float, Mat4, Vec3};
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quaternion<T> {
+    pub w: T,
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Float> Quaternion<T> {
+    pub const IDENTITY: Self = Self { w: T::ONE, x: T::ZERO, y: T::ZERO, z: T::ZERO };
+
+    pub const fn new(w: T, x: T, y: T, z: T) -> Self {
+        Self { w, x, y, z }
+    }
+
+    // None if the axis is the zero vector, which doesn't point anywhere:
+    pub fn from_axis_angle(axis: Vec3<T>, radians: T) -> Option<Self> {
+        let axis = axis.normalized()?;
+        let (sin, cos) = (radians / T::from_f64(2.0)).sin_cos();
+
+        Some(Self::new(cos, axis.x * sin, axis.y * sin, axis.z * sin))
+    }
+
+    // the shortest rotation that turns one direction into the other:
+    pub fn between(from: Vec3<T>, to: Vec3<T>) -> Option<Self> {
+        let (from, to) = (from.normalized()?, to.normalized()?);
+        let axis = from.cross(to);
+
+        match axis.normalized() {
+            Some(axis) => Self::from_axis_angle(axis, from.angle_to(to)),
+            // parallel: either no rotation at all, or half a turn around any perpendicular axis
+            None if from.dot(to) > T::ZERO => Some(Self::IDENTITY),
+            None => {
+                let perpendicular = from.cross(Vec3::X).normalized().or_else(|| from.cross(Vec3::Y).normalized())?;
+                Self::from_axis_angle(perpendicular, T::PI)
+            },
+        }
+    }
+
+    // the axis and the angle (in radians, between 0 and 2pi) it rotates by:
+    pub fn to_axis_angle(self) -> (Vec3<T>, T) {
+        let q = self.normalized();
+        let angle = q.w.acos() * T::from_f64(2.0);
+        let axis = Vec3::new(q.x, q.y, q.z).normalized().unwrap_or(Vec3::X);
+
+        (axis, angle)
+    }
+
+    pub fn dot(self, other: Self) -> T {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn length(self) -> T {
+        self.dot(self).sqrt()
+    }
+
+    // rounding errors slowly stretch a quaternion, and only unit ones are pure rotations:
+    pub fn normalized(self) -> Self {
+        let length = self.length();
+
+        match length > T::EPSILON {
+            true  => Self::new(self.w / length, self.x / length, self.y / length, self.z / length),
+            false => Self::IDENTITY,
+        }
+    }
+
+    // for a unit quaternion, the conjugate is the opposite rotation:
+    pub fn conjugate(self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    pub fn inverse(self) -> Self {
+        let n = self.dot(self);
+        let c = self.conjugate();
+        Self::new(c.w / n, c.x / n, c.y / n, c.z / n)
+    }
+
+    // * rotating a vector: q * v * q⁻¹, with v as a quaternion with w = 0.
+    // this is the same thing expanded, which skips the multiplications by zero:
+    pub fn rotate(self, v: Vec3<T>) -> Vec3<T> {
+        let q = Vec3::new(self.x, self.y, self.z);
+        let two = T::from_f64(2.0);
+        let t = q.cross(v) * two;
+
+        v + t * self.w + q.cross(t)
+    }
+
+    pub fn to_matrix(self) -> Mat4<T> {
+        let Self { w, x, y, z } = self.normalized();
+        let (o, i, two) = (T::ZERO, T::ONE, T::from_f64(2.0));
+
+        Mat4::new([
+            [i - two * (y * y + z * z), two * (x * y - w * z),     two * (x * z + w * y),     o],
+            [two * (x * y + w * z),     i - two * (x * x + z * z), two * (y * z - w * x),     o],
+            [two * (x * z - w * y),     two * (y * z + w * x),     i - two * (x * x + y * y), o],
+            [o,                         o,                         o,                         i],
+        ])
+    }
+
+    // * spherical linear interpolation: `t` of the way from this rotation to the other,
+    // turning at a constant speed (a plain lerp would speed up in the middle).
+    pub fn slerp(self, other: Self, t: T) -> Self {
+        let (a, mut b) = (self.normalized(), other.normalized());
+        let mut cos = a.dot(b);
+
+        // q and -q are the same rotation, pick the one that's the short way round:
+        if cos < T::ZERO {
+            b = Self::new(-b.w, -b.x, -b.y, -b.z);
+            cos = -cos;
+        }
+
+        let (wa, wb) = if cos > T::ONE - T::EPSILON {
+            // nearly the same rotation, sin(angle) is about 0, so just lerp:
+            (T::ONE - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin_cos().0;
+            (((T::ONE - t) * angle).sin_cos().0 / sin, (t * angle).sin_cos().0 / sin)
+        };
+
+        Self::new(
+            a.w * wa + b.w * wb,
+            a.x * wa + b.x * wb,
+            a.y * wa + b.y * wb,
+            a.z * wa + b.z * wb,
+        ).normalized()
+    }
+
+    // q and -q are the same rotation, so both count as equal:
+    pub fn approx_eq(self, other: Self, tolerance: T) -> bool {
+        T::ONE - self.normalized().dot(other.normalized()).abs() <= tolerance
+    }
+}
+
+
+// the Hamilton product, which combines two rotations:
+impl<T: Float> Mul for Quaternion<T> {
+    type Output = Self;
+
+    fn mul(self, o: Self) -> Self {
+        Self::new(
+            self.w * o.w - self.x * o.x - self.y * o.y - self.z * o.z,
+            self.w * o.x + self.x * o.w + self.y * o.z - self.z * o.y,
+            self.w * o.y - self.x * o.z + self.y * o.w + self.z * o.x,
+            self.w * o.z + self.x * o.y - self.y * o.x + self.z * o.w,
+        )
+    }
+}
+
+impl<T: Float> Mul<Vec3<T>> for Quaternion<T> {
+    type Output = Vec3<T>;
+
+    fn mul(self, v: Vec3<T>) -> Vec3<T> {
+        self.rotate(v)
+    }
+}
+
+impl<T: Float> Default for Quaternion<T> {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl<T: Float> From<Quaternion<T>> for Mat4<T> {
+    fn from(q: Quaternion<T>) -> Self {
+        q.to_matrix()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Quaternion<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match f.precision() {
+            Some(p) => write!(f, "{:.p$} + {:.p$}i + {:.p$}j + {:.p$}k", self.w, self.x, self.y, self.z),
+            None    => write!(f, "{} + {}i + {}j + {}k", self.w, self.x, self.y, self.z),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Random;
+    use std::f64::consts::{PI, TAU};
+
+    fn vector(random: &mut Random) -> Vec3<f64> {
+        Vec3::new(random.small() as f64, random.small() as f64, random.small() as f64) / 10.0
+    }
+
+    // a random axis (never the zero vector) and angle:
+    fn rotation(random: &mut Random) -> (Vec3<f64>, f64) {
+        let axis = loop {
+            let v = vector(random);
+            if v.length() > 0.1 {
+                break v;
+            }
+        };
+        (axis, random.float(TAU))
+    }
+
+    #[test]
+    fn quaternions_and_matrices_rotate_the_same_way() {
+        let mut random = Random(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..1000 {
+            let (axis, angle) = rotation(&mut random);
+            let q = Quaternion::from_axis_angle(axis, angle).expect("not the zero axis");
+            let m = Mat4::rotation(axis, angle).expect("not the zero axis");
+            let v = vector(&mut random);
+
+            let rotated = q.rotate(v);
+            assert!(rotated.approx_eq(q.to_matrix() * v, 1e-9), "{axis} by {angle}");
+            assert!(rotated.approx_eq(m * v, 1e-9), "{axis} by {angle}");
+
+            // rotations don't stretch anything:
+            assert!((rotated.length() - v.length()).abs() < 1e-9);
+
+            // and turning back undoes it:
+            assert!(q.conjugate().rotate(rotated).approx_eq(v, 1e-9));
+        }
+    }
+
+    #[test]
+    fn quarter_turns_follow_the_right_hand_rule() {
+        let q = Quaternion::from_axis_angle(Vec3::Z, PI / 2.0).unwrap();
+        assert!(q.rotate(Vec3::X).approx_eq(Vec3::Y, 1e-12));
+        assert!(q.rotate(Vec3::Y).approx_eq(-Vec3::X, 1e-12));
+        assert_eq!(Quaternion::from_axis_angle(Vec3::<f64>::ZERO, 1.0), None);
+    }
+
+    #[test]
+    fn slerp_starts_and_ends_at_its_endpoints() {
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..1000 {
+            let (axis, angle) = rotation(&mut random);
+            let a = Quaternion::from_axis_angle(axis, angle).unwrap();
+            let (axis, angle) = rotation(&mut random);
+            let b = Quaternion::from_axis_angle(axis, angle).unwrap();
+
+            assert!(a.slerp(b, 0.0).approx_eq(a, 1e-9));
+            assert!(a.slerp(b, 1.0).approx_eq(b, 1e-9));
+            assert!(a.slerp(a, 0.5).approx_eq(a, 1e-9));
+
+            // halfway is the same turn from either end:
+            let middle = a.slerp(b, 0.5);
+            assert!((middle.dot(a).abs() - middle.dot(b).abs()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn between_turns_one_direction_into_the_other() {
+        let mut random = Random(0xdead_beef);
+        let mut pairs: Vec<_> = (0..1000).map(|_| (vector(&mut random), vector(&mut random))).collect();
+        pairs.retain(|(a, b)| a.length() > 0.0 && b.length() > 0.0);
+
+        // the awkward ones: the same direction, and opposite ones (there's no single axis for those):
+        let diagonal = Vec3::new(1.0, 2.0, 3.0);
+        pairs.extend([(Vec3::X, Vec3::X * 3.0), (Vec3::X, -Vec3::X), (Vec3::Y, -Vec3::Y), (diagonal, -diagonal)]);
+
+        for (a, b) in pairs {
+            let q = Quaternion::between(a, b).expect("neither is zero");
+            let turned = q.rotate(a.normalized().unwrap());
+            assert!(turned.approx_eq(b.normalized().unwrap(), 1e-9), "{a} onto {b}: {turned}");
+        }
+
+        assert_eq!(Quaternion::between(Vec3::<f64>::ZERO, Vec3::X), None);
+    }
+}
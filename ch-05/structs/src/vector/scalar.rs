@@ -0,0 +1,95 @@
+// The number types a Vec3 can be made of.
+// Scalar is the arithmetic every number type has (i32, i64, f32, f64),
+// and Float adds what only decimals have: square roots and trigonometry.
+
+use std::fmt::{Debug, Display};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+
+pub trait Scalar:
+    Copy
+    + PartialEq
+    + PartialOrd
+    + Debug
+    + Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+pub trait Float: Scalar {
+    const EPSILON: Self;
+    const PI: Self;
+
+    fn from_f64(n: f64) -> Self;
+    fn sqrt(self) -> Self;
+    fn sin_cos(self) -> (Self, Self);
+    fn acos(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+}
+
+
+macro_rules! scalar {
+    ($t:ty, $zero:expr, $one:expr) => {
+        impl Scalar for $t {
+            const ZERO: Self = $zero;
+            const ONE: Self = $one;
+
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        }
+    };
+}
+
+scalar!(i32, 0, 1);
+scalar!(i64, 0, 1);
+scalar!(f32, 0.0, 1.0);
+scalar!(f64, 0.0, 1.0);
+
+
+macro_rules! float {
+    ($t:ident) => {
+        impl Float for $t {
+            // a bit looser than the machine epsilon, so rounding errors from a few operations still count as equal:
+            const EPSILON: Self = $t::EPSILON * 64.0;
+            const PI: Self = std::$t::consts::PI;
+
+            fn from_f64(n: f64) -> Self {
+                n as $t
+            }
+
+            fn sqrt(self) -> Self {
+                $t::sqrt(self)
+            }
+
+            fn sin_cos(self) -> (Self, Self) {
+                $t::sin_cos(self)
+            }
+
+            fn acos(self) -> Self {
+                $t::acos(self.clamp(-1.0, 1.0))
+            }
+
+            fn atan2(self, other: Self) -> Self {
+                $t::atan2(self, other)
+            }
+        }
+    };
+}
+
+float!(f32);
+float!(f64);
+
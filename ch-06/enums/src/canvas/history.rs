@@ -0,0 +1,366 @@
+// Undo and redo.
+// a History owns a canvas, and remembers how to reverse every message it applies:
+//
+// * Inverses:
+// - Move:         the pen's previous position (and whether the move drew a stroke to remove).
+// - ChangeColor:  the previous colour.
+// - Write:        remove the label again (the message itself keeps the text, for redoing).
+// - Quit:         back to drawing.
+//
+// * history branches instead of being thrown away:
+// undoing a few steps and then applying something new starts a new branch, and the old one is
+// still there. redo() follows the branch that was used last, redo_branch() picks another one.
+// ? apply A, apply B, undo, apply C    =>  base - A - B
+// ?                                                 \ C     (redo from A goes to C, B is branch 0)
+//
+// * the size limit is how many steps are remembered, counting every branch.
+// when it's reached, the oldest step that isn't on the way to the current state goes first
+// (an abandoned branch, or the far end of what could be redone). if there isn't one,
+// the oldest step on the current path is forgotten, so undo can't go back that far anymore.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{Canvas, CanvasError, Rgb, State};
+use crate::Message;
+
+
+pub const DEFAULT_LIMIT: usize = 1000;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Inverse {
+    Quit,
+    Move { from: (i32, i32), drew: bool },
+    ChangeColor(Rgb),
+    Write,
+}
+
+#[derive(Debug, Clone)]
+struct Step {
+    message:  Message,
+    inverse:  Inverse,
+    parent:   Option<usize>,  // None is the state the history started from
+    children: Vec<usize>,     // oldest first
+    redo:     Option<usize>,  // the child redo() goes to
+}
+
+
+#[derive(Debug, Clone)]
+pub struct History {
+    canvas:    Canvas,
+    steps:     BTreeMap<usize, Step>,  // ids only go up, so the first one is the oldest
+    leaves:    BTreeSet<usize>,        // the steps with no children, for the size limit
+    roots:     Vec<usize>,             // the steps applied right at the start, oldest first
+    next_id:   usize,
+    current:   Option<usize>,          // the last step applied (None if we're at the start)
+    base_redo: Option<usize>,          // what redo() goes to from the start
+    limit:     usize,
+}
+
+impl History {
+    pub fn new(canvas: Canvas) -> Self {
+        Self::with_limit(canvas, DEFAULT_LIMIT)
+    }
+
+    pub fn with_limit(canvas: Canvas, limit: usize) -> Self {
+        Self {
+            canvas,
+            steps: BTreeMap::new(),
+            leaves: BTreeSet::new(),
+            roots: Vec::new(),
+            next_id: 0,
+            current: None,
+            base_redo: None,
+            limit: limit.max(1),
+        }
+    }
+
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    pub fn into_canvas(self) -> Canvas {
+        self.canvas
+    }
+
+    // how many steps are remembered, on every branch:
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.branches().is_empty()
+    }
+
+    // * applies a message and remembers it. a message the canvas refuses isn't remembered.
+    pub fn apply(&mut self, message: Message) -> Result<(), CanvasError> {
+        let inverse = self.apply_to_canvas(&message)?;
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.steps.insert(id, Step { message, inverse, parent: self.current, children: Vec::new(), redo: None });
+        self.children_mut(self.current).push(id);
+        self.leaves.insert(id);
+        if let Some(parent) = self.current {
+            self.leaves.remove(&parent);
+        }
+        *self.redo_mut(self.current) = Some(id);
+        self.current = Some(id);
+
+        self.enforce_limit();
+        Ok(())
+    }
+
+    // undoes the last step, and returns the message that was undone:
+    pub fn undo(&mut self) -> Option<&Message> {
+        let id = self.current?;
+        let step = &self.steps[&id];
+
+        match step.inverse {
+            Inverse::Quit => self.canvas.state = State::Drawing,
+            Inverse::Move { from, drew } => {
+                self.canvas.pen = from;
+                if drew {
+                    self.canvas.strokes.pop();
+                }
+            },
+            Inverse::ChangeColor(color) => self.canvas.color = color,
+            Inverse::Write => {
+                self.canvas.labels.pop();
+            },
+        }
+
+        let parent = step.parent;
+        *self.redo_mut(parent) = Some(id);
+        self.current = parent;
+
+        Some(&self.steps[&id].message)
+    }
+
+    // redoes the step that was undone last (or the newest branch), and returns its message:
+    pub fn redo(&mut self) -> Option<&Message> {
+        let redo = match self.current {
+            Some(id) => self.steps[&id].redo,
+            None     => self.base_redo,
+        };
+
+        // the remembered branch could have been dropped by the size limit:
+        let id = redo.filter(|id| self.steps.contains_key(id)).or_else(|| self.branches().last().copied())?;
+        self.redo_step(id)
+    }
+
+    // the steps that could be redone from here, oldest first:
+    pub fn branches(&self) -> Vec<usize> {
+        match self.current {
+            Some(id) => self.steps[&id].children.clone(),
+            None     => self.roots.clone(),
+        }
+    }
+
+    pub fn branch_messages(&self) -> Vec<&Message> {
+        self.branches().into_iter().map(|id| &self.steps[&id].message).collect()
+    }
+
+    // redoes the nth branch from branches():
+    pub fn redo_branch(&mut self, n: usize) -> Option<&Message> {
+        let id = *self.branches().get(n)?;
+        self.redo_step(id)
+    }
+
+
+    fn redo_step(&mut self, id: usize) -> Option<&Message> {
+        let message = self.steps[&id].message.clone();
+
+        // the canvas is back exactly where it was when this step was first applied, so it can't fail:
+        let inverse = self.apply_to_canvas(&message).expect("redoing a step that worked before");
+        self.steps.get_mut(&id).expect("a branch of the current step").inverse = inverse;
+
+        *self.redo_mut(self.current) = Some(id);
+        self.current = Some(id);
+
+        Some(&self.steps[&id].message)
+    }
+
+    fn apply_to_canvas(&mut self, message: &Message) -> Result<Inverse, CanvasError> {
+        let before = (self.canvas.pen, self.canvas.color, self.canvas.strokes.len());
+        self.canvas.apply(message)?;
+
+        Ok(match message {
+            Message::Quit                 => Inverse::Quit,
+            Message::Move { .. }          => Inverse::Move { from: before.0, drew: self.canvas.strokes.len() > before.2 },
+            Message::ChangeColor(_, _, _) => Inverse::ChangeColor(before.1),
+            Message::Write(_)             => Inverse::Write,
+        })
+    }
+
+    fn redo_mut(&mut self, id: Option<usize>) -> &mut Option<usize> {
+        match id {
+            Some(id) => &mut self.steps.get_mut(&id).expect("steps on the current path aren't dropped").redo,
+            None     => &mut self.base_redo,
+        }
+    }
+
+    fn children_mut(&mut self, id: Option<usize>) -> &mut Vec<usize> {
+        match id {
+            Some(id) => &mut self.steps.get_mut(&id).expect("a remembered step's parent is remembered too").children,
+            None     => &mut self.roots,
+        }
+    }
+
+    fn enforce_limit(&mut self) {
+        while self.steps.len() > self.limit {
+            // the oldest step nothing else hangs off, that isn't the current one
+            // (which is the only leaf on the path undo() needs, every other step on it has the next one hanging off it):
+            match self.leaves.iter().find(|&&id| Some(id) != self.current).copied() {
+                Some(id) => {
+                    let parent = self.steps.remove(&id).expect("leaves are steps").parent;
+                    self.leaves.remove(&id);
+
+                    let siblings = self.children_mut(parent);
+                    siblings.retain(|&child| child != id);
+                    if let (true, Some(parent)) = (siblings.is_empty(), parent) {
+                        self.leaves.insert(parent);
+                    }
+                },
+                // with only one leaf, the steps are a single line up to the current one,
+                // so the oldest step (the first on it) becomes the new start:
+                None => {
+                    let (oldest, step) = self.steps.pop_first().expect("over the limit, so there's at least one step");
+                    self.roots.retain(|&root| root != oldest);
+
+                    for &child in &step.children {
+                        self.steps.get_mut(&child).expect("children are steps").parent = None;
+                    }
+                    self.roots.extend(&step.children);
+                    self.roots.sort_unstable();
+                    self.base_redo = step.redo;
+                },
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Random;
+
+    fn message(random: &mut Random) -> Message {
+        match random.below(20) {
+            0       => Message::Quit,
+            1..=9   => Message::Move { x: random.below(11) as i32 - 5, y: random.below(11) as i32 - 5 },
+            10..=14 => Message::ChangeColor(random.below(300) as i32, 0, 255),  // sometimes out of range
+            _       => Message::Write(format!("label {}", random.below(100))),
+        }
+    }
+
+    fn replay(path: &[Message]) -> Canvas {
+        let mut canvas = Canvas::new();
+        canvas.apply_all(path).expect("only messages that worked are on the path");
+        canvas
+    }
+
+    // * random applies, undos and redos, checking the canvas against replaying every message
+    // on the current path from scratch after each one:
+    fn check(seed: u64, limit: usize, operations: usize) {
+        let mut random = Random(seed);
+        let mut history = History::with_limit(Canvas::new(), limit);
+        let mut path: Vec<Message> = Vec::new();  // everything from the very start, forgotten or not
+
+        for _ in 0..operations {
+            match random.below(10) {
+                0..=4 => {
+                    let message = message(&mut random);
+                    let mut canvas = replay(&path);
+                    let expected = canvas.apply(&message);
+
+                    assert_eq!(history.apply(message.clone()), expected);
+                    if expected.is_ok() {
+                        path.push(message);
+                    }
+                },
+                5..=7 => match history.undo().cloned() {
+                    Some(message) => assert_eq!(path.pop(), Some(message)),
+                    // only at the start, or where the limit made it forget the rest:
+                    None => assert!(path.is_empty() || history.len() == limit),
+                },
+                8 => {
+                    let can_redo = history.can_redo();
+                    match history.redo().cloned() {
+                        Some(message) => path.push(message),
+                        None          => assert!(!can_redo),
+                    }
+                },
+                _ => {
+                    let branches = history.branch_messages().into_iter().cloned().collect::<Vec<_>>();
+                    let n = random.below(3);
+
+                    match history.redo_branch(n).cloned() {
+                        Some(message) => {
+                            assert_eq!(branches.get(n), Some(&message));
+                            path.push(message);
+                        },
+                        None => assert!(n >= branches.len()),
+                    }
+                },
+            }
+
+            assert_eq!(history.canvas(), &replay(&path));
+            assert!(history.len() <= limit);
+            assert!(!path.is_empty() || !history.can_undo());
+        }
+    }
+
+    #[test]
+    fn random_operations_match_a_replay() {
+        for seed in 1..=20u64 {
+            check(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15), DEFAULT_LIMIT, 2000);
+        }
+    }
+
+    #[test]
+    fn random_operations_match_a_replay_at_the_limit() {
+        for (seed, limit) in (1..=20u64).zip([1, 2, 3, 5, 8, 13, 21].into_iter().cycle()) {
+            check(seed.wrapping_mul(0x2545_f491_4f6c_dd1d), limit, 2000);
+        }
+    }
+
+    #[test]
+    fn the_limit_forgets_abandoned_branches_first() {
+        let mut history = History::with_limit(Canvas::new(), 3);
+        let step = |x| Message::Move { x, y: 0 };
+
+        history.apply(step(1)).unwrap();
+        history.apply(step(2)).unwrap();
+        history.undo();
+        history.apply(step(3)).unwrap();  // 2 is an abandoned branch now
+        history.apply(step(4)).unwrap();
+
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.undo(), Some(&step(4)));
+        assert_eq!(history.branch_messages(), [&step(4)]);
+        assert_eq!(history.undo(), Some(&step(3)));
+        assert_eq!(history.branch_messages(), [&step(3)]);  // 2 was forgotten
+        assert_eq!(history.undo(), Some(&step(1)));
+
+        // and then the oldest on the path, once there are no branches left:
+        history.redo();
+        history.redo();
+        history.redo();
+        history.apply(step(5)).unwrap();
+        assert_eq!(history.len(), 3);
+        assert!(history.undo().is_some() && history.undo().is_some() && history.undo().is_some());
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.canvas().pen(), (1, 0));
+    }
+}
@@ -0,0 +1,202 @@
+// Turtle canvas.
+// the drawing protocol Message always hinted at: a pen that moves around a grid, leaving lines behind it.
+//
+// * What each message does:
+// - Move { x, y }:         moves the pen by (x, y) from where it is, drawing a line on the way.
+// - ChangeColor(r, g, b):  the colour of everything drawn from now on (each 0 to 255).
+// - Write(text):           puts a text label at the pen (the pen doesn't move).
+// - Quit:                  finishes the drawing, nothing can be applied after it.
+//
+// * coordinates are grid cells, starting at (0, 0), with y going *down* (like on a screen and in SVG).
+// ? MOVE 4 0 / MOVE 0 2 / MOVE -4 0   => three sides of a rectangle, ending at (0, 2)
+//
+// the finished drawing renders as ASCII art, an SVG, or a PPM image, see render.rs.
+// to be able to undo messages, apply them through a History (history.rs).
+
+pub mod history;
+pub mod render;
+
+use std::fmt;
+
+use crate::Message;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stroke {
+    pub from:  (i32, i32),
+    pub to:    (i32, i32),
+    pub color: Rgb,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub at:    (i32, i32),
+    pub text:  String,
+    pub color: Rgb,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Drawing,
+    Finished,  // after a Quit
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CanvasError {
+    Finished(Message),                                // a message after Quit
+    ColorOutOfRange(i32, i32, i32),
+    OffTheGrid { from: (i32, i32), by: (i32, i32) },  // the pen would go past i32::MAX
+    TooLarge { width: u64, height: u64 },             // too big an image to render
+}
+
+impl fmt::Display for CanvasError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CanvasError::Finished(message)          => write!(f, "the drawing is finished (it quit), so {message:?} can't be applied"),
+            CanvasError::ColorOutOfRange(r, g, b)   => write!(f, "colour ({r}, {g}, {b}) is out of range, each part has to be from 0 to 255"),
+            CanvasError::OffTheGrid { from, by }    => write!(f, "moving by {by:?} from {from:?} goes off the edge of the grid"),
+            CanvasError::TooLarge { width, height } => write!(f, "a {width} x {height} image is too large to render (the limit is {} on each side)", render::MAX_SIDE),
+        }
+    }
+}
+
+impl std::error::Error for CanvasError {}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    pen:     (i32, i32),
+    color:   Rgb,
+    strokes: Vec<Stroke>,
+    labels:  Vec<Label>,
+    state:   State,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self { pen: (0, 0), color: Rgb::BLACK, strokes: Vec::new(), labels: Vec::new(), state: State::Drawing }
+    }
+}
+
+impl Canvas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pen(&self) -> (i32, i32) {
+        self.pen
+    }
+
+    pub fn color(&self) -> Rgb {
+        self.color
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn strokes(&self) -> &[Stroke] {
+        &self.strokes
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    // * a message that can't be applied leaves the canvas exactly as it was.
+    pub fn apply(&mut self, message: &Message) -> Result<(), CanvasError> {
+        if self.state == State::Finished {
+            return Err(CanvasError::Finished(message.clone()));
+        }
+
+        match *message {
+            Message::Quit => self.state = State::Finished,
+            Message::Move { x, y } => {
+                let (px, py) = self.pen;
+                let to = px.checked_add(x).zip(py.checked_add(y))
+                    .ok_or(CanvasError::OffTheGrid { from: self.pen, by: (x, y) })?;
+
+                // moving nowhere doesn't leave a mark:
+                if to != self.pen {
+                    self.strokes.push(Stroke { from: self.pen, to, color: self.color });
+                }
+                self.pen = to;
+            },
+            Message::ChangeColor(r, g, b) => {
+                let part = |n: i32| u8::try_from(n).map_err(|_| CanvasError::ColorOutOfRange(r, g, b));
+                self.color = Rgb(part(r)?, part(g)?, part(b)?);
+            },
+            Message::Write(ref text) => {
+                self.labels.push(Label { at: self.pen, text: text.clone(), color: self.color });
+            },
+        }
+
+        Ok(())
+    }
+
+    // applies messages in order, stopping at the first one that fails (and saying which it was):
+    pub fn apply_all<'a>(&mut self, messages: impl IntoIterator<Item = &'a Message>) -> Result<(), (usize, CanvasError)> {
+        for (i, message) in messages.into_iter().enumerate() {
+            self.apply(message).map_err(|e| (i, e))?;
+        }
+        Ok(())
+    }
+
+    // the smallest box around everything drawn and the starting point, as (min, max) corners:
+    pub fn bounds(&self) -> ((i32, i32), (i32, i32)) {
+        let points = self.strokes.iter().flat_map(|s| [s.from, s.to])
+            .chain(self.labels.iter().map(|l| l.at))
+            .chain([(0, 0), self.pen]);
+
+        points.fold(((i32::MAX, i32::MAX), (i32::MIN, i32::MIN)), |((x0, y0), (x1, y1)), (x, y)| {
+            ((x0.min(x), y0.min(y)), (x1.max(x), y1.max(y)))
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_applies_after_quit() {
+        let mut canvas = Canvas::new();
+        canvas.apply(&Message::Move { x: 2, y: 0 }).unwrap();
+        canvas.apply(&Message::Quit).unwrap();
+        let finished = canvas.clone();
+
+        let late = Message::Move { x: 1, y: 1 };
+        assert_eq!(canvas.apply(&late), Err(CanvasError::Finished(late.clone())));
+        assert_eq!(canvas.apply(&Message::Quit), Err(CanvasError::Finished(Message::Quit)));
+        assert_eq!(canvas, finished);
+        assert_eq!(canvas.state(), State::Finished);
+    }
+
+    #[test]
+    fn apply_all_says_which_message_failed() {
+        let mut canvas = Canvas::new();
+        let messages = [Message::Move { x: 1, y: 0 }, Message::Quit, Message::Move { x: 0, y: 1 }, Message::Quit];
+
+        assert_eq!(canvas.apply_all(&messages), Err((2, CanvasError::Finished(Message::Move { x: 0, y: 1 }))));
+        assert_eq!(canvas.pen(), (1, 0));
+        assert_eq!(canvas.strokes().len(), 1);
+    }
+}
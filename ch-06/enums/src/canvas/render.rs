@@ -0,0 +1,227 @@
+// Rendering a canvas.
+// - ASCII:  one character per grid cell, lines drawn with - | / \ (and + where they cross).
+// - SVG:    lines and text, scaled up so it's readable in a browser.
+// - PPM:    a plain image (the simplest format there is: a header, then the pixels' bytes).
+//           there's no font, so labels are a small square in their colour instead of text.
+//
+// * everything drawn fits, with a margin around it, whatever the coordinates are.
+// only the part of the grid that was used is rendered, so a drawing at (1000, 1000) isn't mostly blank.
+
+use std::fmt::Write as _;
+
+use super::{Canvas, CanvasError, Rgb};
+
+
+// PPM images bigger than this on either side are refused, instead of using gigabytes of memory:
+pub const MAX_SIDE: u64 = 4096;
+
+// how many SVG units one grid cell is:
+const SVG_CELL: i64 = 10;
+
+
+impl Canvas {
+    // * drawings wider or taller than `max_columns` are scaled down to fit (labels aren't, they're text).
+    pub fn to_ascii(&self, max_columns: usize) -> String {
+        let ((x0, y0), (x1, y1)) = self.bounds();
+        let (width, height) = (span(x0, x1), span(y0, y1));
+        let shrink = ((width.max(height) as u64).div_ceil(max_columns.max(1) as u64) as i64).max(1);
+
+        let cell = |(x, y): (i32, i32)| ((x as i64 - x0 as i64) / shrink, (y as i64 - y0 as i64) / shrink);
+
+        let columns = (width - 1) / shrink + 1;
+        let rows = (height - 1) / shrink + 1;
+        // room for labels sticking out on the right:
+        let columns = self.labels.iter()
+            .map(|l| cell(l.at).0 + l.text.chars().count() as i64)
+            .fold(columns, i64::max);
+
+        let mut grid = vec![vec!['.'; columns as usize]; rows as usize];
+
+        for stroke in &self.strokes {
+            let (dx, dy) = (stroke.to.0 as i64 - stroke.from.0 as i64, stroke.to.1 as i64 - stroke.from.1 as i64);
+            let c = match (dx, dy) {
+                (_, 0) => '-',
+                (0, _) => '|',
+                _ if (dx > 0) == (dy > 0) => '\\',  // y goes down, so down-right is a backslash
+                _ => '/',
+            };
+
+            for (x, y) in line(cell(stroke.from), cell(stroke.to)) {
+                let spot = &mut grid[y as usize][x as usize];
+                *spot = match *spot {
+                    '.'             => c,
+                    old if old == c => c,
+                    _               => '+',
+                };
+            }
+        }
+
+        // labels go on top of the lines, a character per cell (newlines and tabs would break the grid):
+        for label in &self.labels {
+            let (x, y) = cell(label.at);
+            let row = &mut grid[y as usize];
+
+            for (i, c) in label.text.chars().enumerate() {
+                row[x as usize + i] = if c.is_control() { ' ' } else { c };
+            }
+        }
+
+        grid.into_iter().map(|row| row.into_iter().collect::<String>() + "\n").collect()
+    }
+
+    pub fn to_svg(&self) -> String {
+        let ((x0, y0), (x1, y1)) = self.bounds();
+        let scaled = |(x, y): (i32, i32)| (x as i64 * SVG_CELL, y as i64 * SVG_CELL);
+
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+            (x0 as i64 - 1) * SVG_CELL,
+            (y0 as i64 - 1) * SVG_CELL,
+            (span(x0, x1) + 1) * SVG_CELL,
+            (span(y0, y1) + 1) * SVG_CELL,
+        );
+        svg.push('\n');
+        svg.push_str(r#"<rect x="-100%" y="-100%" width="300%" height="300%" fill="white"/>"#);
+        svg.push('\n');
+        svg.push_str(r#"<g stroke-width="2" stroke-linecap="round">"#);
+        svg.push('\n');
+
+        for stroke in &self.strokes {
+            let ((ax, ay), (bx, by)) = (scaled(stroke.from), scaled(stroke.to));
+            let _ = writeln!(svg, r#"  <line x1="{ax}" y1="{ay}" x2="{bx}" y2="{by}" stroke="{}"/>"#, stroke.color);
+        }
+
+        svg.push_str("</g>\n");
+        svg.push_str(r#"<g font-family="monospace" font-size="12">"#);
+        svg.push('\n');
+
+        for label in &self.labels {
+            let (x, y) = scaled(label.at);
+            let _ = writeln!(svg, r#"  <text x="{}" y="{}" fill="{}">{}</text>"#, x + 3, y - 3, label.color, escape(&label.text));
+        }
+
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+
+    // * `scale` is how many pixels wide a grid cell is. there's a cell of margin on every side.
+    pub fn to_ppm(&self, scale: u32) -> Result<Vec<u8>, CanvasError> {
+        let ((x0, y0), (x1, y1)) = self.bounds();
+        let scale = scale.max(1) as i64;
+        let (width, height) = ((span(x0, x1) + 2).saturating_mul(scale), (span(y0, y1) + 2).saturating_mul(scale));
+
+        if width as u64 > MAX_SIDE || height as u64 > MAX_SIDE {
+            return Err(CanvasError::TooLarge { width: width as u64, height: height as u64 });
+        }
+
+        let pixel = |(x, y): (i32, i32)| {
+            ((x as i64 - x0 as i64 + 1) * scale + scale / 2, (y as i64 - y0 as i64 + 1) * scale + scale / 2)
+        };
+        let mut pixels = vec![Rgb::WHITE; (width * height) as usize];
+        let mut paint = |x: i64, y: i64, color: Rgb| {
+            if (0..width).contains(&x) && (0..height).contains(&y) {
+                pixels[(y * width + x) as usize] = color;
+            }
+        };
+
+        for stroke in &self.strokes {
+            for (x, y) in line(pixel(stroke.from), pixel(stroke.to)) {
+                paint(x, y, stroke.color);
+            }
+        }
+
+        for label in &self.labels {
+            let (x, y) = pixel(label.at);
+            for (dx, dy) in [-1, 0, 1].into_iter().flat_map(|dx| [(dx, -1), (dx, 0), (dx, 1)]) {
+                paint(x + dx, y + dy, label.color);
+            }
+        }
+
+        let mut ppm = format!("P6\n{width} {height}\n255\n").into_bytes();
+        ppm.extend(pixels.iter().flat_map(|&Rgb(r, g, b)| [r, g, b]));
+        Ok(ppm)
+    }
+}
+
+
+// how many cells from a to b, counting both ends:
+fn span(a: i32, b: i32) -> i64 {
+    b as i64 - a as i64 + 1
+}
+
+// * Bresenham's line algorithm: every cell on the way from one point to another, ends included.
+// it steps along the longer axis one cell at a time, and keeps track of how far off the true line
+// the other axis has drifted, moving it a cell whenever the error gets past half a cell.
+fn line((mut x, mut y): (i64, i64), (x1, y1): (i64, i64)) -> Vec<(i64, i64)> {
+    let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+    let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+    let mut error = dx + dy;
+    let mut cells = Vec::new();
+
+    loop {
+        cells.push((x, y));
+        if (x, y) == (x1, y1) {
+            return cells;
+        }
+
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wire::text;
+
+    fn draw(script: &str) -> Canvas {
+        let mut canvas = Canvas::new();
+        canvas.apply_all(&text::decode_all(script).unwrap()).unwrap();
+        canvas
+    }
+
+    // a box with a label in it, and a diagonal going up and left out of it:
+    const SCRIPT: &str = "
+        MOVE 4 0
+        MOVE 0 2
+        MOVE -4 0
+        WRITE hi
+        MOVE -2 -2
+        QUIT
+    ";
+
+    #[test]
+    fn ascii_art() {
+        assert_eq!(draw(SCRIPT).to_ascii(80), concat!(
+            "\\.----+\n",
+            ".\\....|\n",
+            "..hi--+\n",
+        ));
+    }
+
+    #[test]
+    fn ppm_header_and_size() {
+        let canvas = draw(SCRIPT);
+        let ppm = canvas.to_ppm(3).unwrap();
+
+        // 7 x 3 cells, plus a cell of margin all round, at 3 pixels a cell:
+        let header = b"P6\n27 15\n255\n";
+        assert!(ppm.starts_with(header));
+        assert_eq!(ppm.len(), header.len() + 27 * 15 * 3);
+        assert_eq!(ppm[header.len()..header.len() + 3], [255, 255, 255]);  // the margin is white
+
+        assert_eq!(canvas.to_ppm(1000), Err(CanvasError::TooLarge { width: 9000, height: 5000 }));
+    }
+}
@@ -0,0 +1,163 @@
+// Command line interface for the turtle canvas.
+// ? cargo run -- draw <script> [--binary] [--columns <n>] [--svg <out.svg>] [--ppm <out.ppm>] [--scale <n>]
+// ?                            [--config <file>] [--explain]
+//
+// * the script is messages in the text wire format (see wire/text.rs), one per line:
+// ? # a square, with a label in the middle
+// ? COLOR 200 0 0
+// ? MOVE 10 0
+// ? MOVE 0 10
+// ? ...
+//
+// a script of "-" reads from stdin, and --binary reads binary frames instead of text.
+// it's read as it arrives, and stops at the first QUIT (anything after it is never read).
+// the drawing is printed as ASCII art, and also saved as an SVG and/or a PPM image if asked.
+//
+// * every option can also be set with a DRAW_<OPTION> environment variable, or in a config file
+// (--config, DRAW_CONFIG, or draw.conf if there is one) with `option = value` lines.
+// flags beat variables, which beat the file. --explain prints where each setting came from.
+
+use std::env;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+use std::process;
+
+use enums::canvas::{Canvas, State};
+use enums::config::{ConfigError, Resolved, Resolver};
+use enums::wire::{self, StreamDecoder, binary, text};
+
+
+const USAGE: &str = "usage: draw <script|-> [--binary] [--columns <n>] [--svg <out.svg>] [--ppm <out.ppm>] [--scale <n>] \
+                     [--config <file>] [--explain]";
+const DEFAULT_CONFIG: &str = "draw.conf";
+const DEFAULT_COLUMNS: usize = 60;
+const DEFAULT_SCALE: u32 = 8;
+
+const KEYS: &[&str] = &["columns", "svg", "ppm", "scale", "config"];
+const SWITCHES: &[&str] = &["binary", "explain"];
+
+
+pub fn run(args: &[String]) {
+    if let Err(e) = execute(args) {
+        eprintln!("Error: {e}");
+        process::exit(1);
+    }
+}
+
+
+struct Settings {
+    binary:  Resolved<bool>,
+    columns: Resolved<usize>,
+    svg:     Option<Resolved<String>>,
+    ppm:     Option<(Resolved<String>, Resolved<u32>)>,
+    explain: bool,
+}
+
+fn settings(args: &[String]) -> Result<(Settings, Vec<String>), ConfigError> {
+    // env::vars() panics on a variable that isn't Unicode, even one that has nothing to do with us,
+    // so those are skipped instead (none of ours can be one, the keys are all ASCII):
+    let env_vars = env::vars_os().filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)));
+    let (resolver, rest) = Resolver::new("DRAW_").with_env_vars(env_vars).with_cli_args(args, KEYS, SWITCHES)?;
+
+    // an explicit config file has to exist, the default one is only used if it's there:
+    let config = resolver.lookup("config").map(|(path, _)| path.to_string())
+        .or_else(|| Some(DEFAULT_CONFIG.to_string()).filter(|path| Path::new(path).exists()));
+
+    let resolver = match config {
+        Some(path) => resolver.with_file(&path)?,
+        None       => resolver,
+    };
+
+    let settings = Settings {
+        binary:  resolver.get("binary", false)?,
+        columns: resolver.get_where("columns", DEFAULT_COLUMNS, |&n| n > 0)?,
+        svg:     resolver.optional("svg")?,
+        // a scale on its own doesn't make an image, so it's only looked at (and checked) when there's a path.
+        // map gives an Option<Result<...>>, and transpose turns it into a Result<Option<...>> for the ?:
+        ppm:     resolver.optional("ppm")?
+            .map(|ppm| Ok((ppm, resolver.get_where("scale", DEFAULT_SCALE, |&n| n > 0)?)))
+            .transpose()?,
+        explain: resolver.get("explain", false)?.value,
+    };
+
+    Ok((settings, rest))
+}
+
+fn explain(settings: &Settings) {
+    let line = |key: &str, value: &dyn fmt::Display, source: &dyn fmt::Display| println!("{key:<8} = {value:<12} ({source})");
+
+    line("binary", &settings.binary.value, &settings.binary.source);
+    line("columns", &settings.columns.value, &settings.columns.source);
+    match &settings.svg {
+        Some(svg) => line("svg", &svg.value, &svg.source),
+        None      => line("svg", &"-", &"not set"),
+    }
+    match &settings.ppm {
+        Some((ppm, scale)) => {
+            line("ppm", &ppm.value, &ppm.source);
+            line("scale", &scale.value, &scale.source);
+        },
+        None => line("ppm", &"-", &"not set"),
+    }
+    println!();
+}
+
+
+fn execute(args: &[String]) -> Result<(), String> {
+    let (settings, rest) = settings(args).map_err(|e| format!("{e}\n{USAGE}"))?;
+
+    let [script] = &rest[..] else {
+        return Err(USAGE.to_string());
+    };
+
+    if settings.explain {
+        explain(&settings);
+    }
+
+    let reader: Box<dyn Read> = match script.as_str() {
+        "-"  => Box::new(io::stdin()),
+        path => Box::new(File::open(path).map_err(|e| format!("couldn't read {path}: {e}"))?),
+    };
+
+    let mut canvas = Canvas::new();
+    match settings.binary.value {
+        true  => draw(reader, binary::Decoder::new(), &mut canvas)?,
+        false => draw(reader, text::Decoder::new(), &mut canvas)?,
+    }
+
+    print!("{}", canvas.to_ascii(settings.columns.value));
+    println!();
+    println!("Strokes: {}, labels: {}, pen at {:?}", canvas.strokes().len(), canvas.labels().len(), canvas.pen());
+
+    if let Some(Resolved { value: path, .. }) = &settings.svg {
+        fs::write(path, canvas.to_svg()).map_err(|e| format!("couldn't write {path}: {e}"))?;
+        println!("Saved {path}");
+    }
+    if let Some((Resolved { value: path, .. }, scale)) = &settings.ppm {
+        let image = canvas.to_ppm(scale.value).map_err(|e| e.to_string())?;
+        fs::write(path, image).map_err(|e| format!("couldn't write {path}: {e}"))?;
+        println!("Saved {path}");
+    }
+
+    Ok(())
+}
+
+
+fn draw<D>(reader: impl Read, decoder: D, canvas: &mut Canvas) -> Result<(), String>
+where
+    D: StreamDecoder,
+    D::Error: fmt::Display,
+{
+    for (i, message) in wire::messages(reader, decoder).enumerate() {
+        let message = message.map_err(|e| e.to_string())?;
+        canvas.apply(&message).map_err(|e| format!("message {}: {e}", i + 1))?;
+
+        if canvas.state() == State::Finished {
+            break;
+        }
+    }
+
+    Ok(())
+}
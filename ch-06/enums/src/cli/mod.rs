@@ -0,0 +1,4 @@
+// Command line tools, run with `cargo run -- <tool> ...`:
+// - draw: run a script of messages on a canvas, see draw.rs.
+
+pub mod draw;
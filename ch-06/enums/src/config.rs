@@ -0,0 +1,302 @@
+// Layered configuration, with Option combinators.
+// every setting can come from four places, and the first one that has it wins:
+//
+// 1) a command line flag        --columns 80
+// 2) an environment variable    DRAW_COLUMNS=80
+// 3) the config file            columns = 80
+// 4) the default                (whatever the program passes in)
+//
+// * each of those is an Option: the flag might not be given, the variable might not be set...
+// so resolving a setting is mostly chaining Options together, and the answer says where it came from:
+// ? columns = 80   (from DRAW_COLUMNS)
+//
+// * The Option combinators used along the way:
+// - or(other):          self if it's Some, otherwise `other` (which is worked out beforehand).
+// - or_else(|| ...):    same, but the closure only runs if it's needed (lazy).
+// - and_then(|x| ...):  for a step that might find nothing: Some(x) becomes whatever the closure returns.
+// - filter(|x| ...):    Some(x) becomes None if it doesn't pass the check.
+// - transpose():        Option<Result<T, E>> to Result<Option<T>, E>, so ? can be used on it.
+// and let..else, for "get the value out, or leave right now".
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File { path: String, line: usize },
+    Env(String),  // the variable's name
+    Cli(String),  // the flag
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Default             => write!(f, "default"),
+            Source::File { path, line } => write!(f, "from {path}, line {line}"),
+            Source::Env(name)           => write!(f, "from {name}"),
+            Source::Cli(flag)           => write!(f, "from {flag}"),
+        }
+    }
+}
+
+// a value, and where it came from:
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolved<T> {
+    pub value:  T,
+    pub source: Source,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    Io { path: String, reason: String },
+    Syntax { path: String, line: usize, reason: String },
+    Invalid { key: String, value: String, source: Source },
+    UnknownFlag(String),
+    MissingValue(String),  // a flag that needs a value, at the end of the arguments
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, reason }            => write!(f, "couldn't read {path}: {reason}"),
+            ConfigError::Syntax { path, line, reason }  => write!(f, "{path}, line {line}: {reason}"),
+            ConfigError::Invalid { key, value, source } => write!(f, "{value:?} isn't a valid {key} ({source})"),
+            ConfigError::UnknownFlag(flag)              => write!(f, "unknown flag {flag}"),
+            ConfigError::MissingValue(flag)             => write!(f, "{flag} needs a value"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+
+#[derive(Debug, Clone, Default)]
+pub struct Resolver {
+    env_prefix: String,                            // "DRAW_" makes `columns` DRAW_COLUMNS
+    env:        HashMap<String, String>,
+    cli:        HashMap<String, String>,           // by key, without the --
+    file_path:  Option<String>,
+    file:       HashMap<String, (String, usize)>,  // the value and its line number
+}
+
+impl Resolver {
+    // * nothing is read from the real environment unless it's asked for (see with_env_vars),
+    // so resolvers can be set up with exactly the layers wanted.
+    pub fn new(env_prefix: &str) -> Self {
+        Self { env_prefix: env_prefix.to_string(), ..Self::default() }
+    }
+
+    pub fn with_env_vars(mut self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.env.extend(vars);
+        self
+    }
+
+    pub fn with_cli_value(mut self, key: &str, value: &str) -> Self {
+        self.cli.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    // * parses `--key value` and `--switch` flags. `switches` don't take a value (they're "true").
+    // anything that isn't a flag is handed back, in order.
+    pub fn with_cli_args(mut self, args: &[String], keys: &[&str], switches: &[&str]) -> Result<(Self, Vec<String>), ConfigError> {
+        let mut rest = Vec::new();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            // let..else: if it isn't a flag, deal with it and skip to the next one.
+            let Some(key) = arg.strip_prefix("--") else {
+                rest.push(arg.clone());
+                continue;
+            };
+
+            let value = match key {
+                _ if switches.contains(&key) => "true".to_string(),
+                _ if keys.contains(&key)     => args.next().ok_or_else(|| ConfigError::MissingValue(arg.clone()))?.clone(),
+                _                            => return Err(ConfigError::UnknownFlag(arg.clone())),
+            };
+            self.cli.insert(key.to_string(), value);
+        }
+
+        Ok((self, rest))
+    }
+
+    // * the file is `key = value` lines, with # comments and blank lines ignored.
+    pub fn with_file_contents(mut self, path: &str, contents: &str) -> Result<Self, ConfigError> {
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                let reason = format!("expected `key = value`, found {line:?}");
+                return Err(ConfigError::Syntax { path: path.to_string(), line: i + 1, reason });
+            };
+
+            self.file.insert(key.trim().to_string(), (value.trim().to_string(), i + 1));
+        }
+
+        self.file_path = Some(path.to_string());
+        Ok(self)
+    }
+
+    pub fn with_file(self, path: &str) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(|e| ConfigError::Io { path: path.to_string(), reason: e.to_string() })?;
+        self.with_file_contents(path, &contents)
+    }
+
+    pub fn env_name(&self, key: &str) -> String {
+        format!("{}{}", self.env_prefix, key.to_ascii_uppercase().replace('-', "_"))
+    }
+
+    // * the raw text for a key, from the highest layer that has it (but not the default):
+    pub fn lookup(&self, key: &str) -> Option<(&str, Source)> {
+        let from_cli = self.cli.get(key).map(|v| (v.as_str(), Source::Cli(format!("--{key}"))));
+
+        // filter: DRAW_SVG= (set, but empty) counts as not set, so it doesn't hide the file's value.
+        let name = self.env_name(key);
+        let from_env = self.env.get(&name).map(|v| v.trim()).filter(|v| !v.is_empty()).map(|v| (v, Source::Env(name)));
+
+        // and_then: there might not be a file, and if there is, it might not have the key.
+        // (map would give an Option<Option<...>> here, and_then flattens it.)
+        let from_file = || {
+            self.file_path.as_deref().and_then(|path| {
+                self.file.get(key).map(|(value, line)| (value.as_str(), Source::File { path: path.to_string(), line: *line }))
+            })
+        };
+
+        // or: the flag and the variable are already looked up, so they're just picked between.
+        // or_else: the file is only searched if neither of them had it.
+        from_cli.or(from_env).or_else(from_file)
+    }
+
+    // * a setting that might not be set anywhere (like an output file), parsed:
+    pub fn optional<T: FromStr>(&self, key: &str) -> Result<Option<Resolved<T>>, ConfigError> {
+        // let..else: nothing to parse means there's nothing more to do.
+        let Some((text, source)) = self.lookup(key) else {
+            return Ok(None);
+        };
+
+        match text.parse() {
+            Ok(value) => Ok(Some(Resolved { value, source })),
+            Err(_)    => Err(ConfigError::Invalid { key: key.to_string(), value: text.to_string(), source }),
+        }
+    }
+
+    // a setting with a default, so it always has a value:
+    pub fn get<T: FromStr>(&self, key: &str, default: T) -> Result<Resolved<T>, ConfigError> {
+        Ok(self.optional(key)?.unwrap_or(Resolved { value: default, source: Source::Default }))
+    }
+
+    // * like get(), but a value that fails `check` is an error instead of being used.
+    // that includes the default, so the error shows the value itself rather than the text it came from:
+    // ? resolver.get_where("columns", 60, |&n| n > 0)
+    pub fn get_where<T>(&self, key: &str, default: T, check: impl Fn(&T) -> bool) -> Result<Resolved<T>, ConfigError>
+    where
+        T: FromStr + fmt::Display,
+    {
+        let resolved = self.get(key, default)?;
+        if check(&resolved.value) {
+            return Ok(resolved);
+        }
+
+        Err(ConfigError::Invalid { key: key.to_string(), value: resolved.value.to_string(), source: resolved.source })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "# settings\ncolumns = 40\n\nscale = 2  # doubled\nsvg = house.svg\n";
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn resolver(vars: &[(&str, &str)], flags: &[&str]) -> Resolver {
+        let (resolver, _) = Resolver::new("DRAW_")
+            .with_env_vars(env(vars))
+            .with_file_contents("draw.conf", FILE)
+            .unwrap()
+            .with_cli_args(&args(flags), &["columns", "scale", "svg"], &["binary"])
+            .unwrap();
+        resolver
+    }
+
+    #[test]
+    fn flags_beat_variables_beat_the_file_beats_the_default() {
+        let file = resolver(&[], &[]);
+        let env = resolver(&[("DRAW_COLUMNS", "80")], &[]);
+        let cli = resolver(&[("DRAW_COLUMNS", "80")], &["--columns", "100"]);
+
+        assert_eq!(file.get("columns", 60), Ok(Resolved { value: 40, source: Source::File { path: "draw.conf".into(), line: 2 } }));
+        assert_eq!(env.get("columns", 60), Ok(Resolved { value: 80, source: Source::Env("DRAW_COLUMNS".into()) }));
+        assert_eq!(cli.get("columns", 60), Ok(Resolved { value: 100, source: Source::Cli("--columns".into()) }));
+        assert_eq!(cli.get("rows", 25), Ok(Resolved { value: 25, source: Source::Default }));
+        assert_eq!(cli.optional::<String>("ppm"), Ok(None));
+    }
+
+    #[test]
+    fn sources_say_where_the_value_came_from() {
+        let resolver = resolver(&[("DRAW_SVG", "out.svg")], &["--binary"]);
+        let source = |key| resolver.lookup(key).map(|(_, source)| source.to_string());
+
+        assert_eq!(source("scale").as_deref(), Some("from draw.conf, line 4"));
+        assert_eq!(source("svg").as_deref(), Some("from DRAW_SVG"));
+        assert_eq!(source("binary").as_deref(), Some("from --binary"));
+        assert_eq!(source("ppm"), None);
+        assert_eq!(Source::Default.to_string(), "default");
+    }
+
+    #[test]
+    fn empty_variables_fall_through_to_the_file() {
+        let resolver = resolver(&[("DRAW_SVG", ""), ("DRAW_SCALE", "  ")], &[]);
+
+        assert_eq!(resolver.lookup("svg"), Some(("house.svg", Source::File { path: "draw.conf".into(), line: 5 })));
+        assert_eq!(resolver.get("scale", 8).map(|r| r.value), Ok(2));
+        assert_eq!(resolver.env_name("line-width"), "DRAW_LINE_WIDTH");
+    }
+
+    #[test]
+    fn command_line_flags() {
+        let parse = |flags: &[&str]| Resolver::new("DRAW_").with_cli_args(&args(flags), &["columns"], &["binary", "explain"]);
+
+        // switches don't take a value, and anything that isn't a flag is handed back in order:
+        let (resolver, rest) = parse(&["a.txt", "--binary", "--columns", "80", "b.txt"]).unwrap();
+        assert_eq!(rest, ["a.txt", "b.txt"]);
+        assert_eq!(resolver.get("binary", false).map(|r| r.value), Ok(true));
+        assert_eq!(resolver.get("explain", false).map(|r| r.value), Ok(false));
+        assert_eq!(resolver.get("columns", 60).map(|r| r.value), Ok(80));
+
+        assert_eq!(parse(&["a.txt", "--columns"]).map(|_| ()), Err(ConfigError::MissingValue("--columns".into())));
+        assert_eq!(parse(&["--rows", "3"]).map(|_| ()), Err(ConfigError::UnknownFlag("--rows".into())));
+    }
+
+    #[test]
+    fn bad_values_are_errors() {
+        let resolver = resolver(&[("DRAW_COLUMNS", "0"), ("DRAW_SCALE", "big")], &[]);
+        let positive = |&n: &usize| n > 0;
+
+        let invalid = |value: &str, source: Source| ConfigError::Invalid { key: "columns".into(), value: value.into(), source };
+        assert_eq!(resolver.get_where("columns", 60, positive), Err(invalid("0", Source::Env("DRAW_COLUMNS".into()))));
+        // a default that fails the check is reported too, with its own value:
+        assert_eq!(resolver.get_where("rows", 0, positive).unwrap_err().to_string(), "\"0\" isn't a valid rows (default)");
+
+        assert!(matches!(resolver.get::<u32>("scale", 8), Err(ConfigError::Invalid { value, .. }) if value == "big"));
+        assert_eq!(
+            Resolver::new("").with_file_contents("x.conf", "ok = 1\noops\n").map(|_| ()),
+            Err(ConfigError::Syntax { path: "x.conf".into(), line: 2, reason: "expected `key = value`, found \"oops\"".into() }),
+        );
+    }
+}
@@ -0,0 +1,234 @@
+// Message dispatcher.
+// instead of one hard-coded match, handlers register for the variants they care about:
+//
+// ? let mut dispatcher = Dispatcher::new();
+// ? dispatcher.on(Kind::Write, |message, _| println!("{message:?}"));
+// ? dispatcher.on_any(|message, outbox| { ... outbox.emit(Message::Quit); });
+// ? dispatcher.send(Message::Write(String::from("hi")))?;
+// ? dispatcher.run();
+//
+// * How messages are processed:
+// - one at a time, in the order they were sent (it's a queue).
+// - each message goes to every handler registered for it, in the order they were registered.
+// - a handler can emit follow-up messages, which join the back of the queue
+//   once every handler has seen the current message.
+//
+// * Quit shuts things down gracefully:
+// the messages queued before it are still processed (draining the queue), but nothing new gets in.
+// - from the moment a Quit is sent, send() gives every message back, so nothing can be queued behind it.
+// - follow-ups from the messages ahead of the Quit still join the queue: they're the rest of work that was
+//   sent before it. from the moment the Quit is processed, follow-ups are dropped (and counted) instead.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::message::{Kind, Message};
+
+
+// what handlers get besides the message, to send follow-ups with:
+#[derive(Debug, Default)]
+pub struct Outbox {
+    messages: Vec<Message>,
+}
+
+impl Outbox {
+    pub fn emit(&mut self, message: Message) {
+        self.messages.push(message);
+    }
+}
+
+type Handler = Box<dyn FnMut(&Message, &mut Outbox)>;
+
+
+// a Quit was sent already, so the message wasn't queued (it's handed back):
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendError(pub Message);
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "can't send {:?}, the dispatcher is quitting", self.0)
+    }
+}
+
+impl std::error::Error for SendError {}
+
+
+// what a run() did:
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Report {
+    pub processed: usize,
+    pub dropped:   usize,  // follow-ups emitted after Quit
+    pub quit:      bool,   // false if the queue just ran out
+}
+
+
+#[derive(Default)]
+pub struct Dispatcher {
+    handlers: Vec<(Option<Kind>, Handler)>,  // None handles every kind
+    queue:    VecDeque<Message>,
+    closed:   bool,  // a Quit was sent, send() doesn't take anything else
+    quitting: bool,  // the Quit was processed, follow-ups are dropped
+}
+
+impl Dispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on(&mut self, kind: Kind, handler: impl FnMut(&Message, &mut Outbox) + 'static) -> &mut Self {
+        self.handlers.push((Some(kind), Box::new(handler)));
+        self
+    }
+
+    pub fn on_any(&mut self, handler: impl FnMut(&Message, &mut Outbox) + 'static) -> &mut Self {
+        self.handlers.push((None, Box::new(handler)));
+        self
+    }
+
+    pub fn send(&mut self, message: Message) -> Result<(), SendError> {
+        if self.closed {
+            return Err(SendError(message));
+        }
+
+        self.closed = message == Message::Quit;
+        self.queue.push_back(message);
+        Ok(())
+    }
+
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn has_quit(&self) -> bool {
+        self.quitting
+    }
+
+    // * processes the next message, if there is one, and returns it.
+    // the second value is how many of its follow-ups were dropped.
+    pub fn step(&mut self) -> Option<(Message, usize)> {
+        let message = self.queue.pop_front()?;
+        let mut outbox = Outbox::default();
+
+        for (kind, handler) in &mut self.handlers {
+            if kind.is_none_or(|kind| kind == message.kind()) {
+                handler(&message, &mut outbox);
+            }
+        }
+
+        if message == Message::Quit {
+            self.quitting = true;
+        }
+
+        let dropped = match self.quitting {
+            true  => outbox.messages.len(),
+            false => {
+                self.queue.extend(outbox.messages);
+                0
+            },
+        };
+
+        Some((message, dropped))
+    }
+
+    // * processes messages until the queue is empty (after a Quit, it always ends up empty).
+    pub fn run(&mut self) -> Report {
+        let mut report = Report::default();
+
+        while let Some((_, dropped)) = self.step() {
+            report.processed += 1;
+            report.dropped += dropped;
+        }
+
+        report.quit = self.quitting;
+        report
+    }
+}
+
+impl fmt::Debug for Dispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Dispatcher")
+            .field("handlers", &self.handlers.len())
+            .field("queue", &self.queue)
+            .field("closed", &self.closed)
+            .field("quitting", &self.quitting)
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // a dispatcher whose handlers write down what they saw, in a log the test keeps too:
+    fn logged() -> (Dispatcher, Rc<RefCell<Vec<String>>>) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut dispatcher = Dispatcher::new();
+
+        let (first, second, moves) = (log.clone(), log.clone(), log.clone());
+        dispatcher
+            .on_any(move |message, _| first.borrow_mut().push(format!("any {:?}", message.kind())))
+            .on(Kind::Move, move |_, _| moves.borrow_mut().push("move".to_string()))
+            .on_any(move |message, _| second.borrow_mut().push(format!("any again {:?}", message.kind())));
+
+        (dispatcher, log)
+    }
+
+    #[test]
+    fn messages_go_in_order_to_handlers_in_order() {
+        let (mut dispatcher, log) = logged();
+        dispatcher.send(Message::Move { x: 1, y: 2 }).unwrap();
+        dispatcher.send(Message::Write(String::from("hi"))).unwrap();
+        assert_eq!(dispatcher.pending(), 2);
+
+        let report = dispatcher.run();
+
+        assert_eq!(report, Report { processed: 2, dropped: 0, quit: false });
+        assert_eq!(*log.borrow(), ["any Move", "move", "any again Move", "any Write", "any again Write"]);
+        assert_eq!(dispatcher.pending(), 0);
+    }
+
+    #[test]
+    fn follow_ups_join_the_back_of_the_queue() {
+        let (mut dispatcher, log) = logged();
+        // every Write gets a Move back, after all the handlers have seen the Write:
+        dispatcher.on(Kind::Write, |_, outbox| outbox.emit(Message::Move { x: 0, y: 0 }));
+
+        dispatcher.send(Message::Write(String::from("a"))).unwrap();
+        dispatcher.send(Message::ChangeColor(1, 2, 3)).unwrap();
+
+        assert_eq!(dispatcher.step().map(|(m, dropped)| (m.kind(), dropped)), Some((Kind::Write, 0)));
+        assert_eq!(dispatcher.pending(), 2);
+        assert_eq!(dispatcher.run().processed, 2);
+
+        // the first handler saw them in this order:
+        let log = log.borrow();
+        let seen: Vec<&str> = log.iter().filter_map(|l| l.strip_prefix("any ")).filter(|l| !l.starts_with("again")).collect();
+        assert_eq!(seen, ["Write", "ChangeColor", "Move"]);
+    }
+
+    #[test]
+    fn quit_drains_the_queue_and_keeps_new_messages_out() {
+        let mut dispatcher = Dispatcher::new();
+        // every message asks for another one, forever, unless something stops it:
+        dispatcher.on_any(|_, outbox| outbox.emit(Message::Write(String::from("more"))));
+
+        dispatcher.send(Message::Move { x: 1, y: 1 }).unwrap();
+        dispatcher.send(Message::Quit).unwrap();
+        assert!(!dispatcher.has_quit());
+
+        // nothing gets in behind the Quit, even before it's processed:
+        let late = Message::Write(String::from("late"));
+        assert_eq!(dispatcher.send(late.clone()), Err(SendError(late)));
+
+        // Move, Quit, then the Move's follow-up (sent before the Quit was processed). the follow-ups
+        // of the Quit and of that last one are dropped, so it stops:
+        let report = dispatcher.run();
+        assert_eq!(report, Report { processed: 3, dropped: 2, quit: true });
+        assert!(dispatcher.has_quit());
+        assert_eq!(dispatcher.pending(), 0);
+        assert!(dispatcher.send(Message::Quit).is_err());
+    }
+}
@@ -0,0 +1,398 @@
+// Finite state machines.
+// the states are an enum, the events are another enum, and a table says which event
+// moves the machine from which state to which:
+//
+// ? let mut light = Machine::new(Light::Red, &[
+// ?     (Light::Red,    Event::Timer, Light::Green),
+// ?     (Light::Green,  Event::Timer, Light::Yellow),
+// ?     (Light::Yellow, Event::Timer, Light::Red),
+// ? ])?;
+// ? light.fire(Event::Timer)?;   // Ok(Light::Green)
+//
+// * Rules:
+// - an event the table doesn't have for the current state is an error, and the state doesn't change.
+// - a table can't say two different things for the same state and event (that's an error from new()).
+// - hooks run on every transition, in this order: on_exit(from), on_transition, on_enter(to).
+//   a transition from a state back to itself still leaves and re-enters it.
+// - new() and reset() put the machine in its starting state without running any hooks.
+//
+// to_dot() draws the machine for Graphviz: `dot -Tsvg machine.dot > machine.svg`.
+// traffic.rs and tcp.rs are two machines built with it.
+
+pub mod tcp;
+pub mod traffic;
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Write as _};
+use std::hash::Hash;
+
+
+// one step the machine took (or is taking, for hooks):
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition<S, E> {
+    pub from:  S,
+    pub event: E,
+    pub to:    S,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FsmError<S, E> {
+    InvalidTransition { state: S, event: E },
+    Conflict { from: S, event: E, to: (S, S) },  // two rows in the table disagree
+}
+
+impl<S: Debug, E: Debug> fmt::Display for FsmError<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FsmError::InvalidTransition { state, event } => write!(f, "{event:?} can't happen in the {state:?} state"),
+            FsmError::Conflict { from, event, to }       => {
+                write!(f, "the table has {event:?} in {from:?} going to both {:?} and {:?}", to.0, to.1)
+            },
+        }
+    }
+}
+
+impl<S: Debug, E: Debug> std::error::Error for FsmError<S, E> {}
+
+
+type Hook<S, E> = Box<dyn FnMut(&Transition<S, E>)>;
+
+pub struct Machine<S, E> {
+    table:    Vec<Transition<S, E>>,  // in the order it was given, for to_dot()
+    next:     HashMap<(S, E), S>,
+    initial:  S,
+    state:    S,
+    on_exit:  Vec<(S, Hook<S, E>)>,
+    on_enter: Vec<(S, Hook<S, E>)>,
+    on_any:   Vec<Hook<S, E>>,
+}
+
+impl<S, E> Machine<S, E>
+where
+    S: Copy + Eq + Hash + Debug,
+    E: Copy + Eq + Hash + Debug,
+{
+    pub fn new(initial: S, table: &[(S, E, S)]) -> Result<Self, FsmError<S, E>> {
+        let mut next = HashMap::new();
+
+        for &(from, event, to) in table {
+            match next.insert((from, event), to) {
+                Some(other) if other != to => return Err(FsmError::Conflict { from, event, to: (other, to) }),
+                _ => (),
+            }
+        }
+
+        Ok(Self {
+            table: table.iter().map(|&(from, event, to)| Transition { from, event, to }).collect(),
+            next,
+            initial,
+            state: initial,
+            on_exit: Vec::new(),
+            on_enter: Vec::new(),
+            on_any: Vec::new(),
+        })
+    }
+
+    pub fn on_enter(&mut self, state: S, hook: impl FnMut(&Transition<S, E>) + 'static) -> &mut Self {
+        self.on_enter.push((state, Box::new(hook)));
+        self
+    }
+
+    pub fn on_exit(&mut self, state: S, hook: impl FnMut(&Transition<S, E>) + 'static) -> &mut Self {
+        self.on_exit.push((state, Box::new(hook)));
+        self
+    }
+
+    pub fn on_transition(&mut self, hook: impl FnMut(&Transition<S, E>) + 'static) -> &mut Self {
+        self.on_any.push(Box::new(hook));
+        self
+    }
+
+    pub fn state(&self) -> S {
+        self.state
+    }
+
+    pub fn initial(&self) -> S {
+        self.initial
+    }
+
+    pub fn table(&self) -> &[Transition<S, E>] {
+        &self.table
+    }
+
+    // where an event would take the machine from a state, without taking it there:
+    pub fn target(&self, state: S, event: E) -> Option<S> {
+        self.next.get(&(state, event)).copied()
+    }
+
+    pub fn can_fire(&self, event: E) -> bool {
+        self.target(self.state, event).is_some()
+    }
+
+    // the events that do something in the current state, in table order:
+    pub fn events(&self) -> Vec<E> {
+        let mut events = Vec::new();
+        for t in self.table.iter().filter(|t| t.from == self.state) {
+            if !events.contains(&t.event) {
+                events.push(t.event);
+            }
+        }
+        events
+    }
+
+    // * moves to the next state, running the hooks, and returns the new state:
+    pub fn fire(&mut self, event: E) -> Result<S, FsmError<S, E>> {
+        let from = self.state;
+        let to = self.target(from, event).ok_or(FsmError::InvalidTransition { state: from, event })?;
+        let transition = Transition { from, event, to };
+
+        for (_, hook) in self.on_exit.iter_mut().filter(|(state, _)| *state == from) {
+            hook(&transition);
+        }
+        for hook in &mut self.on_any {
+            hook(&transition);
+        }
+
+        self.state = to;
+
+        for (_, hook) in self.on_enter.iter_mut().filter(|(state, _)| *state == to) {
+            hook(&transition);
+        }
+
+        Ok(to)
+    }
+
+    // fires events one after another, stopping at the first one that can't happen:
+    pub fn fire_all(&mut self, events: impl IntoIterator<Item = E>) -> Result<S, FsmError<S, E>> {
+        for event in events {
+            self.fire(event)?;
+        }
+        Ok(self.state)
+    }
+
+    pub fn reset(&mut self) {
+        self.state = self.initial;
+    }
+
+    // * a Graphviz digraph: a node per state, an edge per transition (events that go between
+    // the same two states share an edge). the starting state has an arrow coming from nowhere,
+    // and the current state is filled in.
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut states = vec![self.initial];
+        for t in &self.table {
+            for state in [t.from, t.to] {
+                if !states.contains(&state) {
+                    states.push(state);
+                }
+            }
+        }
+
+        let mut edges: Vec<((S, S), Vec<E>)> = Vec::new();
+        for t in &self.table {
+            match edges.iter_mut().find(|(ends, _)| *ends == (t.from, t.to)) {
+                Some((_, events)) => events.push(t.event),
+                None              => edges.push(((t.from, t.to), vec![t.event])),
+            }
+        }
+
+        let id = |state: &S| dot_string(&format!("{state:?}"));
+
+        let mut dot = format!("digraph {} {{\n", dot_string(name));
+        dot.push_str("    rankdir=LR;\n");
+        dot.push_str("    node [shape=box, style=rounded];\n");
+        dot.push_str("    __start [shape=point];\n");
+
+        for state in &states {
+            let current = match *state == self.state {
+                true  => ", style=\"rounded,filled\", fillcolor=lightblue",
+                false => "",
+            };
+            let _ = writeln!(dot, "    {} [label={}{current}];", id(state), id(state));
+        }
+
+        let _ = writeln!(dot, "    __start -> {};", id(&self.initial));
+
+        for ((from, to), events) in &edges {
+            let label: Vec<String> = events.iter().map(|e| format!("{e:?}")).collect();
+            let _ = writeln!(dot, "    {} -> {} [label={}];", id(from), id(to), dot_string(&label.join("\n")));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<S: Debug, E: Debug> Debug for Machine<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Machine")
+            .field("state", &self.state)
+            .field("initial", &self.initial)
+            .field("table", &self.table)
+            .field("hooks", &(self.on_exit.len() + self.on_enter.len() + self.on_any.len()))
+            .finish()
+    }
+}
+
+
+// a quoted DOT string (the quotes and backslashes in it escaped, newlines as \n):
+fn dot_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Door {
+        Open,
+        Closed,
+        Locked,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Push {
+        Close,
+        Open,
+        Lock,
+        Unlock,
+        Knock,
+    }
+
+    fn door() -> Machine<Door, Push> {
+        Machine::new(Door::Open, &[
+            (Door::Open,   Push::Close,  Door::Closed),
+            (Door::Closed, Push::Open,   Door::Open),
+            (Door::Closed, Push::Lock,   Door::Locked),
+            (Door::Locked, Push::Unlock, Door::Closed),
+            (Door::Closed, Push::Knock,  Door::Closed),
+        ]).unwrap()
+    }
+
+    // hooks on every state and on every transition, writing down what ran:
+    fn logged() -> (Machine<Door, Push>, Rc<RefCell<Vec<String>>>) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut door = door();
+
+        for state in [Door::Open, Door::Closed, Door::Locked] {
+            let (exits, enters) = (log.clone(), log.clone());
+            door.on_exit(state, move |_| exits.borrow_mut().push(format!("exit {state:?}")))
+                .on_enter(state, move |_| enters.borrow_mut().push(format!("enter {state:?}")));
+        }
+        let any = log.clone();
+        door.on_transition(move |t| any.borrow_mut().push(format!("{:?} -> {:?} on {:?}", t.from, t.to, t.event)));
+
+        (door, log)
+    }
+
+    #[test]
+    fn tables_cant_disagree_with_themselves() {
+        let conflict = Machine::new(Door::Open, &[
+            (Door::Open, Push::Close, Door::Closed),
+            (Door::Open, Push::Close, Door::Locked),
+        ]);
+        assert_eq!(conflict.unwrap_err(), FsmError::Conflict {
+            from: Door::Open,
+            event: Push::Close,
+            to: (Door::Closed, Door::Locked),
+        });
+
+        // saying the same thing twice is fine:
+        let repeated = Machine::new(Door::Open, &[
+            (Door::Open, Push::Close, Door::Closed),
+            (Door::Open, Push::Close, Door::Closed),
+        ]);
+        assert!(repeated.is_ok());
+    }
+
+    #[test]
+    fn events_the_state_doesnt_have_are_errors() {
+        let (mut door, log) = logged();
+
+        assert_eq!(door.fire(Push::Lock), Err(FsmError::InvalidTransition { state: Door::Open, event: Push::Lock }));
+        assert_eq!(door.state(), Door::Open);
+        assert!(log.borrow().is_empty());
+
+        assert!(!door.can_fire(Push::Lock));
+        assert_eq!(door.events(), [Push::Close]);
+    }
+
+    #[test]
+    fn hooks_run_exit_then_transition_then_enter() {
+        let (mut door, log) = logged();
+
+        assert_eq!(door.fire(Push::Close), Ok(Door::Closed));
+        assert_eq!(door.fire(Push::Knock), Ok(Door::Closed));  // leaves and comes back
+
+        assert_eq!(*log.borrow(), [
+            "exit Open",   "Open -> Closed on Close",   "enter Closed",
+            "exit Closed", "Closed -> Closed on Knock", "enter Closed",
+        ]);
+    }
+
+    #[test]
+    fn fire_all_stops_at_the_first_error() {
+        let (mut door, log) = logged();
+
+        let result = door.fire_all([Push::Close, Push::Lock, Push::Close, Push::Unlock]);
+
+        assert_eq!(result, Err(FsmError::InvalidTransition { state: Door::Locked, event: Push::Close }));
+        assert_eq!(door.state(), Door::Locked);  // the Unlock never happened
+        assert_eq!(log.borrow().iter().filter(|l| l.contains("->")).count(), 2);
+
+        assert_eq!(door.fire_all([Push::Unlock, Push::Open]), Ok(Door::Open));
+    }
+
+    #[test]
+    fn reset_goes_back_without_running_hooks() {
+        let (mut door, log) = logged();
+        door.fire_all([Push::Close, Push::Lock]).unwrap();
+        let before = log.borrow().len();
+
+        door.reset();
+
+        assert_eq!(door.state(), Door::Open);
+        assert_eq!(door.state(), door.initial());
+        assert_eq!(log.borrow().len(), before);
+    }
+
+    #[test]
+    fn the_traffic_light_as_dot() {
+        let mut light = traffic::machine().unwrap();
+        light.fire(traffic::Event::Timer).unwrap();
+
+        assert_eq!(light.to_dot("traffic light"), concat!(
+            "digraph \"traffic light\" {\n",
+            "    rankdir=LR;\n",
+            "    node [shape=box, style=rounded];\n",
+            "    __start [shape=point];\n",
+            "    \"Red\" [label=\"Red\"];\n",
+            "    \"Green\" [label=\"Green\", style=\"rounded,filled\", fillcolor=lightblue];\n",
+            "    \"Yellow\" [label=\"Yellow\"];\n",
+            "    \"FlashingYellow\" [label=\"FlashingYellow\"];\n",
+            "    __start -> \"Red\";\n",
+            "    \"Red\" -> \"Green\" [label=\"Timer\"];\n",
+            "    \"Green\" -> \"Yellow\" [label=\"Timer\"];\n",
+            "    \"Yellow\" -> \"Red\" [label=\"Timer\"];\n",
+            "    \"Red\" -> \"FlashingYellow\" [label=\"Fault\"];\n",
+            "    \"Green\" -> \"FlashingYellow\" [label=\"Fault\"];\n",
+            "    \"Yellow\" -> \"FlashingYellow\" [label=\"Fault\"];\n",
+            "    \"FlashingYellow\" -> \"Red\" [label=\"Repair\"];\n",
+            "}\n",
+        ));
+    }
+
+    #[test]
+    fn events_between_the_same_states_share_an_edge() {
+        let machine = Machine::new(Door::Open, &[
+            (Door::Open, Push::Close, Door::Closed),
+            (Door::Open, Push::Lock,  Door::Closed),
+        ]).unwrap();
+
+        assert!(machine.to_dot("door").contains("    \"Open\" -> \"Closed\" [label=\"Close\\nLock\"];\n"));
+    }
+}
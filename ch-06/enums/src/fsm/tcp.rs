@@ -0,0 +1,71 @@
+// A TCP connection's lifecycle.
+// the states from RFC 793 (figure 6), with events for what the application asks for (App*),
+// what arrives from the other end (Rcv*), and the TIME-WAIT timer running out.
+//
+// * the two usual paths:
+// - opening:  Closed => Listen => SynReceived => Established     (server, passive open)
+//             Closed => SynSent => Established                   (client, active open)
+// - closing:  Established => FinWait1 => FinWait2 => TimeWait => Closed   (we close first)
+//             Established => CloseWait => LastAck => Closed               (they close first)
+// both ends closing at once goes through Closing instead of FinWait2.
+
+use super::{FsmError, Machine};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum State {
+    Closed,
+    Listen,
+    SynSent,
+    SynReceived,
+    Established,
+    FinWait1,
+    FinWait2,
+    CloseWait,
+    Closing,
+    LastAck,
+    TimeWait,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Event {
+    AppPassiveOpen,
+    AppActiveOpen,
+    AppSend,
+    AppClose,
+    RcvSyn,
+    RcvSynAck,
+    RcvAck,
+    RcvFin,
+    RcvFinAck,  // a FIN that also acknowledges ours
+    Timeout,    // 2 * the maximum segment lifetime, in TIME-WAIT
+}
+
+
+pub fn machine() -> Result<Machine<State, Event>, FsmError<State, Event>> {
+    use Event::*;
+    use State::*;
+
+    Machine::new(Closed, &[
+        (Closed,      AppPassiveOpen, Listen),
+        (Closed,      AppActiveOpen,  SynSent),
+        (Listen,      RcvSyn,         SynReceived),
+        (Listen,      AppSend,        SynSent),
+        (Listen,      AppClose,       Closed),
+        (SynSent,     RcvSyn,         SynReceived),
+        (SynSent,     RcvSynAck,      Established),
+        (SynSent,     AppClose,       Closed),
+        (SynReceived, RcvAck,         Established),
+        (SynReceived, AppClose,       FinWait1),
+        (Established, AppClose,       FinWait1),
+        (Established, RcvFin,         CloseWait),
+        (FinWait1,    RcvAck,         FinWait2),
+        (FinWait1,    RcvFin,         Closing),
+        (FinWait1,    RcvFinAck,      TimeWait),
+        (FinWait2,    RcvFin,         TimeWait),
+        (CloseWait,   AppClose,       LastAck),
+        (Closing,     RcvAck,         TimeWait),
+        (LastAck,     RcvAck,         Closed),
+        (TimeWait,    Timeout,        Closed),
+    ])
+}
@@ -0,0 +1,37 @@
+// A traffic light.
+// the timer cycles it red => green => yellow => red. a fault makes it flash yellow
+// from any colour, until it's repaired (it comes back on red, to be safe).
+
+use super::{FsmError, Machine};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Light {
+    Red,
+    Green,
+    Yellow,
+    FlashingYellow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Event {
+    Timer,
+    Fault,
+    Repair,
+}
+
+
+pub fn machine() -> Result<Machine<Light, Event>, FsmError<Light, Event>> {
+    use Event::*;
+    use Light::*;
+
+    Machine::new(Red, &[
+        (Red,            Timer,  Green),
+        (Green,          Timer,  Yellow),
+        (Yellow,         Timer,  Red),
+        (Red,            Fault,  FlashingYellow),
+        (Green,          Fault,  FlashingYellow),
+        (Yellow,         Fault,  FlashingYellow),
+        (FlashingYellow, Repair, Red),
+    ])
+}
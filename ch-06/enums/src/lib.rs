@@ -0,0 +1,15 @@
+// Enums!
+// The Message enum from the chapter, and the things built around it.
+
+pub mod canvas;
+pub mod config;
+pub mod dispatch;
+pub mod fsm;
+pub mod message;
+pub mod wire;
+
+#[cfg(test)]
+mod test_support;
+
+pub use dispatch::Dispatcher;
+pub use message::{Kind, Message};
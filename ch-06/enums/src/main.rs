@@ -2,40 +2,59 @@
 
 #![allow(unused)]  // stops the compiler from complaning :D
 
-enum Message {
-    Quit,
-    Move { x: i32, y: i32 },
-    Write(String),
-    ChangeColor(i32, i32, i32),
-}
+mod cli;
+
+use std::env;
+
+use enums::canvas::Canvas;
+use enums::canvas::history::History;
+use enums::config::Resolver;
+use enums::fsm::{tcp, traffic};
+use enums::{Dispatcher, Kind, Message};
+use enums::wire::{binary, text};
+
+
+// * Message (and its impl block) moved to message.rs, so the library can use it too.
 
-// * the above is equivalent to the following structs:
-// ? struct QuitMessage;
-// ? struct MoveMessage {
-// ?     x: i32,
-// ?     y: i32,
-// ? }
-// ? struct WriteMessage(String);
-// ? struct ChangeColorMessage(i32, i32, i32);
-// ------------------------------------------------------
-// instead of all that, enums allow the grouping of related types under one banner.
-
-// enums can have implementation blocks that apply to all of its variants:
-impl Message {
-    fn call(&self) {
-        // handle each variant of the enum separately:
-        match self {
-            Message::Quit                 => println!("Quitting!"),
-            Message::Write(some_str)      => println!("{some_str}"),
-            Message::Move { .. }          => println!("Moving somewhere else!"),
-            Message::ChangeColor(_, _, _) => println!("Changing color!")
-        }
-    }
-}
 
 fn main() {
+    // * `cargo run -- <tool> ...` runs one of the command line tools instead of the examples:
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if let Some("draw") = args.first().map(String::as_str) {
+        return cli::draw::run(&args[1..]);
+    }
+
     let m = Message::Write(String::from("Enum!"));
-    m.call();
+
+    // what call() used to print, as handlers on a dispatcher:
+    let mut dispatcher = Dispatcher::new();
+    dispatcher
+        .on(Kind::Quit,        |_, _| println!("Quitting!"))
+        .on(Kind::Move,        |_, _| println!("Moving somewhere else!"))
+        .on(Kind::ChangeColor, |_, _| println!("Changing color!"))
+        .on(Kind::Write, |message, outbox| {
+            if let Message::Write(some_str) = message {
+                println!("{some_str}");
+                // handlers can reply with more messages, which go to the back of the queue:
+                if some_str.ends_with('!') {
+                    outbox.emit(Message::ChangeColor(255, 0, 0));
+                }
+            }
+        });
+
+    dispatcher.send(m).unwrap();
+    dispatcher.send(Message::Move { x: 1, y: 2 }).unwrap();
+    dispatcher.send(Message::Quit).unwrap();
+
+    // once a Quit is sent, nothing else gets in (the message is handed back).
+    // the Write's follow-up still does: it comes from a message that was sent before the Quit.
+    if let Err(e) = dispatcher.send(Message::Move { x: 0, y: 0 }) {
+        println!("Error: {e}");
+    }
+
+    let report = dispatcher.run();
+    println!("{report:?}");
 
     // using Option<T> explicitly states that the value *could* be null.
     // having to opt into the possibility of null values makes it clear what could go wrong.
@@ -71,4 +90,140 @@ fn main() {
     // ?     // do stuff
     // ? }
     // * binding is available in the current scope now, i guess???
+    // (yes: the else block has to leave the function/loop, so after it the binding always exists.
+    // config.rs uses it for real, along with the Option combinators, see config_demo below.)
+
+    wire_demo();
+    canvas_demo();
+    config_demo();
+    variants_demo();
+    fsm_demo();
+}
+
+
+// sending messages somewhere and reading them back, see wire/:
+fn wire_demo() {
+    use enums::wire::StreamDecoder;
+
+    let messages = [
+        Message::Move { x: 3, y: -4 },
+        Message::Write(String::from("say \"hi\"\nand wave")),
+        Message::ChangeColor(255, 128, 0),
+        Message::Quit,
+    ];
+
+    let mut bytes = Vec::new();
+    for message in &messages {
+        binary::encode_into(message, &mut bytes).expect("small enough for a frame");
+        println!("{:<32} {:?}", text::encode(message), binary::encode(message).expect("small enough for a frame"));
+    }
+
+    // the bytes arrive three at a time, and messages come out whenever one is complete:
+    let mut decoder = binary::Decoder::new();
+    for chunk in bytes.chunks(3) {
+        decoder.feed(chunk);
+        while let Some(message) = decoder.next_message().expect("encoded just above") {
+            println!("decoded {message:?}");
+        }
+    }
+
+    // cutting the last frame short:
+    match binary::decode_all(&bytes[..bytes.len() - 1]) {
+        Ok(_)  => println!("decoded a truncated stream?!"),
+        Err(e) => println!("Error: {e}"),
+    }
+
+    let script = "# a little script\nmove 1 2\nWRITE unquoted text works too\nCOLOR 1 2\n";
+    match text::decode_all(script) {
+        Ok(messages) => println!("{messages:?}"),
+        Err(e)       => println!("Error: {e}"),
+    }
+}
+
+
+// the messages as drawing commands, see canvas/:
+fn canvas_demo() {
+    let script = text::decode_all(include_str!("../drawing.txt")).expect("the sample drawing is valid");
+
+    let mut canvas = Canvas::new();
+    canvas.apply_all(&script).expect("the sample drawing only uses valid colours");
+    print!("{}", canvas.to_ascii(60));
+
+    // nothing can be drawn after the Quit at the end:
+    if let Err(e) = canvas.apply(&Message::Move { x: 1, y: 1 }) {
+        println!("Error: {e}");
+    }
+
+    // undoing a step and drawing something else keeps the old step around as another branch:
+    let mut history = History::new(Canvas::new());
+    history.apply(Message::Move { x: 4, y: 0 }).unwrap();
+    history.apply(Message::Move { x: 0, y: 2 }).unwrap();
+    history.undo();
+    history.apply(Message::Write(String::from("hi"))).unwrap();
+    print!("{}", history.canvas().to_ascii(60));
+
+    history.undo();
+    println!("Branches: {:?}", history.branch_messages());
+    history.redo_branch(0);
+    print!("{}", history.canvas().to_ascii(60));
+}
+
+
+// the same setting from a few places at once, and which one wins (the first that has it):
+fn config_demo() {
+    let env = [(String::from("DRAW_COLUMNS"), String::from("80")), (String::from("DRAW_SVG"), String::new())];
+    let resolver = Resolver::new("DRAW_")
+        .with_env_vars(env)
+        .with_cli_value("scale", "4")
+        .with_file_contents("draw.conf", "columns = 40\nscale = 2\nsvg = house.svg\n")
+        .expect("valid config");
+
+    let columns = resolver.get("columns", 60).expect("columns is a number");
+    let scale = resolver.get("scale", 8).expect("scale is a number");
+    let svg = resolver.optional::<String>("svg").expect("any text is a path");
+    let binary = resolver.get("binary", false).expect("binary isn't set anywhere");
+
+    println!("columns = {} ({})", columns.value, columns.source);
+    println!("scale   = {} ({})", scale.value, scale.source);
+    if let Some(svg) = svg {
+        println!("svg     = {} ({})", svg.value, svg.source);
+    }
+    println!("binary  = {} ({})", binary.value, binary.source);
+}
+
+
+// what #[derive(Variants)] adds to Message, see the variants-derive crate:
+fn variants_demo() {
+    println!("Message has {} variants: {:?}", Kind::COUNT, Message::VARIANTS);
+
+    for input in ["quit", "change-color", "teleport"] {
+        match input.parse::<Kind>() {
+            // only the variants without data can be made from just a name:
+            Ok(kind) => println!("{input:?} is {kind} ({} fields), {:?}", kind.field_count(), Message::from_kind(kind)),
+            Err(e)   => println!("Error: {e}"),
+        }
+    }
+}
+
+
+// enums as the states and events of a state machine, see fsm/:
+fn fsm_demo() {
+    let mut light = traffic::machine().expect("the traffic light's table is consistent");
+    light
+        .on_enter(traffic::Light::Red, |_| println!("Stop!"))
+        .on_exit(traffic::Light::Red, |_| println!("Go!"))
+        .on_transition(|t| println!("{:?} --{:?}--> {:?}", t.from, t.event, t.to));
+
+    light.fire_all([traffic::Event::Timer, traffic::Event::Timer, traffic::Event::Fault]).unwrap();
+    if let Err(e) = light.fire(traffic::Event::Timer) {
+        println!("Error: {e}");
+    }
+    light.fire(traffic::Event::Repair).unwrap();
+    print!("{}", light.to_dot("traffic_light"));
+
+    // a client connecting, then closing first:
+    use tcp::Event::*;
+    let mut connection = tcp::machine().expect("the TCP table is consistent");
+    let state = connection.fire_all([AppActiveOpen, RcvSynAck, AppClose, RcvAck, RcvFin]).unwrap();
+    println!("{state:?}, can only wait for {:?}", connection.events());
 }
@@ -0,0 +1,44 @@
+// The chapter's Message enum.
+// it lives in the library now so the wire formats (and everything else) can use it.
+
+use variants_derive::Variants;
+
+
+// * Variants also generates Kind: which variant a message is, without its data
+// (what dispatcher handlers register for). see the variants-derive crate for everything it adds.
+#[derive(Debug, Clone, PartialEq, Eq, Variants)]
+#[variants(kind = Kind)]
+pub enum Message {
+    Quit,
+    Move { x: i32, y: i32 },
+    Write(String),
+    ChangeColor(i32, i32, i32),
+}
+
+// * the above is equivalent to the following structs:
+// ? struct QuitMessage;
+// ? struct MoveMessage {
+// ?     x: i32,
+// ?     y: i32,
+// ? }
+// ? struct WriteMessage(String);
+// ? struct ChangeColorMessage(i32, i32, i32);
+// ------------------------------------------------------
+// instead of all that, enums allow the grouping of related types under one banner.
+
+// enums can have implementation blocks that apply to all of its variants.
+// the derive writes Message's for it, and kind() is what it would look like by hand:
+// ? impl Message {
+// ?     pub fn kind(&self) -> Kind {
+// ?         // handle each variant of the enum separately:
+// ?         match self {
+// ?             Message::Quit                 => Kind::Quit,
+// ?             Message::Move { .. }          => Kind::Move,
+// ?             Message::Write(_)             => Kind::Write,
+// ?             Message::ChangeColor(_, _, _) => Kind::ChangeColor,
+// ?         }
+// ?     }
+// ? }
+
+// * call() used to be a match that printed something for each variant.
+// that's a handler on a Dispatcher now (see dispatch.rs), so what happens can be decided by whoever's listening.
@@ -0,0 +1,19 @@
+// Helpers shared by the tests.
+
+// * xorshift: not good randomness, but plenty for test data, and the same every run.
+// the seed can be anything but 0 (which only ever gives 0s).
+pub struct Random(pub u64);
+
+impl Random {
+    pub fn bits(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // 0 to max - 1:
+    pub fn below(&mut self, max: usize) -> usize {
+        (self.bits() % max as u64) as usize
+    }
+}
@@ -0,0 +1,301 @@
+// Binary frames.
+// every message is one frame: its length, then a tag byte for the variant, then its fields.
+//
+// ? [length: varint] [tag: u8] [fields...]
+// - length:  how many bytes come after it (the tag and the fields).
+// - tag:     0 = Quit, 1 = Move, 2 = Write, 3 = ChangeColor.
+// - fields:  numbers are zigzag varints, a Write's text is the rest of the frame (UTF-8).
+//
+// * varints use 7 bits per byte, and the top bit says "there's another byte", so small numbers
+// take one byte instead of four. zigzag maps signed numbers to unsigned ones so that small
+// negative numbers stay small too: 0 => 0, -1 => 1, 1 => 2, -2 => 3...
+// ? Move { x: 3, y: -4 }  => [03] [01] [06] [07]   (4 bytes instead of 1 + 4 + 4)
+//
+// the length prefix means a decoder always knows where the next frame starts,
+// so a malformed frame is reported and skipped without losing the frames after it.
+
+use std::fmt;
+use std::io;
+
+use super::StreamDecoder;
+use crate::Message;
+
+
+// bigger frames are rejected before anything is buffered for them:
+pub const MAX_FRAME: usize = 1 << 20;
+
+const QUIT: u8 = 0;
+const MOVE: u8 = 1;
+const WRITE: u8 = 2;
+const CHANGE_COLOR: u8 = 3;
+
+
+// * a Write with more than MAX_FRAME bytes of text can't be a frame (no decoder would take it),
+// so it's an error here instead of bytes nobody can read. nothing is written for it.
+pub fn encode(message: &Message) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::new();
+    encode_into(message, &mut out)?;
+    Ok(out)
+}
+
+// the error's offset is where the frame would have started in `out`:
+pub fn encode_into(message: &Message, out: &mut Vec<u8>) -> Result<(), Error> {
+    let mut body = Vec::new();
+
+    match message {
+        Message::Quit => body.push(QUIT),
+        Message::Move { x, y } => {
+            body.push(MOVE);
+            write_int(&mut body, *x);
+            write_int(&mut body, *y);
+        },
+        Message::Write(text) => {
+            body.push(WRITE);
+            body.extend_from_slice(text.as_bytes());
+        },
+        Message::ChangeColor(r, g, b) => {
+            body.push(CHANGE_COLOR);
+            for n in [r, g, b] {
+                write_int(&mut body, *n);
+            }
+        },
+    }
+
+    if body.len() > MAX_FRAME {
+        return Err(Error { offset: out.len(), kind: ErrorKind::FrameTooLarge(body.len() as u64) });
+    }
+
+    write_varint(out, body.len() as u64);
+    out.extend_from_slice(&body);
+    Ok(())
+}
+
+// decodes a complete buffer (it's an error if it ends halfway through a frame):
+pub fn decode_all(bytes: &[u8]) -> Result<Vec<Message>, Error> {
+    let mut decoder = Decoder::new();
+    let mut messages = Vec::new();
+
+    decoder.feed(bytes);
+    while let Some(message) = decoder.next_message()? {
+        messages.push(message);
+    }
+    decoder.finish()?;
+
+    Ok(messages)
+}
+
+
+// where it went wrong (counting from the first byte ever fed to the decoder), and what:
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub offset: usize,
+    pub kind:   ErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    Truncated { needed: usize, available: usize },  // the input ended in the middle of a frame
+    VarintTooLong,                                  // more than 10 bytes, it can't be a u64
+    FrameTooLarge(u64),
+    EmptyFrame,                                     // a length of 0, so not even a tag
+    UnknownTag(u8),
+    MissingField(&'static str),                     // the frame ended before this field
+    IntOutOfRange(u64),                             // a varint too big for an i32
+    InvalidUtf8 { valid_up_to: usize },
+    TrailingBytes(usize),                           // the frame is longer than its fields
+    Io(io::ErrorKind),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at byte {}: ", self.offset)?;
+
+        match &self.kind {
+            ErrorKind::Truncated { needed, available } => write!(f, "input ends in the middle of a frame (needed {needed} bytes, only {available} left)"),
+            ErrorKind::VarintTooLong                   => write!(f, "varint is longer than 10 bytes"),
+            ErrorKind::FrameTooLarge(len)              => write!(f, "frame length {len} is over the limit of {MAX_FRAME} bytes"),
+            ErrorKind::EmptyFrame                      => write!(f, "frame is empty, it doesn't even have a tag"),
+            ErrorKind::UnknownTag(tag)                 => write!(f, "unknown message tag {tag}"),
+            ErrorKind::MissingField(field)             => write!(f, "frame ends before its {field} field"),
+            ErrorKind::IntOutOfRange(n)                => write!(f, "number {n} (zigzag encoded) doesn't fit in an i32"),
+            ErrorKind::InvalidUtf8 { valid_up_to }     => write!(f, "text isn't valid UTF-8 after {valid_up_to} bytes"),
+            ErrorKind::TrailingBytes(n)                => write!(f, "frame has {n} unused bytes after its fields"),
+            ErrorKind::Io(kind)                        => write!(f, "couldn't read the input: {kind}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error { offset: 0, kind: ErrorKind::Io(e.kind()) }
+    }
+}
+
+
+#[derive(Debug, Default)]
+pub struct Decoder {
+    buffer:   Vec<u8>,
+    consumed: usize,  // bytes already decoded and dropped from the buffer, for error offsets
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.buffer.drain(..n);
+        self.consumed += n;
+    }
+
+    fn error(&self, at: usize, kind: ErrorKind) -> Error {
+        Error { offset: self.consumed + at, kind }
+    }
+}
+
+impl StreamDecoder for Decoder {
+    type Error = Error;
+
+    fn feed(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    fn next_message(&mut self) -> Result<Option<Message>, Error> {
+        let (length, prefix) = match read_varint(&self.buffer) {
+            Varint::Value(length, size) => (length, size),
+            Varint::Incomplete          => return Ok(None),
+            Varint::TooLong => {
+                // there's no telling where the next frame starts, so everything buffered is dropped:
+                let e = self.error(0, ErrorKind::VarintTooLong);
+                self.consume(self.buffer.len());
+                return Err(e);
+            },
+        };
+
+        if length > MAX_FRAME as u64 {
+            let e = self.error(0, ErrorKind::FrameTooLarge(length));
+            self.consume(self.buffer.len());
+            return Err(e);
+        }
+
+        let end = prefix + length as usize;
+        if self.buffer.len() < end {
+            return Ok(None);
+        }
+
+        // the whole frame is here, so it's consumed whether or not it decodes:
+        let result = decode_frame(&self.buffer[prefix..end]).map_err(|(at, kind)| self.error(prefix + at, kind));
+        self.consume(end);
+
+        result.map(Some)
+    }
+
+    fn finish(&mut self) -> Result<Option<Message>, Error> {
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+
+        let available = self.buffer.len();
+        let needed = match read_varint(&self.buffer) {
+            Varint::Value(length, size) => size + length as usize,
+            _                           => available + 1,  // not even the whole length prefix
+        };
+
+        let e = self.error(0, ErrorKind::Truncated { needed, available });
+        self.consume(available);
+        Err(e)
+    }
+}
+
+
+// one frame, without its length prefix. errors are (offset in the frame, what went wrong):
+fn decode_frame(frame: &[u8]) -> Result<Message, (usize, ErrorKind)> {
+    let Some((&tag, fields)) = frame.split_first() else {
+        return Err((0, ErrorKind::EmptyFrame));
+    };
+
+    let mut fields = Fields { bytes: fields, at: 1 };
+
+    let message = match tag {
+        QUIT         => Message::Quit,
+        MOVE         => Message::Move { x: fields.int("x")?, y: fields.int("y")? },
+        CHANGE_COLOR => Message::ChangeColor(fields.int("red")?, fields.int("green")?, fields.int("blue")?),
+        WRITE => {
+            let text = String::from_utf8(fields.bytes.to_vec())
+                .map_err(|e| (fields.at, ErrorKind::InvalidUtf8 { valid_up_to: e.utf8_error().valid_up_to() }))?;
+            fields.bytes = &[];
+            Message::Write(text)
+        },
+        tag => return Err((0, ErrorKind::UnknownTag(tag))),
+    };
+
+    match fields.bytes.len() {
+        0     => Ok(message),
+        extra => Err((fields.at, ErrorKind::TrailingBytes(extra))),
+    }
+}
+
+
+// the fields of a frame that haven't been read yet:
+struct Fields<'a> {
+    bytes: &'a [u8],
+    at:    usize,  // where they start in the frame
+}
+
+impl Fields<'_> {
+    fn int(&mut self, name: &'static str) -> Result<i32, (usize, ErrorKind)> {
+        let (n, size) = match read_varint(self.bytes) {
+            Varint::Value(n, size) => (n, size),
+            Varint::Incomplete     => return Err((self.at, ErrorKind::MissingField(name))),
+            Varint::TooLong        => return Err((self.at, ErrorKind::VarintTooLong)),
+        };
+
+        let zigzag = u32::try_from(n).map_err(|_| (self.at, ErrorKind::IntOutOfRange(n)))?;
+
+        self.bytes = &self.bytes[size..];
+        self.at += size;
+
+        Ok(((zigzag >> 1) as i32) ^ -((zigzag & 1) as i32))
+    }
+}
+
+
+enum Varint {
+    Value(u64, usize),  // the number, and how many bytes it took
+    Incomplete,
+    TooLong,
+}
+
+fn read_varint(bytes: &[u8]) -> Varint {
+    let mut n: u64 = 0;
+
+    for (i, &byte) in bytes.iter().enumerate() {
+        // the 10th byte can only hold the 64th bit:
+        if i == 9 && byte > 1 {
+            return Varint::TooLong;
+        }
+
+        n |= ((byte & 0x7f) as u64) << (7 * i);
+
+        if byte & 0x80 == 0 {
+            return Varint::Value(n, i + 1);
+        }
+    }
+
+    Varint::Incomplete
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push((n as u8 & 0x7f) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn write_int(out: &mut Vec<u8>, n: i32) {
+    let zigzag = ((n << 1) ^ (n >> 31)) as u32;
+    write_varint(out, zigzag as u64);
+}
@@ -0,0 +1,199 @@
+// Wire formats for Message.
+// turning messages into bytes to send or save, and back again.
+//
+// * Formats:
+// - binary:  compact length-prefixed frames, for programs talking to each other.
+// - text:    one message per line ("MOVE 3 -4"), for people and scripts.
+//
+// * both decoders are streaming: bytes can arrive in any sized chunks (half a frame,
+// three and a bit frames...), and messages come out as soon as they're complete.
+// ? let mut decoder = binary::Decoder::new();
+// ? decoder.feed(&bytes[..5]);
+// ? decoder.next_message()?;   // Ok(None): needs more bytes
+// ? decoder.feed(&bytes[5..]);
+// ? decoder.next_message()?;   // Ok(Some(message))
+// ? decoder.finish()?;         // Err if the input stopped halfway through a message
+//
+// messages() does the feeding from anything that implements Read (a file, a socket, stdin).
+
+pub mod binary;
+pub mod text;
+
+use std::io::{self, Read};
+
+use crate::Message;
+
+
+pub trait StreamDecoder {
+    type Error: From<io::Error>;
+
+    // adds more input:
+    fn feed(&mut self, bytes: &[u8]);
+
+    // the next complete message, or None if more input is needed first:
+    fn next_message(&mut self) -> Result<Option<Message>, Self::Error>;
+
+    // * there's no more input coming.
+    // returns the last message if the format allows one without a terminator (text does),
+    // or an error if there are leftover bytes that don't make a whole message.
+    fn finish(&mut self) -> Result<Option<Message>, Self::Error>;
+}
+
+
+// * every message from a reader, decoded as it's read:
+// ? for message in wire::messages(file, text::Decoder::new()) { ... }
+pub fn messages<R: Read, D: StreamDecoder>(reader: R, decoder: D) -> Messages<R, D> {
+    Messages { reader, decoder, finished: false }
+}
+
+pub struct Messages<R, D> {
+    reader:   R,
+    decoder:  D,
+    finished: bool,
+}
+
+impl<R: Read, D: StreamDecoder> Iterator for Messages<R, D> {
+    type Item = Result<Message, D::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = [0; 4096];
+
+        loop {
+            match self.decoder.next_message() {
+                Ok(Some(message)) => return Some(Ok(message)),
+                Err(e)            => return Some(Err(e)),
+                Ok(None) if self.finished => return None,
+                Ok(None)          => (),
+            }
+
+            match self.reader.read(&mut chunk) {
+                Ok(0) => {
+                    self.finished = true;
+                    return self.decoder.finish().transpose();
+                },
+                Ok(n) => self.decoder.feed(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e.into()));
+                },
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Random;
+
+    fn int(random: &mut Random) -> i32 {
+        match random.below(4) {
+            0 => [0, 1, -1, i32::MIN, i32::MAX][random.below(5)],
+            1 => random.bits() as i32,
+            _ => random.below(2000) as i32 - 1000,
+        }
+    }
+
+    fn char(random: &mut Random) -> char {
+        const AWKWARD: &[char] = &[
+            '"', '\\', '\n', '\r', '\t', ' ', '#', '\0', '\u{7f}', '\u{85}', '\u{a0}', '\u{2028}',
+            '\u{feff}', '\u{301}', '\u{200d}', 'é', 'ß', '中', '🦀', '👩', '\u{10ffff}',
+        ];
+
+        match random.below(3) {
+            0 => AWKWARD[random.below(AWKWARD.len())],
+            1 => (b' ' + random.below(95) as u8) as char,
+            _ => loop {
+                if let Some(c) = char::from_u32(random.below(0x11_0000) as u32) {
+                    break c;
+                }
+            },
+        }
+    }
+
+    fn message(random: &mut Random) -> Message {
+        match random.below(4) {
+            0 => Message::Quit,
+            1 => Message::Move { x: int(random), y: int(random) },
+            2 => Message::ChangeColor(int(random), int(random), int(random)),
+            _ => {
+                let length = match random.below(20) {
+                    0 => 50_000 + random.below(50_000),
+                    _ => random.below(30),
+                };
+                Message::Write((0..length).map(|_| char(random)).collect())
+            },
+        }
+    }
+
+    fn messages(random: &mut Random) -> Vec<Message> {
+        (0..random.below(12)).map(|_| message(random)).collect()
+    }
+
+    // feeds the bytes in random sized chunks (often a single byte), taking messages out whenever there are some:
+    fn decode_in_chunks<D: StreamDecoder>(random: &mut Random, bytes: &[u8], mut decoder: D) -> Result<Vec<Message>, D::Error> {
+        let mut messages = Vec::new();
+        let mut at = 0;
+
+        while at < bytes.len() {
+            let size = match random.below(3) {
+                0 => 1,
+                _ => 1 + random.below(bytes.len() - at),
+            };
+            decoder.feed(&bytes[at..at + size]);
+            at += size;
+
+            while let Some(message) = decoder.next_message()? {
+                messages.push(message);
+            }
+        }
+        messages.extend(decoder.finish()?);
+
+        Ok(messages)
+    }
+
+    #[test]
+    fn binary_round_trips() {
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..300 {
+            let messages = messages(&mut random);
+            let mut bytes = Vec::new();
+            for message in &messages {
+                binary::encode_into(message, &mut bytes).unwrap();
+            }
+
+            assert_eq!(binary::decode_all(&bytes).unwrap(), messages);
+            assert_eq!(decode_in_chunks(&mut random, &bytes, binary::Decoder::new()).unwrap(), messages);
+        }
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let mut random = Random(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..300 {
+            let messages = messages(&mut random);
+            let lines: Vec<String> = messages.iter().map(text::encode).collect();
+            // with and without a newline after the last line:
+            let script = lines.join("\n") + ["", "\n"][random.below(2)];
+
+            assert_eq!(text::decode_all(&script).unwrap(), messages, "{script:?}");
+            assert_eq!(decode_in_chunks(&mut random, script.as_bytes(), text::Decoder::new()).unwrap(), messages);
+        }
+    }
+
+    #[test]
+    fn binary_frames_stay_under_the_limit() {
+        let biggest = Message::Write("x".repeat(binary::MAX_FRAME - 1));  // plus the tag byte
+        let bytes = binary::encode(&biggest).unwrap();
+        assert_eq!(binary::decode_all(&bytes).unwrap(), [biggest]);
+
+        let mut out = vec![1, 2, 3];
+        let e = binary::encode_into(&Message::Write("x".repeat(binary::MAX_FRAME)), &mut out).unwrap_err();
+        assert_eq!(e, binary::Error { offset: 3, kind: binary::ErrorKind::FrameTooLarge(binary::MAX_FRAME as u64 + 1) });
+        assert_eq!(out, [1, 2, 3]);
+    }
+}
@@ -0,0 +1,240 @@
+// Text lines.
+// one message per line, a command and its arguments separated by spaces:
+//
+// ? QUIT
+// ? MOVE 3 -4
+// ? WRITE "hello, \"world\"\n"
+// ? COLOR 255 128 0
+//
+// - commands don't care about case ("move 3 -4" works too).
+// - WRITE's text is quoted, with \" \\ \n \r \t escapes. for hand-written scripts, an unquoted
+//   text is fine too: it's the rest of the line, with the surrounding spaces trimmed.
+// - blank lines and lines starting with # are skipped.
+// - the last line doesn't need a newline at the end.
+
+use std::fmt;
+use std::io;
+
+use super::StreamDecoder;
+use crate::Message;
+
+
+pub fn encode(message: &Message) -> String {
+    match message {
+        Message::Quit                 => "QUIT".to_string(),
+        Message::Move { x, y }        => format!("MOVE {x} {y}"),
+        Message::Write(text)          => format!("WRITE {}", quote(text)),
+        Message::ChangeColor(r, g, b) => format!("COLOR {r} {g} {b}"),
+    }
+}
+
+// one line, without the newline (None for a blank line or a comment):
+pub fn decode_line(line: &str) -> Result<Option<Message>, ErrorKind> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let command = command.to_ascii_uppercase();
+    let mut args = Args { command: &command, rest: rest.trim_start() };
+
+    let message = match command.as_str() {
+        "QUIT"  => Message::Quit,
+        "MOVE"  => Message::Move { x: args.int("x")?, y: args.int("y")? },
+        "COLOR" => Message::ChangeColor(args.int("red")?, args.int("green")?, args.int("blue")?),
+        "WRITE" => Message::Write(args.text()?),
+        _       => return Err(ErrorKind::UnknownCommand(command)),
+    };
+
+    match args.rest.trim() {
+        ""    => Ok(Some(message)),
+        extra => Err(ErrorKind::UnexpectedArgument { command: command.clone(), found: extra.to_string() }),
+    }
+}
+
+pub fn decode_all(text: &str) -> Result<Vec<Message>, Error> {
+    let mut decoder = Decoder::new();
+    let mut messages = Vec::new();
+
+    decoder.feed(text.as_bytes());
+    while let Some(message) = decoder.next_message()? {
+        messages.push(message);
+    }
+    messages.extend(decoder.finish()?);
+
+    Ok(messages)
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,  // starting from 1
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    UnknownCommand(String),
+    MissingArgument { command: String, name: &'static str },
+    UnexpectedArgument { command: String, found: String },
+    InvalidNumber { name: &'static str, found: String },
+    UnterminatedString,
+    InvalidEscape(char),
+    InvalidUtf8,
+    Io(io::ErrorKind),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnknownCommand(c)                   => write!(f, "unknown command {c:?} (expected QUIT, MOVE, WRITE or COLOR)"),
+            ErrorKind::MissingArgument { command, name }   => write!(f, "{command} is missing its {name} argument"),
+            ErrorKind::UnexpectedArgument { command, found } => write!(f, "{command} has too many arguments, {found:?} is extra"),
+            ErrorKind::InvalidNumber { name, found }       => write!(f, "{name} should be a whole number (an i32), found {found:?}"),
+            ErrorKind::UnterminatedString                  => write!(f, "the text's closing quote is missing"),
+            ErrorKind::InvalidEscape(c)                    => write!(f, "unknown escape \\{c} (expected \\\", \\\\, \\n, \\r or \\t)"),
+            ErrorKind::InvalidUtf8                         => write!(f, "the line isn't valid UTF-8"),
+            ErrorKind::Io(kind)                            => write!(f, "couldn't read the input: {kind}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error { line: 0, kind: ErrorKind::Io(e.kind()) }
+    }
+}
+
+
+#[derive(Debug, Default)]
+pub struct Decoder {
+    buffer: Vec<u8>,  // the start of a line that hasn't ended yet
+    line:   usize,    // lines already decoded
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn decode(&mut self, bytes: &[u8]) -> Result<Option<Message>, Error> {
+        self.line += 1;
+        let error = |kind| Error { line: self.line, kind };
+
+        let line = std::str::from_utf8(bytes).map_err(|_| error(ErrorKind::InvalidUtf8))?;
+        decode_line(line.strip_suffix('\r').unwrap_or(line)).map_err(error)
+    }
+}
+
+impl StreamDecoder for Decoder {
+    type Error = Error;
+
+    fn feed(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    fn next_message(&mut self) -> Result<Option<Message>, Error> {
+        // blank lines and comments don't make a message, so keep going until one does:
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+
+            if let Some(message) = self.decode(&line[..end])? {
+                return Ok(Some(message));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn finish(&mut self) -> Result<Option<Message>, Error> {
+        match self.buffer.is_empty() {
+            true  => Ok(None),
+            false => {
+                let line = std::mem::take(&mut self.buffer);
+                self.decode(&line)
+            },
+        }
+    }
+}
+
+
+// the arguments of a line that haven't been read yet:
+struct Args<'a> {
+    command: &'a str,
+    rest:    &'a str,
+}
+
+impl Args<'_> {
+    fn int(&mut self, name: &'static str) -> Result<i32, ErrorKind> {
+        let (arg, rest) = self.rest.split_once(char::is_whitespace).unwrap_or((self.rest, ""));
+        if arg.is_empty() {
+            return Err(ErrorKind::MissingArgument { command: self.command.to_string(), name });
+        }
+
+        self.rest = rest.trim_start();
+        arg.parse().map_err(|_| ErrorKind::InvalidNumber { name, found: arg.to_string() })
+    }
+
+    fn text(&mut self) -> Result<String, ErrorKind> {
+        let Some(quoted) = self.rest.strip_prefix('"') else {
+            // unquoted: the rest of the line is the text
+            return match std::mem::take(&mut self.rest).trim() {
+                ""   => Err(ErrorKind::MissingArgument { command: self.command.to_string(), name: "text" }),
+                text => Ok(text.to_string()),
+            };
+        };
+
+        let mut text = String::new();
+        let mut chars = quoted.char_indices();
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &quoted[i + 1..];
+                    return Ok(text);
+                },
+                '\\' => text.push(match chars.next() {
+                    Some((_, '"'))  => '"',
+                    Some((_, '\\')) => '\\',
+                    Some((_, 'n'))  => '\n',
+                    Some((_, 'r'))  => '\r',
+                    Some((_, 't'))  => '\t',
+                    Some((_, c))    => return Err(ErrorKind::InvalidEscape(c)),
+                    None            => return Err(ErrorKind::UnterminatedString),
+                }),
+                c => text.push(c),
+            }
+        }
+
+        Err(ErrorKind::UnterminatedString)
+    }
+}
+
+
+// the opposite of Args::text(), so any text survives the trip:
+fn quote(text: &str) -> String {
+    let mut out = String::from('"');
+
+    for c in text.chars() {
+        match c {
+            '"'  => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c    => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
@@ -0,0 +1,325 @@
+// Variants derive macro!
+// `#[derive(Variants)]` on an enum (with or without fields) generates a companion enum that's just
+// the variants' names, with none of their data, plus everything for listing, counting and parsing them:
+//
+// ? #[derive(Variants)]
+// ? #[variants(kind = Kind)]        // the companion's name, <Name>Kind if it isn't given
+// ? pub enum Message {
+// ?     Quit,
+// ?     Move { x: i32, y: i32 },
+// ?     Write(String),
+// ? }
+//
+// * Generated:
+// ? #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+// ? pub enum Kind { Quit, Move, Write }
+// - Kind::ALL, Kind::COUNT:        every variant, in order, and how many there are.
+// - kind.name():                   "Move".
+// - kind.field_count():            how many fields the variant has (2 for Move).
+// - Display and FromStr:           by name. parsing ignores case, `_` and `-`, so "move",
+//                                  "MOVE" and "change-color" all work. errors list the names.
+// - Message::VARIANTS:             the same as Kind::ALL, on the enum itself.
+// - message.kind():                which variant it is.
+// - message.variant_name():        its name.
+// - Message::from_kind(kind):      Some(the variant) if it has no fields, None if it needs data.
+// - From<&Message> for Kind.
+//
+// there's no syn or quote here (like builder-derive, and with the same derive-tokens helpers), so the
+// enum is parsed straight from the tokens, and the generated code is written as a string and parsed back.
+// generic enums aren't supported.
+
+use derive_tokens::{attribute, split_top_level, visibility};
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+
+/// ```
+/// use variants_derive::Variants;
+///
+/// #[derive(Variants)]
+/// enum Shape {
+///     Circle(f64),
+///     Square { side: f64 },
+///     Point,
+/// }
+///
+/// assert_eq!(ShapeKind::ALL, [ShapeKind::Circle, ShapeKind::Square, ShapeKind::Point]);
+/// assert_eq!(Shape::Circle(1.0).kind(), ShapeKind::Circle);
+/// assert_eq!("square".parse(), Ok(ShapeKind::Square));
+/// ```
+///
+/// it only works on enums:
+/// ```compile_fail
+/// use variants_derive::Variants;
+///
+/// #[derive(Variants)]
+/// struct Point { x: i32, y: i32 }
+/// ```
+///
+/// and `kind` is the only option:
+/// ```compile_fail
+/// use variants_derive::Variants;
+///
+/// #[derive(Variants)]
+/// #[variants(name = Kind)]
+/// enum Light { Red, Green }
+/// ```
+#[proc_macro_derive(Variants, attributes(variants))]
+pub fn derive_variants(input: TokenStream) -> TokenStream {
+    match parse(input) {
+        Ok(input) => generate(&input).parse().expect("the generated code is valid Rust"),
+        Err(message) => format!("compile_error!({message:?});").parse().expect("compile_error! is valid Rust"),
+    }
+}
+
+
+struct Input {
+    vis:      String,
+    name:     String,
+    kind:     String,
+    variants: Vec<Variant>,
+}
+
+struct Variant {
+    name:   String,
+    fields: Fields,
+}
+
+enum Fields {
+    Unit,
+    Tuple(usize),
+    Named(usize),
+}
+
+impl Fields {
+    fn count(&self) -> usize {
+        match self {
+            Fields::Unit     => 0,
+            Fields::Tuple(n) => *n,
+            Fields::Named(n) => *n,
+        }
+    }
+
+    // a pattern that matches the variant whatever its fields are:
+    fn wildcard(&self) -> &'static str {
+        match self {
+            Fields::Unit     => "",
+            Fields::Tuple(_) => "(..)",
+            Fields::Named(_) => "{ .. }",
+        }
+    }
+}
+
+
+// * parsing:
+// ? #[attributes] pub enum Name { #[attributes] Variant, Variant(Type, ...), Variant { field: Type, ... }, Variant = 3, ... }
+
+fn parse(input: TokenStream) -> Result<Input, String> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut i = 0;
+    let mut kind = None;
+
+    while let Some((args, next)) = attribute(&tokens, i, "variants") {
+        for arg in args.into_iter().flatten() {
+            match arg.split_once('=') {
+                Some((key, name)) if key.trim() == "kind" => kind = Some(name.trim().trim_matches('"').to_string()),
+                _ => return Err(format!("unknown #[variants] option: {arg:?} (expected `kind = <Name>`)")),
+            }
+        }
+        i = next;
+    }
+
+    let (vis, next) = visibility(&tokens, i);
+    i = next;
+
+    match tokens.get(i) {
+        Some(TokenTree::Ident(ident)) if ident.to_string() == "enum" => i += 1,
+        _ => return Err("#[derive(Variants)] only works on enums".to_string()),
+    }
+
+    let name = match tokens.get(i) {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err("expected the enum's name".to_string()),
+    };
+
+    match tokens.get(i + 1) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            let variants = variants(group.stream())?;
+            if variants.is_empty() {
+                return Err(format!("#[derive(Variants)] needs at least one variant, `{name}` has none"));
+            }
+
+            Ok(Input { vis, kind: kind.unwrap_or_else(|| format!("{name}Kind")), name, variants })
+        },
+        Some(TokenTree::Punct(p)) if p.as_char() == '<' => {
+            Err(format!("#[derive(Variants)] doesn't support generic enums like `{name}` yet"))
+        },
+        _ => Err(format!("expected the body of `{name}`")),
+    }
+}
+
+
+fn variants(body: TokenStream) -> Result<Vec<Variant>, String> {
+    let tokens: Vec<TokenTree> = body.into_iter().collect();
+    let mut variants = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        // #[doc], #[default]... don't matter here:
+        while let Some((_, next)) = attribute(&tokens, i, "variants") {
+            i = next;
+        }
+
+        let name = match tokens.get(i) {
+            Some(TokenTree::Ident(ident)) => ident.to_string(),
+            _ => return Err("expected a variant name".to_string()),
+        };
+        i += 1;
+
+        let fields = match tokens.get(i) {
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => Fields::Tuple(count_fields(g.stream())),
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace       => Fields::Named(count_fields(g.stream())),
+            _ => Fields::Unit,
+        };
+        if !matches!(fields, Fields::Unit) {
+            i += 1;
+        }
+
+        // skip an explicit discriminant (`= 3`), up to the comma:
+        while let Some(token) = tokens.get(i) {
+            if matches!(token, TokenTree::Punct(p) if p.as_char() == ',') {
+                break;
+            }
+            i += 1;
+        }
+
+        // FooBar and Foo_Bar would parse from the same text:
+        if let Some(other) = variants.iter().find(|v: &&Variant| normalize(&v.name) == normalize(&name)) {
+            return Err(format!("the variants `{}` and `{name}` have the same name once case and `_` are ignored", other.name));
+        }
+
+        variants.push(Variant { name, fields });
+        i += 1;  // the comma
+    }
+
+    Ok(variants)
+}
+
+
+// the commas that aren't inside <...> separate the fields (`HashMap<K, V>` is one field):
+fn count_fields(stream: TokenStream) -> usize {
+    let tokens: Vec<TokenTree> = stream.into_iter().collect();
+    split_top_level(&tokens).len()
+}
+
+
+// * generating:
+// for `enum Message { Quit, Move { x: i32, y: i32 } }` with `kind = Kind`:
+// ? pub enum Kind { Quit, Move }
+// ? impl Kind { pub const ALL: [Kind; 2] = [...]; pub const COUNT: usize = 2; pub fn name(&self) ... }
+// ? impl Display for Kind { ... }
+// ? impl FromStr for Kind { type Err = KindParseError; ... }
+// ? pub struct KindParseError { pub input: String }
+// ? impl Message { pub const VARIANTS: &'static [Kind] = &Kind::ALL; pub fn kind(&self) -> Kind { ... } ... }
+// ? impl From<&Message> for Kind { ... }
+
+fn generate(input: &Input) -> String {
+    let Input { vis, name, kind, variants } = input;
+    let error = format!("{kind}ParseError");
+    let count = variants.len();
+
+    let names: Vec<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+    let display: Vec<&str> = names.iter().map(|n| n.trim_start_matches("r#")).collect();  // r#Type is "Type"
+    let all: Vec<String> = names.iter().map(|v| format!("{kind}::{v}")).collect();
+    let arms = |f: &dyn Fn(&Variant) -> String| variants.iter().map(f).collect::<Vec<_>>().join(" ");
+
+    let name_arms = arms(&|v| format!("{kind}::{} => {:?},", v.name, v.name.trim_start_matches("r#")));
+    let field_arms = arms(&|v| format!("{kind}::{} => {},", v.name, v.fields.count()));
+    let parse_arms = arms(&|v| format!("{:?} => ::core::result::Result::Ok({kind}::{}),", normalize(&v.name), v.name));
+    let kind_arms = arms(&|v| format!("{name}::{}{} => {kind}::{0},", v.name, v.fields.wildcard()));
+    let from_kind_arms = arms(&|v| match v.fields {
+        Fields::Unit => format!("{kind}::{0} => ::core::option::Option::Some({name}::{0}),", v.name),
+        _            => format!("{kind}::{} => ::core::option::Option::None,", v.name),
+    });
+
+    let mut out = String::new();
+
+    out += &format!(
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]\n\
+         {vis} enum {kind} {{ {} }}\n",
+        names.join(", ")
+    );
+
+    out += &format!(
+        "impl {kind} {{\n\
+             {vis} const ALL: [{kind}; {count}] = [{}];\n\
+             {vis} const COUNT: usize = {count};\n\
+             {vis} const fn name(&self) -> &'static str {{ match self {{ {name_arms} }} }}\n\
+             {vis} const fn field_count(&self) -> usize {{ match self {{ {field_arms} }} }}\n\
+         }}\n",
+        all.join(", ")
+    );
+
+    out += &format!(
+        "impl ::core::fmt::Display for {kind} {{\n\
+             fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {{ f.write_str(self.name()) }}\n\
+         }}\n"
+    );
+
+    // the input goes through the same normalize() as the names did, at compile time:
+    out += &format!(
+        "impl ::core::str::FromStr for {kind} {{\n\
+             type Err = {error};\n\
+             fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {{\n\
+                 let normalized: ::std::string::String = s.trim().chars()\n\
+                     .filter(|c| *c != '_' && *c != '-')\n\
+                     .map(|c| c.to_ascii_lowercase())\n\
+                     .collect();\n\
+                 match normalized.as_str() {{\n\
+                     {parse_arms}\n\
+                     _ => ::core::result::Result::Err({error} {{ input: ::std::string::ToString::to_string(s) }}),\n\
+                 }}\n\
+             }}\n\
+         }}\n"
+    );
+
+    out += &format!(
+        "#[derive(Debug, Clone, PartialEq, Eq)]\n\
+         {vis} struct {error} {{ {vis} input: ::std::string::String }}\n\
+         impl ::core::fmt::Display for {error} {{\n\
+             fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {{\n\
+                 write!(f, \"unknown {name} variant {{:?}} (expected {})\", self.input)\n\
+             }}\n\
+         }}\n\
+         impl ::std::error::Error for {error} {{}}\n",
+        list(&display)
+    );
+
+    out += &format!(
+        "impl {name} {{\n\
+             {vis} const VARIANTS: &'static [{kind}] = &{kind}::ALL;\n\
+             {vis} fn kind(&self) -> {kind} {{ match self {{ {kind_arms} }} }}\n\
+             {vis} fn variant_name(&self) -> &'static str {{ self.kind().name() }}\n\
+             {vis} fn from_kind(kind: {kind}) -> ::core::option::Option<Self> {{ match kind {{ {from_kind_arms} }} }}\n\
+         }}\n\
+         impl ::core::convert::From<&{name}> for {kind} {{\n\
+             fn from(value: &{name}) -> Self {{ value.kind() }}\n\
+         }}\n"
+    );
+
+    out
+}
+
+
+// ChangeColor => changecolor, so "change_color", "change-color" and "CHANGECOLOR" all match it:
+fn normalize(name: &str) -> String {
+    name.trim_start_matches("r#").chars().filter(|&c| c != '_' && c != '-').map(|c| c.to_ascii_lowercase()).collect()
+}
+
+// ["A", "B", "C"] => "A, B or C"
+fn list(names: &[&str]) -> String {
+    match names {
+        [] => String::new(),
+        [one] => one.to_string(),
+        [rest @ .., last] => format!("{} or {last}", rest.join(", ")),
+    }
+}
@@ -0,0 +1,104 @@
+// the generated code, on a sample enum with every kind of variant.
+
+use std::collections::HashSet;
+
+use variants_derive::Variants;
+
+
+#[derive(Debug, Clone, PartialEq, Variants)]
+#[variants(kind = Kind)]
+pub enum Message {
+    Quit,
+    Move { x: i32, y: i32 },
+    Write(String),
+    ChangeColor(i32, i32, i32),
+    r#Type,
+}
+
+fn examples() -> [Message; 5] {
+    [
+        Message::Quit,
+        Message::Move { x: 1, y: 2 },
+        Message::Write("hi".to_string()),
+        Message::ChangeColor(1, 2, 3),
+        Message::r#Type,
+    ]
+}
+
+
+#[test]
+fn variants_are_listed_in_order() {
+    assert_eq!(Kind::ALL, [Kind::Quit, Kind::Move, Kind::Write, Kind::ChangeColor, Kind::r#Type]);
+    assert_eq!(Kind::COUNT, 5);
+    assert_eq!(Message::VARIANTS, &Kind::ALL);
+
+    let names: Vec<&str> = Kind::ALL.iter().map(Kind::name).collect();
+    assert_eq!(names, ["Quit", "Move", "Write", "ChangeColor", "Type"]);
+
+    let fields: Vec<usize> = Kind::ALL.iter().map(Kind::field_count).collect();
+    assert_eq!(fields, [0, 2, 1, 3, 0]);
+}
+
+#[test]
+fn display_and_from_str_round_trip() {
+    for kind in Kind::ALL {
+        assert_eq!(kind.to_string().parse::<Kind>(), Ok(kind));
+    }
+
+    for (input, kind) in [("move", Kind::Move), ("QUIT", Kind::Quit), ("change-color", Kind::ChangeColor),
+                          (" change_color ", Kind::ChangeColor), ("type", Kind::r#Type)] {
+        assert_eq!(input.parse::<Kind>(), Ok(kind), "{input:?}");
+    }
+}
+
+#[test]
+fn from_str_errors_keep_the_input_and_list_the_names() {
+    let e = "jump".parse::<Kind>().unwrap_err();
+    assert_eq!(e, KindParseError { input: "jump".to_string() });
+
+    let message = e.to_string();
+    assert!(message.contains("\"jump\""), "{message}");
+    for name in ["Quit", "Move", "Write", "ChangeColor", "Type"] {
+        assert!(message.contains(name), "{message}");
+    }
+    assert!(!message.contains("r#"), "{message}");
+
+    assert!("".parse::<Kind>().is_err());
+    assert!("move x".parse::<Kind>().is_err());
+}
+
+#[test]
+fn every_message_maps_to_its_own_kind() {
+    let kinds: Vec<Kind> = examples().iter().map(Message::kind).collect();
+    assert_eq!(kinds, Kind::ALL);
+
+    for message in examples() {
+        assert_eq!(Kind::from(&message), message.kind());
+        assert_eq!(message.variant_name(), message.kind().name());
+    }
+
+    let distinct: HashSet<Kind> = kinds.into_iter().collect();
+    assert_eq!(distinct.len(), Kind::COUNT);
+}
+
+#[test]
+fn only_variants_without_fields_come_from_a_kind() {
+    assert_eq!(Message::from_kind(Kind::Quit), Some(Message::Quit));
+    assert_eq!(Message::from_kind(Kind::r#Type), Some(Message::r#Type));
+    assert_eq!(Message::from_kind(Kind::Move), None);
+    assert_eq!(Message::from_kind(Kind::Write), None);
+}
+
+
+// commas inside generics and function pointer types don't separate fields:
+#[derive(Variants)]
+pub enum Nested {
+    Map(std::collections::HashMap<String, Vec<(u8, u8)>>, u8),
+    Callback { f: fn(i32, i32) -> i32, name: String, },
+}
+
+#[test]
+fn generic_fields_count_once() {
+    assert_eq!(NestedKind::Map.field_count(), 2);
+    assert_eq!(NestedKind::Callback.field_count(), 2);
+}
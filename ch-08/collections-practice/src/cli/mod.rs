@@ -0,0 +1,6 @@
+// Command line tools, run with `cargo run -- <tool> ...`:
+// - pig-latin: translate text to Pig Latin and back, see pig_latin.rs.
+// - stats: statistics of a stream of numbers too big to keep in memory, see stats.rs.
+
+pub mod pig_latin;
+pub mod stats;
@@ -0,0 +1,63 @@
+// Command line interface for the Pig Latin translator.
+// ? cargo run -- pig-latin [<file|->] [--reverse]
+//
+// translates a file (or stdin, without one or with "-") and prints it on stdout, a line at a time,
+// so it can sit in the middle of a pipe or be typed into:
+// ? echo "Good morning, world!" | cargo run -- pig-latin                     => Ood-gay orning-may, orld-way!
+// ? echo "Ood-gay orning-may, orld-way!" | cargo run -- pig-latin --reverse  => Good morning, world!
+//
+// --reverse can't always know which word it was (see pig_latin.rs): "Ello-hay" comes back as "Ello", not "Hello".
+// bytes that aren't UTF-8 come out as '\u{FFFD}' (the replacement char),
+// and the rest of the line still gets translated.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process;
+
+use collections_practice::pig_latin;
+
+
+const USAGE: &str = "usage: pig-latin [<file|->] [--reverse]";
+
+
+pub fn run(args: &[String]) {
+    if let Err(e) = execute(args) {
+        eprintln!("Error: {e}");
+        process::exit(1);
+    }
+}
+
+
+fn execute(args: &[String]) -> Result<(), String> {
+    let reverse = args.iter().any(|a| a == "--reverse");
+    let rest: Vec<&str> = args.iter().map(String::as_str).filter(|&a| a != "--reverse").collect();
+
+    let mut reader: Box<dyn BufRead> = match rest[..] {
+        [] | ["-"]                       => Box::new(io::stdin().lock()),
+        [flag] if flag.starts_with("--") => return Err(format!("unknown option {flag}\n{USAGE}")),
+        [path]                           => {
+            Box::new(BufReader::new(File::open(path).map_err(|e| format!("couldn't read {path}: {e}"))?))
+        },
+        _                                => return Err(USAGE.to_string()),
+    };
+    let translate = match reverse {
+        true  => pig_latin::reverse,
+        false => pig_latin::translate,
+    };
+
+    let mut stdout = io::stdout().lock();
+    let mut line = Vec::new();
+
+    // words never go across lines, so each line can be translated on its own.
+    // read as bytes, since read_line() gives up on the whole input at the first line that isn't UTF-8:
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line).map_err(|e| e.to_string())? == 0 {
+            return Ok(());
+        }
+        let text = String::from_utf8_lossy(&line);
+
+        // flushed every line, so it keeps up with whatever's on the other end of the pipe:
+        stdout.write_all(translate(&text).as_bytes()).and_then(|_| stdout.flush()).map_err(|e| e.to_string())?;
+    }
+}
@@ -0,0 +1,139 @@
+// Command line interface for the streaming statistics.
+// ? cargo run -- stats <file|-> [--every <n>] [--quantiles <p,p,...>]
+//
+// * the input is numbers separated by whitespace, usually one per line:
+// ? 12.5
+// ? 13
+// ? # blank lines and lines starting with # are skipped
+// ? 11.75
+//
+// a file of "-" reads from stdin. it's read a line at a time and nothing is kept but the accumulators
+// (see stats/streaming.rs), so the input can be as big as it likes, or never end:
+// ? tail -f latency.log | cargo run -- stats - --every 1000
+//
+// the summary is printed at the end, and every <n> lines along the way with --every.
+// --quantiles are percentiles from 0 to 100, estimated (50, 90 and 99 if it isn't given).
+// something that isn't a number is reported (on stderr) and skipped, it doesn't stop the rest.
+// neither does a line that isn't UTF-8: the whole line counts as one skipped value.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::process;
+
+use collections_practice::stats::Stream;
+
+
+const USAGE: &str = "usage: stats <file|-> [--every <n>] [--quantiles <p,p,...>]";
+const DEFAULT_QUANTILES: [f64; 3] = [50.0, 90.0, 99.0];
+
+
+pub fn run(args: &[String]) {
+    if let Err(e) = execute(args) {
+        eprintln!("Error: {e}");
+        process::exit(1);
+    }
+}
+
+
+struct Options {
+    path:      String,
+    every:     Option<u64>,
+    quantiles: Vec<f64>,
+}
+
+fn options(args: &[String]) -> Result<Options, String> {
+    let mut path = None;
+    let mut every = None;
+    let mut quantiles = DEFAULT_QUANTILES.to_vec();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value\n{USAGE}"));
+
+        match arg.as_str() {
+            "--every"     => {
+                let value = value()?;
+                every = match value.parse() {
+                    Ok(0) | Err(_) => return Err(format!("--every needs a whole number above 0, not '{value}'")),
+                    Ok(n)          => Some(n),
+                };
+            },
+            "--quantiles" => {
+                let value = value()?;
+                quantiles = value.split(',').map(|p| {
+                    p.trim().parse().ok().filter(|p| (0.0..=100.0).contains(p))
+                        .ok_or(format!("--quantiles needs percentiles from 0 to 100, not '{}'", p.trim()))
+                }).collect::<Result<_, _>>()?;
+            },
+            flag if flag.starts_with("--") => return Err(format!("unknown option {flag}\n{USAGE}")),
+            _ if path.is_some()            => return Err(USAGE.to_string()),
+            _                              => path = Some(arg.clone()),
+        }
+    }
+
+    Ok(Options { path: path.ok_or(USAGE)?, every, quantiles })
+}
+
+
+fn execute(args: &[String]) -> Result<(), String> {
+    let options = options(args)?;
+
+    let mut reader: Box<dyn BufRead> = match options.path.as_str() {
+        "-"  => Box::new(io::stdin().lock()),
+        path => Box::new(BufReader::new(File::open(path).map_err(|e| format!("couldn't read {path}: {e}"))?)),
+    };
+
+    let ps: Vec<f64> = options.quantiles.iter().map(|p| p / 100.0).collect();
+    let mut stream = Stream::new(&ps).expect("checked in options()");
+    let mut skipped = 0;
+
+    // one buffer for every line, instead of a new one each time.
+    // it's bytes, since read_line() would give up on the whole input at the first line that isn't UTF-8:
+    let mut line = Vec::new();
+    let mut number: u64 = 0;
+
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line).map_err(|e| format!("line {}: {e}", number + 1))? == 0 {
+            break;
+        }
+        number += 1;
+
+        let text = match std::str::from_utf8(&line) {
+            Ok(text) => text,
+            Err(_)   => {
+                eprintln!("line {number}: isn't valid UTF-8, skipping it");
+                skipped += 1;
+                ""
+            },
+        };
+
+        let content = text.trim();
+        let words = match content.starts_with('#') {
+            true  => "",
+            false => content,
+        };
+
+        for word in words.split_whitespace() {
+            // "NaN" and "inf" parse, but would spoil everything after them:
+            match word.parse::<f64>().ok().filter(|n| n.is_finite()) {
+                Some(n) => stream.push(n),
+                None    => {
+                    eprintln!("line {number}: '{word}' isn't a number, skipping it");
+                    skipped += 1;
+                },
+            }
+        }
+
+        if options.every.is_some_and(|every| number.is_multiple_of(every)) {
+            println!("After {number} lines:\n{stream}\n");
+        }
+    }
+
+    println!("{stream}");
+    if skipped > 0 {
+        println!("Skipped:  {skipped}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,8 @@
+// Collections practice!
+// The chapter's exercises, grown into small libraries.
+
+pub mod pig_latin;
+pub mod stats;
+
+#[cfg(test)]
+mod test_support;
@@ -11,41 +11,63 @@
 //    Then let the user retrieve a list of all people in a department or all people
 //    in the company by department, sorted alphabetically.
 
-use::std::collections::HashMap;
+mod cli;
 
+use std::env;
 
-fn main() {
-    let (median, mode) = exercise1(vec![3, 4, 1, 7, 9, 3, 2, 7, 5, 3, 8, 6]);
-
-    println!("Exercise 1:");
-    println!("Median: {median}.");
-    println!("Mode: {mode}.");
+use collections_practice::pig_latin;
+use collections_practice::stats::{self, Histogram, Stream};
 
-    exercise3::ex3_main();
-}
 
+fn main() {
+    // * `cargo run -- <tool> ...` runs one of the command line tools instead of the exercises:
+    let args: Vec<String> = env::args().skip(1).collect();
 
-fn exercise1(mut numbers: Vec<i32>) -> (i32, i32) {
-    numbers.sort();
-    let size = numbers.len();
+    match args.first().map(String::as_str) {
+        Some("pig-latin") => return cli::pig_latin::run(&args[1..]),
+        Some("stats")     => return cli::stats::run(&args[1..]),
+        _                 => (),
+    }
 
-    let median = {
-        if size % 2 == 0 {
-            (numbers[size / 2] + numbers[(size / 2) + 1]) / 2
-        } else {
-            numbers[size / 2]
-        }
-    };
+    let numbers = [3, 4, 1, 7, 9, 3, 2, 7, 5, 3, 8, 6];
 
-    let mut num_count: HashMap<i32, i32> = HashMap::new();
+    println!("Exercise 1:");
+    // the median of an even number of values is halfway between the two middle ones (so not always whole),
+    // and the mode is the most common *value*, not how many times it's there:
+    println!("Median: {}.", stats::median(&numbers).expect("there are numbers"));
+    println!("Mode: {:?}.", stats::modes(&numbers));
+
+    // and the rest of the statistics, for any kind of number:
+    println!();
+    println!("{}", stats::summary(&numbers).expect("there are numbers"));
+    println!("90th percentile: {}", stats::percentile(&numbers, 90.0).expect("in range"));
+    print!("{:.1}", Histogram::new(&[2.5, 3.1, 3.3, 4.0, 4.2, 4.8, 7.5], 3).expect("there are numbers"));
+
+    // nothing to work with is None or an Err, not a panic:
+    let empty: [f64; 0] = [];
+    println!("{:?} {:?}", stats::median(&empty), stats::percentile(&empty, 50.0));
+
+    // the same, a number at a time (the quantiles are estimates, but only a few numbers are kept for them):
+    println!();
+    let mut stream = Stream::new(&[0.5, 0.9]).expect("in range");
+    for n in (1..=1000).map(|n| (n * 37 % 1000) as f64) {
+        stream.push(n);
+    }
+    println!("{stream}");
 
-    for i in &numbers {
-        let key = num_count.entry(*i).or_insert(0);
-        *key += 1;
+    println!();
+    println!("Exercise 2:");
+    for word in ["first", "apple", "string", "Queen", "rhythm"] {
+        println!("{word} => {}", pig_latin::word(word));
     }
 
-    let mode = num_count.values().max().unwrap();
-    (median, *mode)
+    // whole sentences keep their capitals and punctuation, and can be turned back:
+    let sentence = "Don't stop the music, Amir! ¿Qué pasó, señor?";
+    let translated = pig_latin::translate(sentence);
+    println!("{translated}");
+    println!("{}", pig_latin::reverse(&translated));
+
+    exercise3::ex3_main();
 }
 
 
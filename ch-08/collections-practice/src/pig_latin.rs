@@ -0,0 +1,281 @@
+// Pig Latin.
+// exercise 2: the consonants at the start of each word move to the end, followed by "ay",
+// and words that start with a vowel just get "-hay":
+//
+// ? pig_latin::translate("first")             => "irst-fay"
+// ? pig_latin::translate("string")            => "ing-stray"   (the whole cluster moves, not just one letter)
+// ? pig_latin::translate("Apple, please!")    => "Apple-hay, ease-play!"
+// ? pig_latin::reverse("Ease-play!")          => "Please!"
+//
+// * Rules:
+// - a word is a run of letters (any alphabet, it's UTF-8 safe), with apostrophes allowed
+//   between letters ("don't" => "on't-day"). everything else is copied as it is.
+//   combining accents stay with the letter before them, so "cafe\u{301}" (café, with the accent
+//   as a char of its own) is one word too.
+// - "qu" moves together ("queen" => "een-quay"), and "y" is a consonant at the start of
+//   a word but a vowel after one ("yes" => "es-yay", "rhythm" => "ythm-rhay").
+// - a word with no vowels at all keeps its letters and gets "-ay" ("hmm" => "hmm-ay").
+//   the vowels are the Latin alphabet's (accents included), so that's every word in Greek or Japanese too.
+// - capitalisation is kept: "String" => "Ing-stray", "STRING" => "ING-STRAY".
+//   any other mix of cases keeps each letter's case, wherever it moves to:
+//   "iPhone" => "iPhone-hay", "McDonald" => "onald-McDay".
+//
+// * reverse() undoes translate(), and leaves alone anything that isn't Pig Latin.
+// it can't always tell what the word was: "at-hay" is both "at" and "hat". it picks the vowel one ("at").
+
+const VOWELS: &str = "aeiouàáâãäåæèéêëìíîïòóôõöøœùúûüāēīōūăĕĭŏŭ";
+
+
+pub fn translate(text: &str) -> String {
+    split(text).into_iter().map(|(is_word, s)| match is_word {
+        true  => word(s),
+        false => s.to_string(),
+    }).collect()
+}
+
+// * a single word (no punctuation or spaces in it):
+pub fn word(word: &str) -> String {
+    let case = Case::of(word);
+    let letters = case.normalise(word);
+
+    let translated = match cluster_len(&letters) {
+        0                       => format!("{letters}-hay"),
+        n if n == letters.len() => format!("{letters}-ay"),
+        n                       => format!("{}-{}ay", &letters[n..], &letters[..n]),
+    };
+
+    case.apply(&translated)
+}
+
+pub fn reverse(text: &str) -> String {
+    let parts = split(text);
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < parts.len() {
+        // a translated word is two words with a "-" between them:
+        if let [(true, rest), (false, "-"), (true, suffix), ..] = parts[i..]
+            && let Some(original) = unword(rest, suffix)
+        {
+            out.push_str(&original);
+            i += 3;
+            continue;
+        }
+
+        out.push_str(parts[i].1);
+        i += 1;
+    }
+
+    out
+}
+
+// the word that `rest-suffix` was translated from, if it's Pig Latin at all:
+fn unword(rest: &str, suffix: &str) -> Option<String> {
+    let case = Case::of(&format!("{rest}{suffix}"));
+    let (rest, suffix) = (case.normalise(rest), case.normalise(suffix));
+    let cluster = suffix.strip_suffix("ay")?;
+
+    let original = match cluster == "h" && cluster_len(&rest) == 0 {
+        true  => rest.clone(),
+        false => format!("{cluster}{rest}"),
+    };
+
+    // translating it back is the easiest way to check it really came from there:
+    (word(&original) == format!("{rest}-{suffix}")).then(|| case.apply(&original))
+}
+
+
+// how many bytes of the word are consonants before the first vowel:
+fn cluster_len(word: &str) -> usize {
+    let mut chars = word.char_indices().map(|(i, c)| (i, lower(c))).peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let vowel = VOWELS.contains(c) || (c == 'y' && i > 0);
+
+        match c {
+            _ if vowel => return i,
+            // the u in "qu" goes with the q:
+            'q' => if let Some(&(_, 'u')) = chars.peek() {
+                chars.next();
+            },
+            _ => (),
+        }
+    }
+
+    word.len()
+}
+
+// good enough to tell vowels from consonants ('İ' lowercases to two chars, the first one is the letter):
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+// * splits text into words and what's between them, as (is it a word, the text):
+// ? "Don't stop!"  => [(true, "Don't"), (false, " "), (true, "stop"), (false, "!")]
+fn split(text: &str) -> Vec<(bool, &str)> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_word = false;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let next_is_letter = chars.peek().is_some_and(|&(_, next)| next.is_alphabetic());
+        let letter = c.is_alphabetic() || (in_word && (is_mark(c) || (matches!(c, '\'' | '’') && next_is_letter)));
+
+        if letter != in_word && i > start {
+            parts.push((in_word, &text[start..i]));
+            start = i;
+        }
+        in_word = letter;
+    }
+
+    if start < text.len() {
+        parts.push((in_word, &text[start..]));
+    }
+
+    parts
+}
+
+// the std library has no Unicode categories, so these are the blocks of combining marks
+// (accents and such, added onto the char before them) that Latin, Greek and Cyrillic text uses:
+fn is_mark(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'    // combining diacritical marks
+        | '\u{1AB0}'..='\u{1AFF}'  // ... extended
+        | '\u{1DC0}'..='\u{1DFF}'  // ... supplement
+        | '\u{20D0}'..='\u{20FF}'  // ... for symbols
+        | '\u{FE20}'..='\u{FE2F}'  // combining half marks
+    )
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Case {
+    Lower,
+    Title,
+    Upper,
+    Mixed,  // anything else, like "iPhone": every letter keeps its own case
+}
+
+impl Case {
+    // a single capital letter ("I", "A") is a capitalised word, not a shouted one:
+    fn of(word: &str) -> Case {
+        let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+        let upper = |c: &char| c.is_uppercase();
+
+        match letters[..] {
+            [_, _, ..] if letters.iter().all(upper)                            => Case::Upper,
+            [first, ref rest @ ..] if upper(&first) && !rest.iter().any(upper) => Case::Title,
+            _ if letters.iter().any(upper)                                     => Case::Mixed,
+            _                                                                  => Case::Lower,
+        }
+    }
+
+    // what the word gets translated from: lowercase, unless the case has to go along with the letters:
+    fn normalise(self, word: &str) -> String {
+        match self {
+            Case::Mixed => word.to_string(),
+            _           => word.to_lowercase(),
+        }
+    }
+
+    fn apply(self, word: &str) -> String {
+        match self {
+            Case::Mixed => word.to_string(),
+            Case::Lower => word.to_lowercase(),
+            Case::Upper => word.to_uppercase(),
+            Case::Title => {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+                    None        => String::new(),
+                }
+            },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_follow_the_rules() {
+        let cases = [
+            ("first",  "irst-fay"),
+            ("string", "ing-stray"),
+            ("queen",  "een-quay"),
+            ("square", "are-squay"),
+            ("yes",    "es-yay"),
+            ("rhythm", "ythm-rhay"),
+            ("hmm",    "hmm-ay"),
+            ("apple",  "apple-hay"),
+            ("don't",  "on't-day"),
+            ("über",   "über-hay"),
+            ("γεια",   "γεια-ay"),
+        ];
+
+        for (original, translated) in cases {
+            assert_eq!(translate(original), translated, "translating {original:?}");
+        }
+    }
+
+    #[test]
+    fn only_words_are_translated() {
+        assert_eq!(translate("Apple, please!"), "Apple-hay, ease-play!");
+        assert_eq!(translate("  don't-stop 42\n"), "  on't-day-op-stay 42\n");
+        assert_eq!(translate("'quoted'"), "'oted-quay'");
+        assert_eq!(translate(""), "");
+        assert_eq!(reverse("Not pig latin - at all"), "Not pig latin - at all");
+    }
+
+    // words that don't start with "h" and a vowel, or "-hay" would be ambiguous:
+    #[test]
+    fn reverse_undoes_translate() {
+        let texts = [
+            "string", "queen", "rhythm", "hmm", "don't", "yes", "apple",
+            "The quick brown fox jumps over the lazy dog.",
+            "Don't STOP believing, iPhone users!",
+        ];
+
+        for text in texts {
+            assert_eq!(reverse(&translate(text)), text, "round trip of {text:?}");
+        }
+    }
+
+    #[test]
+    fn case_is_kept() {
+        let cases = [
+            ("string",   "ing-stray"),
+            ("String",   "Ing-stray"),
+            ("STRING",   "ING-STRAY"),
+            ("I",        "I-hay"),
+            ("Apple",    "Apple-hay"),
+            ("iPhone",   "iPhone-hay"),
+            ("McDonald", "onald-McDay"),
+            ("eBay",     "eBay-hay"),
+            ("QUeen",    "een-QUay"),
+        ];
+
+        for (original, translated) in cases {
+            assert_eq!(translate(original), translated, "translating {original:?}");
+            assert_eq!(reverse(translated), original, "reversing {translated:?}");
+        }
+    }
+
+    #[test]
+    fn combining_marks_stay_in_the_word() {
+        assert_eq!(split("cafe\u{301}!"), [(true, "cafe\u{301}"), (false, "!")]);
+        assert_eq!(translate("cafe\u{301} au lait"), "afe\u{301}-cay au-hay ait-lay");
+        assert_eq!(translate("c\u{327}a va"), "a-c\u{327}ay a-vay");
+        assert_eq!(translate("E\u{301}cole"), "E\u{301}cole-hay");
+
+        // a mark with no letter before it isn't a word:
+        assert_eq!(split("\u{301}a"), [(false, "\u{301}"), (true, "a")]);
+
+        for text in ["cafe\u{301}", "c\u{327}a va", "E\u{301}cole"] {
+            assert_eq!(reverse(&translate(text)), text);
+        }
+    }
+}
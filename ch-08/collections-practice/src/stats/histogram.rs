@@ -0,0 +1,88 @@
+// Histograms.
+// the range from the smallest value to the largest is split into equal-width bins,
+// and each value is counted in the bin it falls into:
+//
+// ? print!("{:.0}", Histogram::new(&[1, 2, 2, 3, 9], 4)?);
+// ? [1, 3)  ████████████████████████████████████████ 3
+// ? [3, 5)  █████████████ 1
+// ? [5, 7)  0
+// ? [7, 9]  █████████████ 1
+//
+// the precision is used for the bins' edges (2 decimal places if it isn't given).
+//
+// * every bin includes its start but not its end, except the last one, which also includes the
+// largest value (otherwise it wouldn't be in any bin). if every value is the same, they're all in the first bin.
+
+use std::fmt;
+
+use super::{Number, StatsError};
+
+
+// how wide the longest bar is when it's displayed:
+const BAR_WIDTH: usize = 40;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bin {
+    pub start: f64,
+    pub end:   f64,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub bins: Vec<Bin>,
+}
+
+impl Histogram {
+    pub fn new<T: Number>(data: &[T], bins: usize) -> Result<Self, StatsError> {
+        if bins == 0 {
+            return Err(StatsError::NoBins);
+        }
+
+        let min = super::min(data).ok_or(StatsError::Empty)?.to_f64();
+        let max = super::max(data).ok_or(StatsError::Empty)?.to_f64();
+        let width = (max - min) / bins as f64;
+
+        let mut histogram = Histogram {
+            bins: (0..bins)
+                .map(|i| Bin { start: min + width * i as f64, end: min + width * (i + 1) as f64, count: 0 })
+                .collect(),
+        };
+
+        for n in data {
+            let index = match width > 0.0 {
+                true  => ((n.to_f64() - min) / width) as usize,
+                false => 0,
+            };
+            // the largest value would get an index one past the end:
+            histogram.bins[index.min(bins - 1)].count += 1;
+        }
+
+        Ok(histogram)
+    }
+
+    pub fn total(&self) -> usize {
+        self.bins.iter().map(|b| b.count).sum()
+    }
+}
+
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let highest = self.bins.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+        let precision = f.precision().unwrap_or(2);
+
+        let labels: Vec<String> = self.bins.iter().enumerate().map(|(i, bin)| {
+            let close = match i == self.bins.len() - 1 { true => ']', false => ')' };
+            format!("[{:.precision$}, {:.precision$}{close}", bin.start, bin.end)
+        }).collect();
+        let label_width = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+
+        for (label, bin) in labels.iter().zip(&self.bins) {
+            let bar = "█".repeat(bin.count * BAR_WIDTH / highest);
+            writeln!(f, "{label:<label_width$}  {bar}{}{}", if bar.is_empty() { "" } else { " " }, bin.count)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,304 @@
+// Statistics.
+// exercise 1 (the median and mode of a list of integers), for any kind of number:
+//
+// ? stats::median(&[3, 1, 2])          => Some(2.0)
+// ? stats::modes(&[1, 2, 2, 3, 3])     => [2, 3]   (every value that's tied for most common)
+// ? stats::percentile(&data, 90.0)     => Ok(...)
+//
+// * Empty input:
+// nothing can be said about an empty list, so instead of panicking:
+// - the single-number results (mean, median, variance...) are an Option, None when it's empty.
+// - the ones that also take a parameter that can be wrong (percentile, histogram) are a Result.
+// - modes() is a Vec, which is just empty.
+//
+// the results are f64s whatever the input is (the median of [1, 2] is 1.5, not 1).
+// all of these need the whole list at once, streaming.rs has the ones that take numbers one at a time.
+
+pub mod histogram;
+pub mod streaming;
+
+use std::cmp::Ordering;
+use std::fmt;
+
+pub use histogram::Histogram;
+pub use streaming::{Quantile, Running, Stream};
+
+
+pub trait Number: Copy + PartialEq + PartialOrd + fmt::Debug + fmt::Display {
+    fn to_f64(self) -> f64;
+
+    // an order for sorting that never gives up (floats use total_cmp, so NaN sorts last):
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+macro_rules! integer {
+    ($($t:ident),*) => {$(
+        impl Number for $t {
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                self.cmp(other)
+            }
+        }
+    )*};
+}
+
+macro_rules! float {
+    ($($t:ident),*) => {$(
+        impl Number for $t {
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                $t::total_cmp(self, other)
+            }
+        }
+    )*};
+}
+
+integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+float!(f32, f64);
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsError {
+    Empty,
+    PercentileOutOfRange(f64),  // has to be from 0 to 100
+    NoBins,                     // a histogram needs at least one bin
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatsError::Empty                   => write!(f, "there are no numbers to work with"),
+            StatsError::PercentileOutOfRange(p) => write!(f, "percentile {p} is out of range, it has to be from 0 to 100"),
+            StatsError::NoBins                  => write!(f, "a histogram needs at least one bin"),
+        }
+    }
+}
+
+impl std::error::Error for StatsError {}
+
+
+pub fn sorted<T: Number>(data: &[T]) -> Vec<T> {
+    let mut sorted = data.to_vec();
+    sorted.sort_by(T::total_cmp);
+    sorted
+}
+
+pub fn min<T: Number>(data: &[T]) -> Option<T> {
+    data.iter().copied().min_by(T::total_cmp)
+}
+
+pub fn max<T: Number>(data: &[T]) -> Option<T> {
+    data.iter().copied().max_by(T::total_cmp)
+}
+
+pub fn sum<T: Number>(data: &[T]) -> f64 {
+    data.iter().map(|n| n.to_f64()).sum()
+}
+
+pub fn mean<T: Number>(data: &[T]) -> Option<f64> {
+    match data.len() {
+        0 => None,
+        n => Some(sum(data) / n as f64),
+    }
+}
+
+// * the middle value once they're sorted, or halfway between the two middle ones
+// if there's an even number of them: [1, 2, 3, 4] => 2.5
+pub fn median<T: Number>(data: &[T]) -> Option<f64> {
+    let sorted = sorted(data);
+    let middle = sorted.len() / 2;
+
+    match sorted.len() {
+        0               => None,
+        n if n % 2 == 1 => Some(sorted[middle].to_f64()),
+        _               => Some((sorted[middle - 1].to_f64() + sorted[middle].to_f64()) / 2.0),
+    }
+}
+
+// * the most common values (there can be more than one), smallest first.
+// every value is a mode if none of them repeat.
+pub fn modes<T: Number>(data: &[T]) -> Vec<T> {
+    let counts = counts(data);
+    let highest = counts.iter().map(|&(_, count)| count).max().unwrap_or(0);
+
+    counts.into_iter().filter(|&(_, count)| count == highest).map(|(value, _)| value).collect()
+}
+
+// every distinct value and how many times it's there, smallest first:
+pub fn counts<T: Number>(data: &[T]) -> Vec<(T, usize)> {
+    let mut counts: Vec<(T, usize)> = Vec::new();
+
+    // once they're sorted, equal values are next to each other:
+    for value in sorted(data) {
+        match counts.last_mut() {
+            Some((last, count)) if last.total_cmp(&value) == Ordering::Equal => *count += 1,
+            _ => counts.push((value, 1)),
+        }
+    }
+
+    counts
+}
+
+// * how spread out the values are: the average squared distance from the mean.
+// this is the population variance (dividing by n), sample_variance() divides by n - 1.
+pub fn variance<T: Number>(data: &[T]) -> Option<f64> {
+    let mean = mean(data)?;
+    Some(squared_distances(data, mean) / data.len() as f64)
+}
+
+// * for when the data is a sample of something bigger: dividing by n - 1 instead of n
+// corrects for the sample's mean being closer to its own values than the real mean is.
+// needs at least two values.
+pub fn sample_variance<T: Number>(data: &[T]) -> Option<f64> {
+    let mean = mean(data).filter(|_| data.len() > 1)?;
+    Some(squared_distances(data, mean) / (data.len() - 1) as f64)
+}
+
+pub fn std_dev<T: Number>(data: &[T]) -> Option<f64> {
+    variance(data).map(f64::sqrt)
+}
+
+pub fn sample_std_dev<T: Number>(data: &[T]) -> Option<f64> {
+    sample_variance(data).map(f64::sqrt)
+}
+
+fn squared_distances<T: Number>(data: &[T], mean: f64) -> f64 {
+    data.iter().map(|n| (n.to_f64() - mean).powi(2)).sum()
+}
+
+// * the value `p` percent of the way through the sorted data (0 is the smallest, 100 the largest,
+// 50 the median). between two values, it's interpolated: the 25th percentile of [0, 10] is 2.5.
+pub fn percentile<T: Number>(data: &[T], p: f64) -> Result<f64, StatsError> {
+    percentile_of_sorted(&sorted(data), p)
+}
+
+// several percentiles, sorting the data only once:
+pub fn percentiles<T: Number>(data: &[T], ps: &[f64]) -> Result<Vec<f64>, StatsError> {
+    let sorted = sorted(data);
+    ps.iter().map(|&p| percentile_of_sorted(&sorted, p)).collect()
+}
+
+fn percentile_of_sorted<T: Number>(sorted: &[T], p: f64) -> Result<f64, StatsError> {
+    if !(0.0..=100.0).contains(&p) {
+        return Err(StatsError::PercentileOutOfRange(p));
+    }
+    if sorted.is_empty() {
+        return Err(StatsError::Empty);
+    }
+
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+    let fraction = rank - below as f64;
+
+    Ok(sorted[below].to_f64() + (sorted[above].to_f64() - sorted[below].to_f64()) * fraction)
+}
+
+// * everything at once, for printing:
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary<T> {
+    pub count:   usize,
+    pub min:     T,
+    pub max:     T,
+    pub mean:    f64,
+    pub median:  f64,
+    pub modes:   Vec<T>,
+    pub std_dev: f64,
+}
+
+pub fn summary<T: Number>(data: &[T]) -> Option<Summary<T>> {
+    Some(Summary {
+        count:   data.len(),
+        min:     min(data)?,
+        max:     max(data)?,
+        mean:    mean(data)?,
+        median:  median(data)?,
+        modes:   modes(data),
+        std_dev: std_dev(data)?,
+    })
+}
+
+impl<T: Number> fmt::Display for Summary<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modes: Vec<String> = self.modes.iter().map(|m| m.to_string()).collect();
+
+        writeln!(f, "Count:    {}", self.count)?;
+        writeln!(f, "Min:      {}", self.min)?;
+        writeln!(f, "Max:      {}", self.max)?;
+        writeln!(f, "Mean:     {:.3}", self.mean)?;
+        writeln!(f, "Median:   {}", self.median)?;
+        writeln!(f, "Mode:     {}", modes.join(", "))?;
+        write!(f, "Std dev:  {:.3}", self.std_dev)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median_of_an_even_number_is_between_the_middle_two() {
+        assert_eq!(median(&[1, 2, 3, 4]), Some(2.5));
+        assert_eq!(median(&[4, 1, 3]), Some(3.0));
+    }
+
+    #[test]
+    fn every_tied_value_is_a_mode() {
+        assert_eq!(modes(&[1, 2, 2, 3, 3]), vec![2, 3]);
+        assert_eq!(modes(&[3, 1, 2]), vec![1, 2, 3]);
+        assert_eq!(modes::<i32>(&[]), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn empty_input_is_none_or_an_error() {
+        let empty: [f64; 0] = [];
+
+        assert_eq!(mean(&empty), None);
+        assert_eq!(median(&empty), None);
+        assert_eq!(variance(&empty), None);
+        assert_eq!(sample_variance(&[1.0]), None);
+        assert_eq!(summary(&empty), None);
+        assert_eq!(percentile(&empty, 50.0), Err(StatsError::Empty));
+        assert_eq!(Histogram::new(&empty, 3), Err(StatsError::Empty));
+    }
+
+    #[test]
+    fn percentiles_outside_0_to_100_are_errors() {
+        assert_eq!(percentile(&[1, 2], -1.0), Err(StatsError::PercentileOutOfRange(-1.0)));
+        assert_eq!(percentile(&[1, 2], 100.5), Err(StatsError::PercentileOutOfRange(100.5)));
+        assert!(matches!(percentile(&[1, 2], f64::NAN), Err(StatsError::PercentileOutOfRange(p)) if p.is_nan()));
+
+        assert_eq!(percentile(&[0, 10], 25.0), Ok(2.5));
+        assert_eq!(percentiles(&[0, 10], &[0.0, 100.0]), Ok(vec![0.0, 10.0]));
+    }
+
+    #[test]
+    fn variance_and_std_dev() {
+        let data = [2, 4, 4, 4, 5, 5, 7, 9];
+
+        assert_eq!(variance(&data), Some(4.0));
+        assert_eq!(std_dev(&data), Some(2.0));
+        assert_eq!(sample_variance(&data), Some(32.0 / 7.0));
+    }
+
+    #[test]
+    fn histogram_bins() {
+        assert_eq!(Histogram::new(&[1, 2, 3], 0), Err(StatsError::NoBins));
+
+        let histogram = Histogram::new(&[1, 2, 2, 3, 9], 4).unwrap();
+        let counts: Vec<usize> = histogram.bins.iter().map(|b| b.count).collect();
+        assert_eq!(counts, [3, 1, 0, 1]);
+
+        // no width at all, so they all go in the first bin:
+        let same = Histogram::new(&[5.0, 5.0, 5.0], 3).unwrap();
+        let counts: Vec<usize> = same.bins.iter().map(|b| b.count).collect();
+        assert_eq!(counts, [3, 0, 0]);
+        assert_eq!(same.total(), 3);
+    }
+}
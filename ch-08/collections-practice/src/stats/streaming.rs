@@ -0,0 +1,404 @@
+// Streaming statistics.
+// everything in stats/mod.rs needs the whole list in memory (the median even sorts a copy of it).
+// these take the numbers one at a time instead, and only keep a few numbers of their own,
+// so they work on inputs of any size, like a file that doesn't fit in memory or a never-ending stdin:
+//
+// ? let mut stats = Running::new();
+// ? for n in numbers { stats.push(n); }
+// ? stats.mean()   // Some(...), or None if there weren't any
+//
+// * Accumulators:
+// - Running:   count, sum, mean, variance, min and max, all exact.
+// - Quantile:  one quantile (like the median, p = 0.5), estimated with the P² algorithm.
+// - Stream:    a Running and a few Quantiles together, for printing a summary.
+//
+// numbers should be finite: a NaN or an infinity makes the results NaN or infinite from then on.
+
+use std::fmt;
+
+
+// * Welford's algorithm keeps the mean and the sum of squared distances from it (m2) up to date
+// as each number arrives. the obvious way (sum of squares - square of the sum) subtracts two huge,
+// nearly equal numbers, and loses most of its precision on big inputs; this doesn't.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Running {
+    count: u64,
+    mean:  f64,
+    m2:    f64,
+    min:   f64,
+    max:   f64,
+}
+
+impl Running {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, x: f64) {
+        self.count += 1;
+        if self.count == 1 {
+            (self.min, self.max) = (x, x);
+        } else {
+            self.min = self.min.min(x);
+            self.max = self.max.max(x);
+        }
+
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    // * combines two accumulators, as if one had seen the other's numbers too
+    // (so parts of the input can be worked on separately, and put together after):
+    pub fn merge(&mut self, other: &Running) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count as f64 * other.count as f64 / count as f64);
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.count = count;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.mean * self.count as f64
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        self.some(self.mean)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.some(self.max)
+    }
+
+    pub fn variance(&self) -> Option<f64> {
+        self.some(self.m2 / self.count as f64)
+    }
+
+    // dividing by n - 1 (see stats::sample_variance), so it needs at least two numbers:
+    pub fn sample_variance(&self) -> Option<f64> {
+        (self.count > 1).then(|| self.m2 / (self.count - 1) as f64)
+    }
+
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    fn some(&self, value: f64) -> Option<f64> {
+        (self.count > 0).then_some(value)
+    }
+}
+
+
+// * The P² algorithm (Jain and Chlamtac, 1985):
+// it keeps five markers: the smallest value, the largest, the quantile it's after, and one halfway
+// on each side of it. each marker has a height (a value) and a position (how many values are at or below it).
+// every new number moves the positions along, and when a marker drifts away from where it should be
+// (p of the way through for the middle one), its height is adjusted along a parabola through its neighbours.
+// that's five numbers of memory whatever the input size, and the estimate is usually very close.
+// until there are five numbers, it just keeps them and gives the exact answer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantile {
+    p:         f64,
+    count:     u64,
+    heights:   [f64; 5],
+    positions: [f64; 5],  // the actual positions, 1-based
+    desired:   [f64; 5],  // where they should be
+    increment: [f64; 5],  // how much `desired` moves for each number
+}
+
+impl Quantile {
+    // None unless 0 <= p <= 1:
+    pub fn new(p: f64) -> Option<Self> {
+        (0.0..=1.0).contains(&p).then_some(Self {
+            p,
+            count:     0,
+            heights:   [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired:   [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increment: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        })
+    }
+
+    pub fn p(&self) -> f64 {
+        self.p
+    }
+
+    pub fn push(&mut self, x: f64) {
+        self.count += 1;
+
+        // the first five are just kept (sorted), they become the markers' heights:
+        if self.count <= 5 {
+            let filled = self.count as usize - 1;
+            let at = self.heights[..filled].partition_point(|&h| h <= x);
+            self.heights.copy_within(at..filled, at + 1);
+            self.heights[at] = x;
+            return;
+        }
+
+        let h = &mut self.heights;
+
+        // which cell between the markers it falls into (stretching the ends if it's outside them):
+        let cell = if x < h[0] {
+            h[0] = x;
+            0
+        } else if x >= h[4] {
+            h[4] = x;
+            3
+        } else {
+            (1..5).find(|&i| x < h[i]).expect("x < h[4]") - 1
+        };
+
+        for position in &mut self.positions[cell + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increment) {
+            *desired += increment;
+        }
+
+        for i in 1..4 {
+            let drift = self.desired[i] - self.positions[i];
+            let room_right = self.positions[i + 1] - self.positions[i];
+            let room_left = self.positions[i - 1] - self.positions[i];
+
+            if (drift >= 1.0 && room_right > 1.0) || (drift <= -1.0 && room_left < -1.0) {
+                let d = drift.signum();
+                let parabolic = self.parabolic(i, d);
+
+                self.heights[i] = match self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] {
+                    true  => parabolic,
+                    false => self.linear(i, d),  // the parabola overshot a neighbour
+                };
+                self.positions[i] += d;
+            }
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn estimate(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            // exact, interpolated like stats::percentile:
+            n @ 1..=5 => {
+                let kept = &self.heights[..n as usize];
+                let rank = self.p * (n - 1) as f64;
+                let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+                Some(kept[below] + (kept[above] - kept[below]) * (rank - below as f64))
+            },
+            // the end markers are exactly the smallest and largest (the middle one never gets all the way out to them):
+            _ if self.p == 0.0 => Some(self.heights[0]),
+            _ if self.p == 1.0 => Some(self.heights[4]),
+            _                  => Some(self.heights[2]),
+        }
+    }
+
+    fn parabolic(&self, i: usize, d: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+
+        q[i] + d / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, d: f64) -> f64 {
+        let j = if d > 0.0 { i + 1 } else { i - 1 };
+        self.heights[i] + d * (self.heights[j] - self.heights[i]) / (self.positions[j] - self.positions[i])
+    }
+}
+
+
+// * a Running and some Quantiles, fed together:
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stream {
+    pub running:   Running,
+    pub quantiles: Vec<Quantile>,
+}
+
+impl Stream {
+    // None if one of the quantiles isn't from 0 to 1:
+    pub fn new(quantiles: &[f64]) -> Option<Self> {
+        Some(Self {
+            running:   Running::new(),
+            quantiles: quantiles.iter().map(|&p| Quantile::new(p)).collect::<Option<_>>()?,
+        })
+    }
+
+    pub fn push(&mut self, x: f64) {
+        self.running.push(x);
+        for quantile in &mut self.quantiles {
+            quantile.push(x);
+        }
+    }
+}
+
+impl fmt::Display for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r = &self.running;
+        let show = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.3}"));
+
+        writeln!(f, "Count:    {}", r.count())?;
+        writeln!(f, "Mean:     {}", show(r.mean()))?;
+        writeln!(f, "Std dev:  {}", show(r.std_dev()))?;
+        writeln!(f, "Min:      {}", show(r.min()))?;
+        write!(f, "Max:      {}", show(r.max()))?;
+
+        for quantile in &self.quantiles {
+            write!(f, "\n{:<9} ~{}", format!("p{}:", percent(quantile.p())), show(quantile.estimate()))?;
+        }
+
+        Ok(())
+    }
+}
+
+// p * 100 isn't always exact (0.07 * 100.0 == 7.000000000000001), so it's rounded,
+// and the zeros left at the end are trimmed: 0.07 => "7", 0.999 => "99.9".
+fn percent(p: f64) -> String {
+    let rounded = format!("{:.3}", p * 100.0);
+    rounded.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats;
+    use crate::test_support::Random;
+
+    // -1000 to 1000, with a fraction:
+    fn number(random: &mut Random) -> f64 {
+        random.below(2_000_000) as f64 / 1000.0 - 1000.0
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+    }
+
+    fn running(data: &[f64]) -> Running {
+        let mut running = Running::new();
+        for &x in data {
+            running.push(x);
+        }
+        running
+    }
+
+    #[test]
+    fn running_matches_the_whole_list_functions() {
+        let mut random = Random(0x5EED);
+
+        for len in [1, 2, 3, 10, 100, 1000] {
+            let data: Vec<f64> = (0..len).map(|_| number(&mut random)).collect();
+            let r = running(&data);
+
+            assert_eq!(r.count(), len as u64);
+            assert!(close(r.sum(), stats::sum(&data)));
+            assert!(close(r.mean().unwrap(), stats::mean(&data).unwrap()));
+            assert!(close(r.variance().unwrap(), stats::variance(&data).unwrap()));
+            assert_eq!(r.sample_variance().is_some(), len > 1);
+            if let (Some(a), Some(b)) = (r.sample_variance(), stats::sample_variance(&data)) {
+                assert!(close(a, b));
+            }
+            assert_eq!(r.min(), stats::min(&data));
+            assert_eq!(r.max(), stats::max(&data));
+        }
+
+        assert_eq!(Running::new().mean(), None);
+        assert_eq!(Running::new().variance(), None);
+    }
+
+    #[test]
+    fn merging_halves_is_one_pass() {
+        let mut random = Random(0xD1CE);
+        let data: Vec<f64> = (0..500).map(|_| number(&mut random)).collect();
+        let whole = running(&data);
+
+        for split in [0, 1, 250, 499, 500] {
+            let (left, right) = data.split_at(split);
+            let mut merged = running(left);
+            merged.merge(&running(right));
+
+            assert_eq!(merged.count(), whole.count());
+            assert_eq!(merged.min(), whole.min());
+            assert_eq!(merged.max(), whole.max());
+            assert!(close(merged.mean().unwrap(), whole.mean().unwrap()));
+            assert!(close(merged.variance().unwrap(), whole.variance().unwrap()));
+        }
+    }
+
+    #[test]
+    fn quantiles_of_five_or_fewer_are_exact() {
+        let mut random = Random(0xFEED);
+
+        for len in 1..=5 {
+            let data: Vec<f64> = (0..len).map(|_| number(&mut random)).collect();
+
+            for p in [0.0, 0.1, 0.25, 0.5, 0.9, 1.0] {
+                let mut quantile = Quantile::new(p).unwrap();
+                for &x in &data {
+                    quantile.push(x);
+                }
+
+                let expected = stats::percentile(&data, p * 100.0).unwrap();
+                assert!(close(quantile.estimate().unwrap(), expected), "p{p} of {data:?}");
+            }
+        }
+
+        assert_eq!(Quantile::new(0.5).unwrap().estimate(), None);
+        assert_eq!(Quantile::new(1.5), None);
+        assert_eq!(Quantile::new(-0.1), None);
+    }
+
+    #[test]
+    fn quantiles_of_a_big_input_are_close() {
+        let mut random = Random(0xBEEF);
+
+        // 0 to 9999 in a random order (Fisher-Yates), so the true quantiles are known:
+        let mut data: Vec<f64> = (0..10_000).map(f64::from).collect();
+        for i in (1..data.len()).rev() {
+            data.swap(i, random.below(i + 1));
+        }
+
+        let ps = [0.0, 0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99, 1.0];
+        let mut stream = Stream::new(&ps).unwrap();
+        for &x in &data {
+            stream.push(x);
+        }
+
+        for quantile in &stream.quantiles {
+            let expected = stats::percentile(&data, quantile.p() * 100.0).unwrap();
+            let estimate = quantile.estimate().unwrap();
+
+            // within 1% of the range:
+            assert!((estimate - expected).abs() <= 100.0, "p{}: {estimate} isn't close to {expected}", quantile.p());
+        }
+    }
+
+    #[test]
+    fn percentile_labels_are_rounded() {
+        let stream = Stream::new(&[0.0, 0.07, 0.5, 0.999, 1.0]).unwrap();
+        let shown = stream.to_string();
+        let labels: Vec<&str> = shown.lines().skip(5).map(|l| l.split(' ').next().unwrap()).collect();
+
+        assert_eq!(labels, ["p0:", "p7:", "p50:", "p99.9:", "p100:"]);
+    }
+}